@@ -169,6 +169,7 @@ export default function PreviewPanel() {
 
     return executePreviewTrace({
       nodes: rustNodes,
+      edges: edges.map(edge => ({ source: edge.source, target: edge.target })),
       variables: rustVars,
       outputStyle: "labeled",
     });
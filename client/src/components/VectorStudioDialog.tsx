@@ -275,6 +275,7 @@ export function VectorStudioDialog(props: {
                       </div>
                       <div className="text-[11px] text-muted-foreground font-mono truncate">
                         {c.dimension} · {c.distance}
+                        {c.quantization ? ` · ${c.quantization}` : ""}
                       </div>
                     </button>
                   ))
@@ -6,8 +6,16 @@ export type ExecuteNode = {
   label: string;
   kind: string;
   content: string;
+  disabled?: boolean;
 };
 
+export type ExecuteEdge = {
+  source: string;
+  target: string;
+};
+
+export type IsolatedNodePolicy = "include" | "skip" | "error";
+
 export type ExecuteVariable = {
   id: string;
   name: string;
@@ -24,6 +32,8 @@ export type ExecuteVariableSpec = {
 
 export async function executeTrace(input: {
   nodes: ExecuteNode[];
+  edges?: ExecuteEdge[];
+  isolatedNodePolicy?: IsolatedNodePolicy;
   variables: ExecuteVariable[];
   outputStyle: TraceOutputStyle;
 }): Promise<TraceRun> {
@@ -36,6 +46,8 @@ export async function executeTrace(input: {
 
 export async function executePreviewTrace(input: {
   nodes: ExecuteNode[];
+  edges?: ExecuteEdge[];
+  isolatedNodePolicy?: IsolatedNodePolicy;
   variables: ExecuteVariableSpec[];
   outputStyle: TraceOutputStyle;
 }): Promise<TraceRun> {
@@ -36,3 +36,35 @@ export async function embedToVectorJob(input: {
     body: JSON.stringify(input),
   });
 }
+
+export type IngestDocument = {
+  id: string;
+  text: string;
+  title?: string;
+  metadata?: Record<string, unknown>;
+};
+
+export async function ingestDocumentsJob(input: {
+  providerId: string;
+  collection: string;
+  documents?: IngestDocument[];
+  dataSourceId?: string;
+  query?: string;
+  idField?: string;
+  textField?: string;
+  titleField?: string;
+  metadataFields?: string[];
+  chunking?: {
+    strategy?: "tokens" | "headings";
+    chunkSize?: number;
+    overlap?: number;
+  };
+  batchSize?: number;
+  pruneMissing?: boolean;
+}): Promise<{ job: JobRecord }> {
+  return requestJson<{ job: JobRecord }>("/api/jobs/ingest-documents", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(input),
+  });
+}
@@ -1,10 +1,20 @@
 import { requestJson } from "./client";
 
+export type ProviderKind =
+  | "siliconflow"
+  | "openai_compatible"
+  | "openai"
+  | "deepseek"
+  | "ollama"
+  | "vllm"
+  | "lmstudio";
+
 export type Provider = {
   id: string;
   name: string;
-  provider: string;
+  provider: ProviderKind;
   baseUrl: string;
+  authHeader?: string;
   defaultChatModel?: string;
   defaultEmbeddingModel?: string;
   updatedAt: string;
@@ -16,9 +26,10 @@ export async function listProviders(): Promise<Provider[]> {
 
 export async function createProvider(input: {
   name: string;
-  provider: "siliconflow";
-  baseUrl: string;
-  apiKey: string;
+  provider: ProviderKind;
+  baseUrl?: string;
+  apiKey?: string;
+  authHeader?: string;
   defaultChatModel?: string;
   defaultEmbeddingModel?: string;
 }): Promise<Provider> {
@@ -35,6 +46,7 @@ export async function updateProvider(
     name?: string;
     baseUrl?: string;
     apiKey?: string;
+    authHeader?: string;
     defaultChatModel?: string;
     defaultEmbeddingModel?: string;
   }
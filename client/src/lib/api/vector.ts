@@ -1,9 +1,17 @@
 import { requestJson } from "./client";
+import { ApiError } from "./types";
+
+export type VectorDistance = "cosine" | "dot" | "euclidean";
+
+export type VectorQuantization = "none" | "int8" | "binary";
 
 export type VectorCollection = {
   name: string;
   dimension: number;
-  distance: string;
+  distance: VectorDistance;
+  quantization?: VectorQuantization;
+  payloadIndexes?: { field: string; schema: PayloadSchema }[];
+  embeddingModel?: string;
   createdAt: string;
 };
 
@@ -14,7 +22,9 @@ export async function listVectorCollections(): Promise<VectorCollection[]> {
 export async function createVectorCollection(input: {
   name: string;
   dimension: number;
-  distance: "cosine";
+  distance: VectorDistance;
+  quantization?: VectorQuantization;
+  embeddingModel?: string;
 }): Promise<VectorCollection> {
   return requestJson<VectorCollection>("/api/vector/collections/create", {
     method: "POST",
@@ -34,6 +44,7 @@ export async function upsertVectorPoints(input: {
   collection: string;
   points: VectorPoint[];
   batchId?: string;
+  embeddingModel?: string;
 }): Promise<{ upserted: number }> {
   return requestJson<{ upserted: number }>("/api/vector/points/upsert", {
     method: "POST",
@@ -42,19 +53,66 @@ export async function upsertVectorPoints(input: {
   });
 }
 
+export type VectorFieldCondition = {
+  key: string;
+  match?:
+    | { value: unknown }
+    | { any: unknown[] }
+    | { except: unknown[] }
+    | { text: string };
+  range?: {
+    gt?: number | string;
+    gte?: number | string;
+    lt?: number | string;
+    lte?: number | string;
+  };
+  exists?: boolean;
+};
+
+export type VectorCondition =
+  | VectorFieldCondition
+  | { hasId: (string | number)[] }
+  | { isEmpty: { key: string } }
+  | { isNull: { key: string } }
+  | VectorFilter;
+
 export type VectorFilter = {
-  must?: { key: string; match: { value: unknown } }[];
+  must?: VectorCondition[];
+  should?: VectorCondition[];
+  mustNot?: VectorCondition[];
+};
+
+export type PayloadSchema = "keyword" | "number" | "datetime" | "text";
+
+export type VectorSearchHit = {
+  id: string;
+  score: number;
+  vectorScore?: number;
+  keywordScore?: number;
+  payload: unknown;
 };
 
 export async function searchVector(input: {
   collection: string;
-  vector: number[];
+  vector?: number[];
   topK?: number;
   filter?: VectorFilter;
-}): Promise<{ hits: { id: string; score: number; payload: unknown }[] }> {
-  return requestJson<{
-    hits: { id: string; score: number; payload: unknown }[];
-  }>("/api/vector/search", {
+  exact?: boolean;
+  ef?: number;
+  distance?: VectorDistance;
+  rescore?: boolean;
+  oversampling?: number;
+  query?: string;
+  providerId?: string;
+  embeddingModel?: string;
+  mode?: "vector" | "keyword" | "hybrid";
+  textField?: string;
+  fusion?: "rrf" | "weighted";
+  alpha?: number;
+  rrfK?: number;
+  mmr?: { lambda?: number; fetchK?: number };
+}): Promise<{ hits: VectorSearchHit[] }> {
+  return requestJson<{ hits: VectorSearchHit[] }>("/api/vector/search", {
     method: "POST",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify(input),
@@ -72,3 +130,138 @@ export async function deleteVectorPoints(input: {
     body: JSON.stringify(input),
   });
 }
+
+export async function createPayloadIndex(input: {
+  collection: string;
+  field: string;
+  schema: PayloadSchema;
+}): Promise<VectorCollection> {
+  return requestJson<VectorCollection>("/api/vector/collections/index", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(input),
+  });
+}
+
+export async function deletePayloadIndex(input: {
+  collection: string;
+  field: string;
+}): Promise<VectorCollection> {
+  return requestJson<VectorCollection>("/api/vector/collections/index/delete", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(input),
+  });
+}
+
+export async function dropVectorCollection(collection: string): Promise<{
+  collection: string;
+  dropped: boolean;
+}> {
+  return requestJson<{ collection: string; dropped: boolean }>(
+    "/api/vector/collections/delete",
+    {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ collection }),
+    }
+  );
+}
+
+export type PayloadFieldStats = {
+  points: number;
+  types: Record<string, number>;
+  index?: PayloadSchema;
+};
+
+export type VectorCollectionStats = {
+  collection: VectorCollection;
+  points: number;
+  records: number;
+  deadRecords: number;
+  generation: number;
+  bytes: { vectors: number; payloads: number; index: number };
+  payloadSchema: Record<string, PayloadFieldStats>;
+};
+
+export async function getVectorCollectionStats(
+  collection: string
+): Promise<VectorCollectionStats> {
+  return requestJson<VectorCollectionStats>("/api/vector/collections/stats", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ collection }),
+  });
+}
+
+export type VectorPointRecord = {
+  id: string;
+  vector?: number[];
+  payload: unknown;
+  batchId?: string;
+};
+
+export async function scrollVectorPoints(input: {
+  collection: string;
+  filter?: VectorFilter;
+  limit?: number;
+  cursor?: string | null;
+  withVector?: boolean;
+}): Promise<{ points: VectorPointRecord[]; nextCursor: string | null }> {
+  return requestJson<{ points: VectorPointRecord[]; nextCursor: string | null }>(
+    "/api/vector/points/scroll",
+    {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(input),
+    }
+  );
+}
+
+export async function getVectorPoints(input: {
+  collection: string;
+  ids: string[];
+  withVector?: boolean;
+}): Promise<{ points: VectorPointRecord[] }> {
+  return requestJson<{ points: VectorPointRecord[] }>("/api/vector/points/get", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(input),
+  });
+}
+
+export async function snapshotVectorCollection(collection: string): Promise<Blob> {
+  const res = await fetch("/api/vector/collections/snapshot", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ collection }),
+  });
+  if (!res.ok) {
+    throw new ApiError(`HTTP ${res.status}`, {
+      kind: "http",
+      status: res.status,
+      url: res.url,
+      bodyText: await res.text(),
+    });
+  }
+  return res.blob();
+}
+
+export async function restoreVectorCollection(
+  archive: Blob,
+  options: { name?: string; overwrite?: boolean } = {}
+): Promise<{ collection: VectorCollection; points: number }> {
+  const params = new URLSearchParams();
+  if (options.name) params.set("name", options.name);
+  if (options.overwrite) params.set("overwrite", "true");
+  const query = params.toString();
+  return requestJson<{ collection: VectorCollection; points: number }>(
+    `/api/vector/collections/restore${query ? `?${query}` : ""}`,
+    {
+      method: "POST",
+      headers: { "Content-Type": "application/octet-stream" },
+      body: archive,
+    },
+    { timeoutMs: 120_000 }
+  );
+}
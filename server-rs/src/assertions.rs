@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+
+use crate::runs::TokenUsage;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RowExpectations {
+    #[serde(default, deserialize_with = "one_or_many")]
+    contains: Vec<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    not_contains: Vec<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    regex: Vec<String>,
+    max_tokens: Option<u64>,
+    digest: Option<String>,
+    json_schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AssertionResult {
+    pub(crate) kind: String,
+    pub(crate) expected: serde_json::Value,
+    pub(crate) passed: bool,
+    #[serde(default)]
+    pub(crate) message: Option<String>,
+}
+
+/// What a row's expectations are checked against: the completion when the
+/// row was executed, otherwise the rendered prompt. `digest` is always the
+/// digest of `text`.
+pub(crate) struct AssertionInput<'a> {
+    pub(crate) text: &'a str,
+    pub(crate) digest: &'a str,
+    pub(crate) usage: Option<&'a TokenUsage>,
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(OneOrMany::One(s)) => vec![s],
+        Some(OneOrMany::Many(v)) => v,
+    })
+}
+
+impl RowExpectations {
+    pub(crate) fn from_row(row: &serde_json::Value) -> anyhow::Result<Option<Self>> {
+        match row.get("_expect") {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(v) => Ok(Some(serde_json::from_value(v.clone())?)),
+        }
+    }
+
+    pub(crate) fn evaluate(&self, input: &AssertionInput) -> Vec<AssertionResult> {
+        let mut out = Vec::<AssertionResult>::new();
+
+        for needle in &self.contains {
+            out.push(AssertionResult {
+                kind: "contains".to_string(),
+                expected: serde_json::json!(needle),
+                passed: input.text.contains(needle.as_str()),
+                message: None,
+            });
+        }
+
+        for needle in &self.not_contains {
+            out.push(AssertionResult {
+                kind: "notContains".to_string(),
+                expected: serde_json::json!(needle),
+                passed: !input.text.contains(needle.as_str()),
+                message: None,
+            });
+        }
+
+        for pattern in &self.regex {
+            let (passed, message) = match regex::Regex::new(pattern) {
+                Ok(re) => (re.is_match(input.text), None),
+                Err(err) => (false, Some(format!("invalid_regex: {err}"))),
+            };
+            out.push(AssertionResult {
+                kind: "regex".to_string(),
+                expected: serde_json::json!(pattern),
+                passed,
+                message,
+            });
+        }
+
+        if let Some(max) = self.max_tokens {
+            let (tokens, source) = match input.usage {
+                Some(usage) => (usage.completion_tokens, "usage"),
+                None => (estimate_tokens(input.text), "estimate"),
+            };
+            out.push(AssertionResult {
+                kind: "maxTokens".to_string(),
+                expected: serde_json::json!(max),
+                passed: tokens <= max,
+                message: Some(format!("tokens={tokens} source={source}")),
+            });
+        }
+
+        if let Some(digest) = &self.digest {
+            let passed = digest == input.digest;
+            out.push(AssertionResult {
+                kind: "digest".to_string(),
+                expected: serde_json::json!(digest),
+                passed,
+                message: (!passed).then(|| format!("actual={}", input.digest)),
+            });
+        }
+
+        if let Some(schema) = &self.json_schema {
+            let (passed, message) = check_json_schema(schema, input.text);
+            out.push(AssertionResult {
+                kind: "jsonSchema".to_string(),
+                expected: schema.clone(),
+                passed,
+                message,
+            });
+        }
+
+        out
+    }
+}
+
+fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+pub(crate) fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    let rest = rest.split_once('\n').map(|(_, body)| body).unwrap_or("");
+    rest.trim_end().strip_suffix("```").unwrap_or(rest).trim()
+}
+
+fn check_json_schema(schema: &serde_json::Value, text: &str) -> (bool, Option<String>) {
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(v) => v,
+        Err(err) => return (false, Some(format!("invalid_schema: {err}"))),
+    };
+    let instance = match serde_json::from_str::<serde_json::Value>(strip_code_fence(text)) {
+        Ok(v) => v,
+        Err(err) => return (false, Some(format!("invalid_json: {err}"))),
+    };
+    let errors = validator
+        .iter_errors(&instance)
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        (true, None)
+    } else {
+        (false, Some(errors.join("; ")))
+    }
+}
@@ -60,6 +60,55 @@ impl MilvusRestClient {
     ) -> anyhow::Result<serde_json::Value> {
         self.post_json("/v2/vectordb/entities/query", body).await
     }
+
+    pub async fn create_collection(
+        &self,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/collections/create", body)
+            .await
+    }
+
+    pub async fn drop_collection(
+        &self,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/collections/drop", body).await
+    }
+
+    pub async fn describe_collection(
+        &self,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/collections/describe", body)
+            .await
+    }
+
+    pub async fn load_collection(
+        &self,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/collections/load", body).await
+    }
+
+    pub async fn release_collection(
+        &self,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/collections/release", body)
+            .await
+    }
+
+    pub async fn create_index(&self, body: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/indexes/create", body).await
+    }
+
+    pub async fn delete_entities(
+        &self,
+        body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.post_json("/v2/vectordb/entities/delete", body).await
+    }
 }
 
 #[cfg(not(feature = "milvus"))]
@@ -96,4 +145,53 @@ impl MilvusRestClient {
     ) -> anyhow::Result<serde_json::Value> {
         Err(anyhow::anyhow!("milvus feature 未启用"))
     }
+
+    pub async fn create_collection(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
+
+    pub async fn drop_collection(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
+
+    pub async fn describe_collection(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
+
+    pub async fn load_collection(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
+
+    pub async fn release_collection(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
+
+    pub async fn create_index(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
+
+    pub async fn delete_entities(
+        &self,
+        _body: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("milvus feature 未启用"))
+    }
 }
@@ -1,8 +1,494 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum GraphValue {
+    Scalar {
+        value: serde_json::Value,
+    },
+    List {
+        items: Vec<GraphValue>,
+    },
+    Map {
+        entries: BTreeMap<String, GraphValue>,
+    },
+    Node(GraphNode),
+    Relationship(GraphRelationship),
+    Path {
+        nodes: Vec<GraphNode>,
+        relationships: Vec<GraphRelationship>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    pub id: i64,
+    pub labels: Vec<String>,
+    pub properties: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphRelationship {
+    pub id: i64,
+    pub r#type: String,
+    pub start: i64,
+    pub end: i64,
+    pub properties: serde_json::Map<String, serde_json::Value>,
+}
+
+pub type GraphRow = BTreeMap<String, GraphValue>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neo4jOutputFormat {
+    Value,
+    Json,
+    Triples,
+    Table,
+}
+
+impl Neo4jOutputFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "value" => Some(Self::Value),
+            "json" => Some(Self::Json),
+            "triples" => Some(Self::Triples),
+            "table" => Some(Self::Table),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Value => "value",
+            Self::Json => "json",
+            Self::Triples => "triples",
+            Self::Table => "table",
+        }
+    }
+}
+
+impl GraphValue {
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            GraphValue::Scalar { value } => value.clone(),
+            GraphValue::List { items } => {
+                serde_json::Value::Array(items.iter().map(|v| v.to_json()).collect())
+            }
+            GraphValue::Map { entries } => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.to_json()))
+                    .collect(),
+            ),
+            GraphValue::Node(n) => serde_json::json!({
+                "id": n.id,
+                "labels": n.labels,
+                "properties": n.properties,
+            }),
+            GraphValue::Relationship(r) => serde_json::json!({
+                "id": r.id,
+                "type": r.r#type,
+                "start": r.start,
+                "end": r.end,
+                "properties": r.properties,
+            }),
+            GraphValue::Path {
+                nodes,
+                relationships,
+            } => serde_json::json!({
+                "nodes": nodes
+                    .iter()
+                    .map(|n| GraphValue::Node(n.clone()).to_json())
+                    .collect::<Vec<_>>(),
+                "relationships": relationships
+                    .iter()
+                    .map(|r| GraphValue::Relationship(r.clone()).to_json())
+                    .collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            GraphValue::Scalar { value } => match value {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            },
+            other => other.to_json().to_string(),
+        }
+    }
+
+    fn collect_graph<'a>(
+        &'a self,
+        nodes: &mut Vec<&'a GraphNode>,
+        rels: &mut Vec<&'a GraphRelationship>,
+    ) {
+        match self {
+            GraphValue::Scalar { .. } => {}
+            GraphValue::List { items } => {
+                for item in items {
+                    item.collect_graph(nodes, rels);
+                }
+            }
+            GraphValue::Map { entries } => {
+                for v in entries.values() {
+                    v.collect_graph(nodes, rels);
+                }
+            }
+            GraphValue::Node(n) => nodes.push(n),
+            GraphValue::Relationship(r) => rels.push(r),
+            GraphValue::Path {
+                nodes: path_nodes,
+                relationships,
+            } => {
+                nodes.extend(path_nodes.iter());
+                rels.extend(relationships.iter());
+            }
+        }
+    }
+}
+
+fn node_display(n: &GraphNode) -> String {
+    for key in ["name", "title", "id", "key"] {
+        if let Some(v) = n.properties.get(key) {
+            return match v {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+        }
+    }
+    match n.labels.first() {
+        Some(label) => format!("{label}#{}", n.id),
+        None => format!("#{}", n.id),
+    }
+}
+
+pub fn format_rows(rows: &[GraphRow], format: Neo4jOutputFormat) -> String {
+    match format {
+        Neo4jOutputFormat::Value => {
+            let Some(first) = rows.first() else {
+                return String::new();
+            };
+            if let Some(v @ GraphValue::Scalar { .. }) = first.get("value") {
+                return v.to_text();
+            }
+            format_rows(rows, Neo4jOutputFormat::Json)
+        }
+        Neo4jOutputFormat::Json => serde_json::Value::Array(
+            rows.iter()
+                .map(|row| {
+                    serde_json::Value::Object(
+                        row.iter().map(|(k, v)| (k.clone(), v.to_json())).collect(),
+                    )
+                })
+                .collect(),
+        )
+        .to_string(),
+        Neo4jOutputFormat::Triples => {
+            let mut nodes = Vec::<&GraphNode>::new();
+            let mut rels = Vec::<&GraphRelationship>::new();
+            for row in rows {
+                for v in row.values() {
+                    v.collect_graph(&mut nodes, &mut rels);
+                }
+            }
+            let names = nodes
+                .iter()
+                .map(|n| (n.id, node_display(n)))
+                .collect::<HashMap<_, _>>();
+            let name_of = |id: i64| names.get(&id).cloned().unwrap_or_else(|| format!("#{id}"));
+            let linked = rels
+                .iter()
+                .flat_map(|r| [r.start, r.end])
+                .collect::<HashSet<_>>();
+            let mut seen = HashSet::<String>::new();
+            let mut lines = Vec::<String>::new();
+            for r in &rels {
+                let line = format!(
+                    "({})-[{}]->({})",
+                    name_of(r.start),
+                    r.r#type,
+                    name_of(r.end)
+                );
+                if seen.insert(line.clone()) {
+                    lines.push(line);
+                }
+            }
+            for n in nodes.iter().filter(|n| !linked.contains(&n.id)) {
+                let line = format!("({})", name_of(n.id));
+                if seen.insert(line.clone()) {
+                    lines.push(line);
+                }
+            }
+            lines.join("\n")
+        }
+        Neo4jOutputFormat::Table => {
+            let mut columns = Vec::<&str>::new();
+            for row in rows {
+                for k in row.keys() {
+                    if !columns.contains(&k.as_str()) {
+                        columns.push(k);
+                    }
+                }
+            }
+            let mut lines = vec![columns.join(" | ")];
+            for row in rows {
+                lines.push(
+                    columns
+                        .iter()
+                        .map(|c| row.get(*c).map(|v| v.to_text()).unwrap_or_default())
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                );
+            }
+            lines.join("\n")
+        }
+    }
+}
+
+#[cfg(feature = "neo4j")]
+pub fn graph_value_from_bolt(v: &neo4rs::BoltType) -> GraphValue {
+    use neo4rs::BoltType;
+
+    match v {
+        BoltType::Null(_) => GraphValue::Scalar {
+            value: serde_json::Value::Null,
+        },
+        BoltType::String(s) => GraphValue::Scalar {
+            value: serde_json::Value::String(s.value.clone()),
+        },
+        BoltType::Boolean(b) => GraphValue::Scalar {
+            value: serde_json::Value::Bool(b.value),
+        },
+        BoltType::Integer(i) => GraphValue::Scalar {
+            value: serde_json::Value::Number(i.value.into()),
+        },
+        BoltType::Float(f) => GraphValue::Scalar {
+            value: serde_json::Number::from_f64(f.value)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+        },
+        BoltType::List(l) => GraphValue::List {
+            items: l.value.iter().map(graph_value_from_bolt).collect(),
+        },
+        BoltType::Map(m) => GraphValue::Map {
+            entries: m
+                .value
+                .iter()
+                .map(|(k, v)| (k.value.clone(), graph_value_from_bolt(v)))
+                .collect(),
+        },
+        BoltType::Node(n) => GraphValue::Node(graph_node_from_bolt(n)),
+        BoltType::Relation(r) => GraphValue::Relationship(GraphRelationship {
+            id: r.id.value,
+            r#type: r.typ.value.clone(),
+            start: r.start_node_id.value,
+            end: r.end_node_id.value,
+            properties: properties_from_bolt(&r.properties),
+        }),
+        BoltType::UnboundedRelation(r) => GraphValue::Relationship(GraphRelationship {
+            id: r.id.value,
+            r#type: r.typ.value.clone(),
+            start: -1,
+            end: -1,
+            properties: properties_from_bolt(&r.properties),
+        }),
+        BoltType::Path(p) => {
+            let nodes = p
+                .nodes()
+                .iter()
+                .map(graph_node_from_bolt)
+                .collect::<Vec<_>>();
+            let rels = p.rels();
+            let indices = p.indices().iter().map(|i| i.value).collect::<Vec<_>>();
+            let mut relationships = Vec::new();
+            let mut prev = nodes.first().map(|n| n.id).unwrap_or(-1);
+            for pair in indices.chunks(2) {
+                let [rel_idx, node_idx] = pair else {
+                    break;
+                };
+                let Some(rel) = usize::try_from(rel_idx.unsigned_abs())
+                    .ok()
+                    .and_then(|i| i.checked_sub(1))
+                    .and_then(|i| rels.get(i))
+                else {
+                    break;
+                };
+                let next = usize::try_from(*node_idx)
+                    .ok()
+                    .and_then(|i| nodes.get(i))
+                    .map(|n| n.id)
+                    .unwrap_or(-1);
+                let (start, end) = if *rel_idx > 0 {
+                    (prev, next)
+                } else {
+                    (next, prev)
+                };
+                relationships.push(GraphRelationship {
+                    id: rel.id.value,
+                    r#type: rel.typ.value.clone(),
+                    start,
+                    end,
+                    properties: properties_from_bolt(&rel.properties),
+                });
+                prev = next;
+            }
+            GraphValue::Path {
+                nodes,
+                relationships,
+            }
+        }
+        other => GraphValue::Scalar {
+            value: serde_json::Value::String(format!("{other:?}")),
+        },
+    }
+}
+
+#[cfg(feature = "neo4j")]
+fn graph_node_from_bolt(n: &neo4rs::BoltNode) -> GraphNode {
+    GraphNode {
+        id: n.id.value,
+        labels: n
+            .labels
+            .value
+            .iter()
+            .filter_map(|l| match l {
+                neo4rs::BoltType::String(s) => Some(s.value.clone()),
+                _ => None,
+            })
+            .collect(),
+        properties: properties_from_bolt(&n.properties),
+    }
+}
+
+#[cfg(feature = "neo4j")]
+fn properties_from_bolt(m: &neo4rs::BoltMap) -> serde_json::Map<String, serde_json::Value> {
+    m.value
+        .iter()
+        .map(|(k, v)| (k.value.clone(), graph_value_from_bolt(v).to_json()))
+        .collect()
+}
+
+#[cfg(feature = "neo4j")]
+pub async fn execute_rows(
+    uri: &str,
+    user: &str,
+    pass: &str,
+    cypher: &str,
+    params: &serde_json::Value,
+    row_limit: usize,
+) -> anyhow::Result<(Vec<GraphRow>, bool)> {
+    use neo4rs::{query, BoltType, Graph};
+
+    let graph = Graph::new(uri, user, pass).await?;
+    let mut q = query(cypher);
+    if let Some(obj) = params.as_object() {
+        for (k, v) in obj {
+            match v {
+                serde_json::Value::Null => {}
+                serde_json::Value::Bool(b) => {
+                    q = q.param(k, *b);
+                }
+                serde_json::Value::Number(n) => {
+                    if let Some(i) = n.as_i64() {
+                        q = q.param(k, i);
+                    } else if let Some(f) = n.as_f64() {
+                        q = q.param(k, f);
+                    }
+                }
+                serde_json::Value::String(s) => {
+                    q = q.param(k, s.as_str());
+                }
+                other => {
+                    q = q.param(k, other.to_string());
+                }
+            }
+        }
+    }
+
+    let mut result = graph.execute(q).await?;
+    let mut rows = Vec::<GraphRow>::new();
+    let mut truncated = false;
+    while let Some(row) = result.next().await? {
+        if rows.len() == row_limit {
+            truncated = true;
+            break;
+        }
+        let columns = row.to_strict::<HashMap<String, BoltType>>()?;
+        rows.push(
+            columns
+                .iter()
+                .map(|(k, v)| (k.clone(), graph_value_from_bolt(v)))
+                .collect(),
+        );
+    }
+    Ok((rows, truncated))
+}
+
+#[cfg(not(feature = "neo4j"))]
+pub async fn execute_rows(
+    _uri: &str,
+    _user: &str,
+    _pass: &str,
+    _cypher: &str,
+    _params: &serde_json::Value,
+    _row_limit: usize,
+) -> anyhow::Result<(Vec<GraphRow>, bool)> {
+    Err(anyhow::anyhow!("neo4j feature 未启用"))
+}
+
+pub fn escape_label(label: &str) -> String {
+    format!("`{}`", label.replace('`', "``"))
+}
+
+pub fn rows_to_json(rows: &[GraphRow]) -> Vec<serde_json::Value> {
+    rows.iter()
+        .map(|row| {
+            serde_json::Value::Object(row.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+        })
+        .collect()
+}
+
+pub fn relationship_types_from_rows(rows: &[GraphRow]) -> Vec<String> {
+    rows.iter()
+        .filter_map(|row| match row.get("relationshipType") {
+            Some(GraphValue::Scalar {
+                value: serde_json::Value::String(s),
+            }) => Some(s.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn label_properties_from_rows(rows: &[GraphRow]) -> Vec<serde_json::Value> {
+    rows_to_json(rows)
+        .into_iter()
+        .map(|row| {
+            serde_json::json!({
+                "name": row.get("propertyName").cloned().unwrap_or(serde_json::Value::Null),
+                "types": row.get("propertyTypes").cloned().unwrap_or(serde_json::json!([])),
+                "mandatory": row.get("mandatory").and_then(|v| v.as_bool()).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+pub fn preview_nodes_from_rows(rows: &[GraphRow], column: &str) -> Vec<serde_json::Value> {
+    rows.iter()
+        .filter_map(|row| row.get(column).map(GraphValue::to_json))
+        .collect()
+}
+
 #[cfg(feature = "neo4j")]
 pub async fn test_connection(uri: &str, user: &str, pass: &str) -> anyhow::Result<()> {
     use neo4rs::{query, Graph};
 
-    let graph = Graph::new(uri, user, pass)?;
+    let graph = Graph::new(uri, user, pass).await?;
     let mut result = graph.execute(query("RETURN 1")).await?;
     let _ = result.next().await?;
     Ok(())
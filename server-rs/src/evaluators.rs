@@ -0,0 +1,520 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    assertions::strip_code_fence,
+    now_ms,
+    runs::{
+        list_run_records, load_replay_executor, load_run_record, write_run_record, ReplayExecutor,
+        RunRecord,
+    },
+    AppState,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EvaluatorRecord {
+    id: String,
+    name: String,
+    prompt: String,
+    #[serde(default)]
+    rubric: Option<String>,
+    score_schema: serde_json::Value,
+    provider_id: String,
+    #[serde(default)]
+    model: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EvaluatorScore {
+    pub(crate) evaluator_id: String,
+    pub(crate) input_digest: String,
+    pub(crate) model: String,
+    pub(crate) score: Option<serde_json::Value>,
+    pub(crate) valid: bool,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+    #[serde(default)]
+    pub(crate) cached: bool,
+    pub(crate) created_at: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EvaluatorCreateRequest {
+    name: String,
+    prompt: String,
+    rubric: Option<String>,
+    score_schema: Option<serde_json::Value>,
+    provider_id: String,
+    model: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EvaluatorUpdateRequest {
+    name: Option<String>,
+    prompt: Option<String>,
+    rubric: Option<String>,
+    score_schema: Option<serde_json::Value>,
+    provider_id: Option<String>,
+    model: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EvaluateRequest {
+    pub(crate) evaluator_ids: Vec<String>,
+    #[serde(default)]
+    pub(crate) force: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScoreFieldAggregate {
+    count: u64,
+    mean: f64,
+    min: f64,
+    max: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScoreAggregate {
+    count: u64,
+    valid: u64,
+    errors: u64,
+    fields: BTreeMap<String, ScoreFieldAggregate>,
+}
+
+pub(crate) struct LoadedEvaluator {
+    record: EvaluatorRecord,
+    executor: ReplayExecutor,
+}
+
+fn evaluators_dir(state: &AppState) -> PathBuf {
+    state.data_dir.join("evaluators")
+}
+
+fn cache_path(state: &AppState, evaluator_id: &str, input_digest: &str) -> PathBuf {
+    state
+        .data_dir
+        .join("evaluator_cache")
+        .join(evaluator_id)
+        .join(format!("{input_digest}.json"))
+}
+
+async fn write_evaluator(state: &AppState, ev: &EvaluatorRecord) -> anyhow::Result<()> {
+    let dir = evaluators_dir(state);
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!("{}.json", ev.id));
+    let text = serde_json::to_string_pretty(ev)?;
+    tokio::fs::write(path, text).await?;
+    Ok(())
+}
+
+async fn load_evaluator(state: &AppState, id: &str) -> anyhow::Result<EvaluatorRecord> {
+    let path = evaluators_dir(state).join(format!("{id}.json"));
+    let text = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+fn default_score_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "required": ["score"],
+        "properties": { "score": { "type": "number" } }
+    })
+}
+
+fn error_response(status: StatusCode, error: &str, id: &str) -> axum::response::Response {
+    (
+        status,
+        Json(serde_json::json!({ "error": error, "id": id })),
+    )
+        .into_response()
+}
+
+pub(crate) async fn load_evaluators(
+    state: &AppState,
+    ids: &[String],
+) -> Result<Vec<LoadedEvaluator>, axum::response::Response> {
+    if ids.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response());
+    }
+    let mut out = Vec::<LoadedEvaluator>::new();
+    for id in ids {
+        let record = load_evaluator(state, id)
+            .await
+            .map_err(|_| error_response(StatusCode::NOT_FOUND, "evaluator_not_found", id))?;
+        let executor =
+            load_replay_executor(state, Some(&record.provider_id), record.model.as_deref())
+                .await
+                .map_err(|(status, error)| error_response(status, error, &record.provider_id))?;
+        out.push(LoadedEvaluator { record, executor });
+    }
+    Ok(out)
+}
+
+fn judge_request_body(ev: &LoadedEvaluator, run: &RunRecord) -> serde_json::Value {
+    let mut system = ev.record.prompt.clone();
+    if let Some(rubric) = ev.record.rubric.as_deref().filter(|r| !r.trim().is_empty()) {
+        system.push_str("\n\nRubric:\n");
+        system.push_str(rubric);
+    }
+    system.push_str("\n\nReply with JSON only, matching this schema:\n");
+    system.push_str(&ev.record.score_schema.to_string());
+    let user = format!(
+        "Input:\n{}\n\nOutput:\n{}",
+        run.trace.text,
+        run.output_text()
+    );
+    serde_json::json!({
+        "model": ev.executor.model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": user }
+        ],
+        "stream": false,
+    })
+}
+
+fn digest_body(body: &serde_json::Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(body.to_string().as_bytes());
+    URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
+
+fn parse_score(
+    schema: &serde_json::Value,
+    content: &str,
+) -> (Option<serde_json::Value>, bool, Option<String>) {
+    let score = match serde_json::from_str::<serde_json::Value>(strip_code_fence(content)) {
+        Ok(v) => v,
+        Err(err) => return (None, false, Some(format!("invalid_json: {err}"))),
+    };
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(v) => v,
+        Err(err) => return (Some(score), false, Some(format!("invalid_schema: {err}"))),
+    };
+    let errors = validator
+        .iter_errors(&score)
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        (Some(score), true, None)
+    } else {
+        (Some(score), false, Some(errors.join("; ")))
+    }
+}
+
+async fn score_run(
+    state: &AppState,
+    ev: &LoadedEvaluator,
+    run: &RunRecord,
+    force: bool,
+) -> EvaluatorScore {
+    let body = judge_request_body(ev, run);
+    let input_digest = digest_body(&body);
+    let path = cache_path(state, &ev.record.id, &input_digest);
+    if !force {
+        if let Ok(text) = tokio::fs::read_to_string(&path).await {
+            if let Ok(mut cached) = serde_json::from_str::<EvaluatorScore>(&text) {
+                cached.cached = true;
+                return cached;
+            }
+        }
+    }
+
+    let mut score = EvaluatorScore {
+        evaluator_id: ev.record.id.clone(),
+        input_digest,
+        model: ev.executor.model.clone(),
+        score: None,
+        valid: false,
+        error: None,
+        cached: false,
+        created_at: now_ms().to_string(),
+    };
+    match ev.executor.client.chat_completions(&body).await {
+        Ok(v) => {
+            let (content, _) = ev.executor.client.completion_content(&v);
+            (score.score, score.valid, score.error) =
+                parse_score(&ev.record.score_schema, &content);
+            if let Some(parent) = path.parent() {
+                let _ = tokio::fs::create_dir_all(parent).await;
+            }
+            if let Ok(text) = serde_json::to_string_pretty(&score) {
+                let _ = tokio::fs::write(&path, text).await;
+            }
+        }
+        Err(err) => score.error = Some(err.to_string()),
+    }
+    score
+}
+
+pub(crate) async fn evaluate_run(
+    state: &AppState,
+    evaluators: &[LoadedEvaluator],
+    run_id: &str,
+    force: bool,
+) -> anyhow::Result<RunRecord> {
+    let mut run = load_run_record(state, run_id).await?;
+    for ev in evaluators {
+        let score = score_run(state, ev, &run, force).await;
+        run.scores.retain(|s| s.evaluator_id != score.evaluator_id);
+        run.scores.push(score);
+    }
+    write_run_record(state, &run).await?;
+    Ok(run)
+}
+
+pub(crate) fn aggregate_scores<'a>(
+    scores: impl IntoIterator<Item = &'a EvaluatorScore>,
+) -> BTreeMap<String, ScoreAggregate> {
+    let mut out = BTreeMap::<String, ScoreAggregate>::new();
+    for s in scores {
+        let agg = out.entry(s.evaluator_id.clone()).or_default();
+        agg.count += 1;
+        if s.valid {
+            agg.valid += 1;
+        }
+        if s.error.is_some() {
+            agg.errors += 1;
+        }
+        let numeric = match &s.score {
+            Some(serde_json::Value::Number(n)) => vec![("score".to_string(), n.as_f64())],
+            Some(serde_json::Value::Object(map)) => map
+                .iter()
+                .filter(|(_, v)| v.is_number())
+                .map(|(k, v)| (k.clone(), v.as_f64()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (field, value) in numeric {
+            let Some(value) = value else {
+                continue;
+            };
+            let f = agg.fields.entry(field).or_default();
+            if f.count == 0 {
+                f.min = value;
+                f.max = value;
+            } else {
+                f.min = f.min.min(value);
+                f.max = f.max.max(value);
+            }
+            f.mean = (f.mean * f.count as f64 + value) / (f.count + 1) as f64;
+            f.count += 1;
+        }
+    }
+    out
+}
+
+pub(crate) async fn list_evaluators(State(state): State<AppState>) -> axum::response::Response {
+    let dir = evaluators_dir(&state);
+    let mut out = Vec::<EvaluatorRecord>::new();
+    let mut rd = match tokio::fs::read_dir(&dir).await {
+        Ok(rd) => rd,
+        Err(_) => return (StatusCode::OK, Json(out)).into_response(),
+    };
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        if let Ok(text) = tokio::fs::read_to_string(&path).await {
+            if let Ok(ev) = serde_json::from_str::<EvaluatorRecord>(&text) {
+                out.push(ev);
+            }
+        }
+    }
+    out.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+fn validate_evaluator(ev: &EvaluatorRecord) -> Option<axum::response::Response> {
+    if ev.name.trim().is_empty() || ev.prompt.trim().is_empty() || ev.provider_id.trim().is_empty()
+    {
+        return Some(
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "validation_failed" })),
+            )
+                .into_response(),
+        );
+    }
+    if let Err(err) = jsonschema::validator_for(&ev.score_schema) {
+        return Some(
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "invalid_schema", "message": err.to_string() })),
+            )
+                .into_response(),
+        );
+    }
+    None
+}
+
+pub(crate) async fn create_evaluator(
+    State(state): State<AppState>,
+    Json(req): Json<EvaluatorCreateRequest>,
+) -> axum::response::Response {
+    let now = now_ms().to_string();
+    let ev = EvaluatorRecord {
+        id: format!("eval_{}", now_ms()),
+        name: req.name,
+        prompt: req.prompt,
+        rubric: req.rubric,
+        score_schema: req.score_schema.unwrap_or_else(default_score_schema),
+        provider_id: req.provider_id,
+        model: req.model,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    if let Some(resp) = validate_evaluator(&ev) {
+        return resp;
+    }
+    if let Err(err) = write_evaluator(&state, &ev).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed", "message": err.to_string() })),
+        )
+            .into_response();
+    }
+    (StatusCode::CREATED, Json(ev)).into_response()
+}
+
+pub(crate) async fn get_evaluator(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match load_evaluator(&state, &id).await {
+        Ok(ev) => (StatusCode::OK, Json(ev)).into_response(),
+        Err(_) => error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    }
+}
+
+pub(crate) async fn update_evaluator(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<EvaluatorUpdateRequest>,
+) -> axum::response::Response {
+    let mut ev = match load_evaluator(&state, &id).await {
+        Ok(ev) => ev,
+        Err(_) => return error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    };
+    if let Some(v) = req.name {
+        ev.name = v;
+    }
+    if let Some(v) = req.prompt {
+        ev.prompt = v;
+    }
+    if let Some(v) = req.score_schema {
+        ev.score_schema = v;
+    }
+    if let Some(v) = req.provider_id {
+        ev.provider_id = v;
+    }
+    ev.rubric = req.rubric.or(ev.rubric);
+    ev.model = req.model.or(ev.model);
+    if let Some(resp) = validate_evaluator(&ev) {
+        return resp;
+    }
+    ev.updated_at = now_ms().to_string();
+    if let Err(err) = write_evaluator(&state, &ev).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed", "message": err.to_string() })),
+        )
+            .into_response();
+    }
+    (StatusCode::OK, Json(ev)).into_response()
+}
+
+pub(crate) async fn delete_evaluator(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let path = evaluators_dir(&state).join(format!("{id}.json"));
+    match tokio::fs::remove_file(&path).await {
+        Ok(_) => (StatusCode::NO_CONTENT, Body::empty()).into_response(),
+        Err(_) => error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    }
+}
+
+pub(crate) async fn evaluate_run_handler(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    Json(req): Json<EvaluateRequest>,
+) -> axum::response::Response {
+    if load_run_record(&state, &run_id).await.is_err() {
+        return error_response(StatusCode::NOT_FOUND, "not_found", &run_id);
+    }
+    let evaluators = match load_evaluators(&state, &req.evaluator_ids).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+    match evaluate_run(&state, &evaluators, &run_id, req.force).await {
+        Ok(run) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "runId": run.run_id, "scores": run.scores })),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed" })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DatasetScoresQuery {
+    project_id: Option<String>,
+}
+
+pub(crate) async fn dataset_scores(
+    State(state): State<AppState>,
+    Path(dataset_id): Path<String>,
+    Query(query): Query<DatasetScoresQuery>,
+) -> axum::response::Response {
+    let runs = list_run_records(&state, &dataset_id)
+        .await
+        .into_iter()
+        .filter(|run| {
+            query
+                .project_id
+                .as_deref()
+                .is_none_or(|p| run.project_id == p)
+        })
+        .collect::<Vec<_>>();
+    let evaluators = aggregate_scores(runs.iter().flat_map(|run| run.scores.iter()));
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "datasetId": dataset_id,
+            "runs": runs.len(),
+            "evaluators": evaluators,
+        })),
+    )
+        .into_response()
+}
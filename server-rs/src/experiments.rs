@@ -0,0 +1,824 @@
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assertions::strip_code_fence,
+    evaluators::{
+        aggregate_scores, evaluate_run, load_evaluators, EvaluateRequest, LoadedEvaluator,
+    },
+    now_ms, register_running_job,
+    runs::{
+        load_dataset_record, load_replay_executor, load_run_record, replay_row, ReplayExecutor,
+        ReplayPlan, RunRecord,
+    },
+    unregister_running_job, write_job, write_json_atomically, AppState, JobRecord,
+};
+
+const EXPERIMENT_MAX_ROWS: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExperimentArm {
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    project_id: String,
+    #[serde(default)]
+    snapshot_id: Option<String>,
+}
+
+impl ExperimentArm {
+    fn requested_id(&self) -> &str {
+        self.snapshot_id.as_deref().unwrap_or(&self.project_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExperimentJudge {
+    provider_id: String,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    rubric: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CreateExperimentRequest {
+    #[serde(default)]
+    name: Option<String>,
+    dataset_id: String,
+    baseline: ExperimentArm,
+    candidate: ExperimentArm,
+    #[serde(default)]
+    offset: Option<u32>,
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    execute: bool,
+    #[serde(default)]
+    provider_id: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    judge: Option<ExperimentJudge>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AssertionTally {
+    passed: u64,
+    failed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JudgeVerdict {
+    winner: Option<String>,
+    score: Option<f64>,
+    reason: Option<String>,
+    error: Option<String>,
+    /// True when the candidate was shown to the judge as response A.
+    #[serde(default)]
+    swapped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExperimentRowComparison {
+    row_index: u64,
+    baseline_run_id: String,
+    candidate_run_id: String,
+    baseline_status: String,
+    candidate_status: String,
+    digest_changed: bool,
+    baseline_assertions: AssertionTally,
+    candidate_assertions: AssertionTally,
+    baseline_tokens: Option<u64>,
+    candidate_tokens: Option<u64>,
+    token_delta: Option<i64>,
+    judge: Option<JudgeVerdict>,
+    outcome: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExperimentRecord {
+    id: String,
+    name: String,
+    dataset_id: String,
+    baseline: ExperimentArm,
+    candidate: ExperimentArm,
+    offset: u64,
+    limit: u64,
+    execute: bool,
+    provider_id: Option<String>,
+    model: Option<String>,
+    judge: Option<ExperimentJudge>,
+    created_at: String,
+    #[serde(default = "default_experiment_status")]
+    status: String,
+    #[serde(default)]
+    job_id: Option<String>,
+    rows: Vec<ExperimentRowComparison>,
+}
+
+fn default_experiment_status() -> String {
+    "succeeded".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExperimentJobStats {
+    experiment_id: String,
+    total: u64,
+    processed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExperimentSummary {
+    id: String,
+    name: String,
+    dataset_id: String,
+    baseline: ExperimentArm,
+    candidate: ExperimentArm,
+    created_at: String,
+    status: String,
+    rows: u64,
+    wins: u64,
+    losses: u64,
+    ties: u64,
+    digest_changed: u64,
+    baseline_passed: u64,
+    candidate_passed: u64,
+    baseline_assertions: AssertionTally,
+    candidate_assertions: AssertionTally,
+    token_delta: i64,
+    judged: u64,
+}
+
+fn experiments_dir(state: &AppState) -> PathBuf {
+    state.data_dir.join("experiments")
+}
+
+async fn write_experiment(state: &AppState, exp: &ExperimentRecord) -> anyhow::Result<()> {
+    let dir = experiments_dir(state);
+    tokio::fs::create_dir_all(&dir).await?;
+    write_json_atomically(&dir.join(format!("{}.json", exp.id)), exp).await
+}
+
+async fn load_experiment(state: &AppState, id: &str) -> anyhow::Result<ExperimentRecord> {
+    let path = experiments_dir(state).join(format!("{id}.json"));
+    let text = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+pub(crate) async fn pinned_run_ids(state: &AppState) -> HashSet<String> {
+    let mut out = HashSet::<String>::new();
+    let Ok(mut rd) = tokio::fs::read_dir(experiments_dir(state)).await else {
+        return out;
+    };
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(text) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        if let Ok(exp) = serde_json::from_str::<ExperimentRecord>(&text) {
+            for row in exp.rows {
+                out.insert(row.baseline_run_id);
+                out.insert(row.candidate_run_id);
+            }
+        }
+    }
+    out
+}
+
+fn error_response(status: StatusCode, error: &str, id: &str) -> axum::response::Response {
+    (
+        status,
+        Json(serde_json::json!({ "error": error, "id": id })),
+    )
+        .into_response()
+}
+
+fn assertion_tally(run: &RunRecord) -> AssertionTally {
+    let passed = run.assertions.iter().filter(|a| a.passed).count() as u64;
+    AssertionTally {
+        passed,
+        failed: run.assertions.len() as u64 - passed,
+    }
+}
+
+fn total_tokens(run: &RunRecord) -> Option<u64> {
+    run.execution
+        .as_ref()
+        .and_then(|e| e.usage.as_ref())
+        .map(|u| u.total_tokens)
+}
+
+fn completion_text(run: &RunRecord) -> Option<&str> {
+    run.execution.as_ref().and_then(|e| e.completion.as_deref())
+}
+
+/// Asks the judge to compare both completions. With `swapped` the candidate
+/// is presented as A, so alternating it per row cancels out position bias;
+/// the verdict is always reported in baseline/candidate terms.
+async fn judge_pair(
+    executor: &ReplayExecutor,
+    rubric: Option<&str>,
+    baseline: &RunRecord,
+    candidate: &RunRecord,
+    swapped: bool,
+) -> Option<JudgeVerdict> {
+    let (first, second) = if swapped {
+        (candidate, baseline)
+    } else {
+        (baseline, candidate)
+    };
+    let (Some(a), Some(b)) = (completion_text(first), completion_text(second)) else {
+        return None;
+    };
+    let mut system = String::from(
+        "You compare two responses produced for the same dataset row and decide which one is better.",
+    );
+    if let Some(rubric) = rubric.filter(|r| !r.trim().is_empty()) {
+        system.push_str("\n\nRubric:\n");
+        system.push_str(rubric);
+    }
+    system.push_str(
+        "\n\nReply with JSON only: {\"winner\": \"A\" | \"B\" | \"tie\", \"reason\": \"...\"}",
+    );
+    let user = format!(
+        "Prompt A:\n{}\n\nResponse A:\n{}\n\nPrompt B:\n{}\n\nResponse B:\n{}",
+        first.trace.text, a, second.trace.text, b
+    );
+    let body = serde_json::json!({
+        "model": executor.model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": user }
+        ],
+        "stream": false,
+    });
+    let mut verdict = JudgeVerdict {
+        winner: None,
+        score: None,
+        reason: None,
+        error: None,
+        swapped,
+    };
+    let v = match executor.client.chat_completions(&body).await {
+        Ok(v) => v,
+        Err(err) => {
+            verdict.error = Some(err.to_string());
+            return Some(verdict);
+        }
+    };
+    let (content, _) = executor.client.completion_content(&v);
+    let parsed = match serde_json::from_str::<serde_json::Value>(strip_code_fence(&content)) {
+        Ok(p) => p,
+        Err(_) => {
+            verdict.error = Some(format!("invalid_judge_output: {content}"));
+            return Some(verdict);
+        }
+    };
+    let (winner, score) = match (
+        parsed["winner"]
+            .as_str()
+            .map(|w| w.trim().to_ascii_lowercase())
+            .as_deref(),
+        swapped,
+    ) {
+        (Some("a"), false) | (Some("b"), true) => ("baseline", 0.0),
+        (Some("b"), false) | (Some("a"), true) => ("candidate", 1.0),
+        (Some("tie"), _) => ("tie", 0.5),
+        _ => {
+            verdict.error = Some(format!("invalid_judge_output: {content}"));
+            return Some(verdict);
+        }
+    };
+    verdict.winner = Some(winner.to_string());
+    verdict.score = Some(score);
+    verdict.reason = parsed["reason"].as_str().map(|s| s.to_string());
+    Some(verdict)
+}
+
+fn status_rank(status: &str) -> u8 {
+    match status {
+        "failed" => 0,
+        _ => 1,
+    }
+}
+
+fn decide_outcome(
+    baseline: &RunRecord,
+    candidate: &RunRecord,
+    judge: Option<&JudgeVerdict>,
+) -> &'static str {
+    if let Some(winner) = judge.and_then(|j| j.winner.as_deref()) {
+        return match winner {
+            "candidate" => "win",
+            "baseline" => "loss",
+            _ => "tie",
+        };
+    }
+    let by_status = status_rank(&candidate.status).cmp(&status_rank(&baseline.status));
+    let by_assertions = assertion_tally(baseline)
+        .failed
+        .cmp(&assertion_tally(candidate).failed);
+    match by_status.then(by_assertions) {
+        std::cmp::Ordering::Greater => "win",
+        std::cmp::Ordering::Less => "loss",
+        std::cmp::Ordering::Equal => "tie",
+    }
+}
+
+fn summarize(exp: &ExperimentRecord) -> ExperimentSummary {
+    let mut summary = ExperimentSummary {
+        id: exp.id.clone(),
+        name: exp.name.clone(),
+        dataset_id: exp.dataset_id.clone(),
+        baseline: exp.baseline.clone(),
+        candidate: exp.candidate.clone(),
+        created_at: exp.created_at.clone(),
+        status: exp.status.clone(),
+        rows: exp.rows.len() as u64,
+        wins: 0,
+        losses: 0,
+        ties: 0,
+        digest_changed: 0,
+        baseline_passed: 0,
+        candidate_passed: 0,
+        baseline_assertions: AssertionTally::default(),
+        candidate_assertions: AssertionTally::default(),
+        token_delta: 0,
+        judged: 0,
+    };
+    for row in &exp.rows {
+        match row.outcome.as_str() {
+            "win" => summary.wins += 1,
+            "loss" => summary.losses += 1,
+            _ => summary.ties += 1,
+        }
+        if row.digest_changed {
+            summary.digest_changed += 1;
+        }
+        if row.baseline_status != "failed" {
+            summary.baseline_passed += 1;
+        }
+        if row.candidate_status != "failed" {
+            summary.candidate_passed += 1;
+        }
+        summary.baseline_assertions.passed += row.baseline_assertions.passed;
+        summary.baseline_assertions.failed += row.baseline_assertions.failed;
+        summary.candidate_assertions.passed += row.candidate_assertions.passed;
+        summary.candidate_assertions.failed += row.candidate_assertions.failed;
+        summary.token_delta += row.token_delta.unwrap_or(0);
+        if row.judge.as_ref().is_some_and(|j| j.winner.is_some()) {
+            summary.judged += 1;
+        }
+    }
+    summary
+}
+
+pub(crate) async fn create_experiment(
+    State(state): State<AppState>,
+    Json(req): Json<CreateExperimentRequest>,
+) -> axum::response::Response {
+    if req.dataset_id.trim().is_empty()
+        || req.baseline.requested_id().trim().is_empty()
+        || req.candidate.requested_id().trim().is_empty()
+        || (req.judge.is_some() && !req.execute)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response();
+    }
+
+    let dataset = match load_dataset_record(&state, &req.dataset_id).await {
+        Ok(ds) => ds,
+        Err(_) => {
+            return error_response(StatusCode::NOT_FOUND, "dataset_not_found", &req.dataset_id)
+        }
+    };
+
+    let executor = if req.execute {
+        match load_replay_executor(&state, req.provider_id.as_deref(), req.model.as_deref()).await {
+            Ok(e) => Some(e),
+            Err((status, error)) => {
+                return error_response(status, error, req.provider_id.as_deref().unwrap_or(""))
+            }
+        }
+    } else {
+        None
+    };
+    let mut plans = Vec::<ReplayPlan>::new();
+    for (arm, suffix) in [(&req.baseline, "_base"), (&req.candidate, "_cand")] {
+        match ReplayPlan::load(
+            &state,
+            &arm.project_id,
+            arm.snapshot_id.as_deref(),
+            &dataset.id,
+            executor.clone(),
+            suffix,
+        )
+        .await
+        {
+            Ok(plan) => plans.push(plan),
+            Err((status, error)) => return error_response(status, error, arm.requested_id()),
+        }
+    }
+    let judge_executor = match &req.judge {
+        Some(judge) => {
+            match load_replay_executor(&state, Some(&judge.provider_id), judge.model.as_deref())
+                .await
+            {
+                Ok(e) => Some(e),
+                Err((status, error)) => return error_response(status, error, &judge.provider_id),
+            }
+        }
+        None => None,
+    };
+
+    let limit = (req.limit.unwrap_or(20) as usize).min(EXPERIMENT_MAX_ROWS);
+    let start = (req.offset.unwrap_or(0) as usize).min(dataset.rows.len());
+    let end = (start + limit).min(dataset.rows.len());
+
+    let id = format!("exp_{}", now_ms());
+    let mut baseline = req.baseline.clone();
+    let mut candidate = req.candidate.clone();
+    if baseline.label.trim().is_empty() {
+        baseline.label = "baseline".to_string();
+    }
+    if candidate.label.trim().is_empty() {
+        candidate.label = "candidate".to_string();
+    }
+    for (arm, plan) in [(&mut baseline, &plans[0]), (&mut candidate, &plans[1])] {
+        arm.project_id = plan.snapshot.project_id.clone();
+        arm.snapshot_id = Some(plan.snapshot.id.clone());
+    }
+    let job_id = format!("job_{}", now_ms());
+    let exp = ExperimentRecord {
+        name: req
+            .name
+            .clone()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| id.clone()),
+        id,
+        dataset_id: dataset.id.clone(),
+        baseline,
+        candidate,
+        offset: start as u64,
+        limit: limit as u64,
+        execute: req.execute,
+        provider_id: req.provider_id.clone(),
+        model: req.model.clone(),
+        judge: req.judge.clone(),
+        created_at: now_ms().to_string(),
+        status: "running".to_string(),
+        job_id: Some(job_id.clone()),
+        rows: Vec::new(),
+    };
+    let stats = ExperimentJobStats {
+        experiment_id: exp.id.clone(),
+        total: (end - start) as u64,
+        processed: 0,
+    };
+    let job = JobRecord {
+        id: job_id,
+        job_type: "experiment".to_string(),
+        status: "running".to_string(),
+        created_at: now_ms().to_string(),
+        finished_at: None,
+        summary: None,
+        stats: serde_json::to_value(&stats).unwrap_or_default(),
+        error: None,
+    };
+
+    let Some(cancel) = register_running_job(&state, &job.id) else {
+        return error_response(StatusCode::CONFLICT, "job_running", &job.id);
+    };
+    if write_experiment(&state, &exp).await.is_err() || write_job(&state, &job).await.is_err() {
+        unregister_running_job(&state, &job.id);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed" })),
+        )
+            .into_response();
+    }
+
+    let run = ExperimentRun {
+        plans,
+        judge_executor,
+        rubric: req.judge.as_ref().and_then(|j| j.rubric.clone()),
+        start,
+        rows: dataset.rows[start..end].to_vec(),
+    };
+    let response = serde_json::json!({ "job": job, "experimentId": exp.id });
+    let job_id = job.id.clone();
+    tokio::spawn(async move {
+        run_experiment_job(&state, job, exp, run, &cancel).await;
+        unregister_running_job(&state, &job_id);
+    });
+    (StatusCode::ACCEPTED, Json(response)).into_response()
+}
+
+struct ExperimentRun {
+    plans: Vec<ReplayPlan>,
+    judge_executor: Option<ReplayExecutor>,
+    rubric: Option<String>,
+    start: usize,
+    rows: Vec<serde_json::Value>,
+}
+
+async fn compare_row(
+    state: &AppState,
+    run: &ExperimentRun,
+    row_index: u64,
+    row: &serde_json::Value,
+) -> anyhow::Result<ExperimentRowComparison> {
+    let baseline = replay_row(state, &run.plans[0], row_index, row).await?;
+    let candidate = replay_row(state, &run.plans[1], row_index, row).await?;
+    let judge = match &run.judge_executor {
+        Some(executor) => {
+            let swapped = row_index % 2 == 1;
+            judge_pair(
+                executor,
+                run.rubric.as_deref(),
+                &baseline,
+                &candidate,
+                swapped,
+            )
+            .await
+        }
+        None => None,
+    };
+    let baseline_tokens = total_tokens(&baseline);
+    let candidate_tokens = total_tokens(&candidate);
+    Ok(ExperimentRowComparison {
+        row_index,
+        baseline_run_id: baseline.run_id.clone(),
+        candidate_run_id: candidate.run_id.clone(),
+        baseline_status: baseline.status.clone(),
+        candidate_status: candidate.status.clone(),
+        digest_changed: baseline.output_digest != candidate.output_digest,
+        baseline_assertions: assertion_tally(&baseline),
+        candidate_assertions: assertion_tally(&candidate),
+        baseline_tokens,
+        candidate_tokens,
+        token_delta: baseline_tokens
+            .zip(candidate_tokens)
+            .map(|(b, c)| c as i64 - b as i64),
+        outcome: decide_outcome(&baseline, &candidate, judge.as_ref()).to_string(),
+        judge,
+    })
+}
+
+async fn run_experiment_job(
+    state: &AppState,
+    mut job: JobRecord,
+    mut exp: ExperimentRecord,
+    run: ExperimentRun,
+    cancel: &AtomicBool,
+) {
+    let mut stats =
+        serde_json::from_value::<ExperimentJobStats>(job.stats.clone()).unwrap_or_default();
+    for (i, row) in run.rows.iter().enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            job.status = "cancelled".to_string();
+            break;
+        }
+        match compare_row(state, &run, (run.start + i) as u64, row).await {
+            Ok(comparison) => exp.rows.push(comparison),
+            Err(err) => {
+                job.status = "failed".to_string();
+                job.error = Some(err.to_string());
+                break;
+            }
+        }
+        stats.processed += 1;
+        job.summary = Some(format!("processed={}/{}", stats.processed, stats.total));
+        job.stats = serde_json::to_value(&stats).unwrap_or_default();
+        let _ = write_experiment(state, &exp).await;
+        let _ = write_job(state, &job).await;
+    }
+
+    if job.status == "running" {
+        job.status = "succeeded".to_string();
+    }
+    job.finished_at = Some(now_ms().to_string());
+    job.summary = Some(format!("processed={}/{}", stats.processed, stats.total));
+    job.stats = serde_json::to_value(&stats).unwrap_or_default();
+    exp.status = job.status.clone();
+    let _ = write_experiment(state, &exp).await;
+    let _ = write_job(state, &job).await;
+}
+
+pub(crate) async fn list_experiments(State(state): State<AppState>) -> axum::response::Response {
+    let dir = experiments_dir(&state);
+    let mut out = Vec::<ExperimentSummary>::new();
+    let mut rd = match tokio::fs::read_dir(&dir).await {
+        Ok(rd) => rd,
+        Err(_) => return (StatusCode::OK, Json(out)).into_response(),
+    };
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(text) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        if let Ok(exp) = serde_json::from_str::<ExperimentRecord>(&text) {
+            out.push(summarize(&exp));
+        }
+    }
+    out.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+pub(crate) async fn get_experiment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match load_experiment(&state, &id).await {
+        Ok(exp) => (StatusCode::OK, Json(exp)).into_response(),
+        Err(_) => error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    }
+}
+
+pub(crate) async fn get_experiment_summary(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match load_experiment(&state, &id).await {
+        Ok(exp) => (StatusCode::OK, Json(summarize(&exp))).into_response(),
+        Err(_) => error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    }
+}
+
+async fn experiment_scores(state: &AppState, exp: &ExperimentRecord) -> serde_json::Value {
+    let mut baseline = Vec::<RunRecord>::new();
+    let mut candidate = Vec::<RunRecord>::new();
+    for row in &exp.rows {
+        if let Ok(run) = load_run_record(state, &row.baseline_run_id).await {
+            baseline.push(run);
+        }
+        if let Ok(run) = load_run_record(state, &row.candidate_run_id).await {
+            candidate.push(run);
+        }
+    }
+    serde_json::json!({
+        "experimentId": exp.id,
+        "baseline": aggregate_scores(baseline.iter().flat_map(|run| run.scores.iter())),
+        "candidate": aggregate_scores(candidate.iter().flat_map(|run| run.scores.iter())),
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvaluateJobStats {
+    experiment_id: String,
+    evaluator_ids: Vec<String>,
+    total: u64,
+    evaluated: u64,
+    failed: u64,
+}
+
+pub(crate) async fn evaluate_experiment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<EvaluateRequest>,
+) -> axum::response::Response {
+    let exp = match load_experiment(&state, &id).await {
+        Ok(exp) => exp,
+        Err(_) => return error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    };
+    let evaluators = match load_evaluators(&state, &req.evaluator_ids).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+    let run_ids = exp
+        .rows
+        .iter()
+        .flat_map(|row| [row.baseline_run_id.clone(), row.candidate_run_id.clone()])
+        .collect::<Vec<_>>();
+    for run_id in &run_ids {
+        if load_run_record(&state, run_id).await.is_err() {
+            return error_response(StatusCode::NOT_FOUND, "run_not_found", run_id);
+        }
+    }
+
+    let stats = EvaluateJobStats {
+        experiment_id: exp.id.clone(),
+        evaluator_ids: req.evaluator_ids.clone(),
+        total: run_ids.len() as u64,
+        ..EvaluateJobStats::default()
+    };
+    let job = JobRecord {
+        id: format!("job_{}", now_ms()),
+        job_type: "evaluate_experiment".to_string(),
+        status: "running".to_string(),
+        created_at: now_ms().to_string(),
+        finished_at: None,
+        summary: None,
+        stats: serde_json::to_value(&stats).unwrap_or_default(),
+        error: None,
+    };
+    let Some(cancel) = register_running_job(&state, &job.id) else {
+        return error_response(StatusCode::CONFLICT, "job_running", &job.id);
+    };
+    if write_job(&state, &job).await.is_err() {
+        unregister_running_job(&state, &job.id);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed" })),
+        )
+            .into_response();
+    }
+
+    let response = serde_json::json!({ "job": job, "experimentId": exp.id });
+    let job_id = job.id.clone();
+    tokio::spawn(async move {
+        run_evaluate_job(&state, job, &evaluators, &run_ids, req.force, &cancel).await;
+        unregister_running_job(&state, &job_id);
+    });
+    (StatusCode::ACCEPTED, Json(response)).into_response()
+}
+
+async fn run_evaluate_job(
+    state: &AppState,
+    mut job: JobRecord,
+    evaluators: &[LoadedEvaluator],
+    run_ids: &[String],
+    force: bool,
+    cancel: &AtomicBool,
+) {
+    let mut stats =
+        serde_json::from_value::<EvaluateJobStats>(job.stats.clone()).unwrap_or_default();
+    for run_id in run_ids {
+        if cancel.load(Ordering::SeqCst) {
+            job.status = "cancelled".to_string();
+            break;
+        }
+        match evaluate_run(state, evaluators, run_id, force).await {
+            Ok(_) => stats.evaluated += 1,
+            Err(err) => {
+                stats.failed += 1;
+                job.error.get_or_insert_with(|| format!("{run_id}: {err}"));
+            }
+        }
+        job.summary = Some(format!(
+            "evaluated={}/{} failed={}",
+            stats.evaluated, stats.total, stats.failed
+        ));
+        job.stats = serde_json::to_value(&stats).unwrap_or_default();
+        let _ = write_job(state, &job).await;
+    }
+
+    if job.status == "running" {
+        job.status = if stats.failed == 0 {
+            "succeeded"
+        } else if stats.evaluated > 0 {
+            "partial"
+        } else {
+            "failed"
+        }
+        .to_string();
+    }
+    job.finished_at = Some(now_ms().to_string());
+    job.summary = Some(format!(
+        "evaluated={}/{} failed={}",
+        stats.evaluated, stats.total, stats.failed
+    ));
+    job.stats = serde_json::to_value(&stats).unwrap_or_default();
+    let _ = write_job(state, &job).await;
+}
+
+pub(crate) async fn get_experiment_scores(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match load_experiment(&state, &id).await {
+        Ok(exp) => (StatusCode::OK, Json(experiment_scores(&state, &exp).await)).into_response(),
+        Err(_) => error_response(StatusCode::NOT_FOUND, "not_found", &id),
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, HashMap};
+
+use context_engine::{render_with_trace, EngineNode, NodeKind, OutputStyle};
+use serde::Deserialize;
+
+use crate::runs::json_value_to_string;
+
+const DEFAULT_EXAMPLES: usize = 3;
+const MAX_EXAMPLES: usize = 50;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FewShotSpec {
+    pub(crate) sample: Option<String>,
+    pub(crate) n: Option<usize>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) stratify_by: Option<String>,
+    #[serde(default)]
+    pub(crate) filter: serde_json::Map<String, serde_json::Value>,
+    pub(crate) fields: Option<Vec<String>>,
+    pub(crate) template: Option<String>,
+    pub(crate) separator: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SampleStrategy {
+    First,
+    Random,
+    Stratified,
+}
+
+impl SampleStrategy {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            SampleStrategy::First => "first",
+            SampleStrategy::Random => "random",
+            SampleStrategy::Stratified => "stratified",
+        }
+    }
+}
+
+pub(crate) struct FewShotSample {
+    pub(crate) strategy: SampleStrategy,
+    pub(crate) seed: Option<u64>,
+    pub(crate) matched_rows: usize,
+    pub(crate) row_indexes: Vec<usize>,
+    pub(crate) text: String,
+}
+
+impl FewShotSpec {
+    pub(crate) fn parse(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(Self::default());
+        }
+        if value.starts_with('{') {
+            return Ok(serde_json::from_str(value)?);
+        }
+        if let Ok(n) = value.parse::<usize>() {
+            return Ok(Self {
+                n: Some(n),
+                ..Self::default()
+            });
+        }
+        anyhow::bail!("invalid_spec")
+    }
+
+    fn strategy(&self) -> anyhow::Result<SampleStrategy> {
+        match self
+            .sample
+            .as_deref()
+            .map(|s| s.trim().to_ascii_lowercase())
+        {
+            None => Ok(if self.stratify_by.is_some() {
+                SampleStrategy::Stratified
+            } else {
+                SampleStrategy::First
+            }),
+            Some(s) if s.is_empty() || s == "first" => Ok(SampleStrategy::First),
+            Some(s) if s == "random" => Ok(SampleStrategy::Random),
+            Some(s) if s == "stratified" => Ok(SampleStrategy::Stratified),
+            Some(_) => anyhow::bail!("unsupported_sample"),
+        }
+    }
+}
+
+pub(crate) fn sample_rows(
+    rows: &[serde_json::Value],
+    spec: &FewShotSpec,
+    fallback_seed: u64,
+) -> anyhow::Result<FewShotSample> {
+    let strategy = spec.strategy()?;
+    let n = spec.n.unwrap_or(DEFAULT_EXAMPLES).clamp(1, MAX_EXAMPLES);
+
+    let matched = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row_matches_filter(row, &spec.filter))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    let (row_indexes, seed) = match strategy {
+        SampleStrategy::First => (matched.iter().copied().take(n).collect::<Vec<_>>(), None),
+        SampleStrategy::Random => {
+            let seed = spec.seed.unwrap_or(fallback_seed);
+            let mut shuffled = matched.clone();
+            shuffle(&mut shuffled, seed);
+            shuffled.truncate(n);
+            (shuffled, Some(seed))
+        }
+        SampleStrategy::Stratified => {
+            let Some(field) = spec.stratify_by.as_deref().filter(|f| !f.trim().is_empty()) else {
+                anyhow::bail!("missing_stratify_by");
+            };
+            let mut groups = BTreeMap::<String, Vec<usize>>::new();
+            for idx in &matched {
+                let key = lookup_path(&rows[*idx], field)
+                    .map(json_value_to_string)
+                    .unwrap_or_default();
+                groups.entry(key).or_default().push(*idx);
+            }
+            if let Some(seed) = spec.seed {
+                for (i, group) in groups.values_mut().enumerate() {
+                    shuffle(group, seed.wrapping_add(i as u64));
+                }
+            }
+            let mut picked = Vec::<usize>::new();
+            let mut cursor = 0usize;
+            while picked.len() < n {
+                let mut advanced = false;
+                for group in groups.values() {
+                    if let Some(idx) = group.get(cursor) {
+                        picked.push(*idx);
+                        advanced = true;
+                        if picked.len() == n {
+                            break;
+                        }
+                    }
+                }
+                if !advanced {
+                    break;
+                }
+                cursor += 1;
+            }
+            (picked, spec.seed)
+        }
+    };
+
+    let separator = spec.separator.clone().unwrap_or_else(|| "\n\n".to_string());
+    let text = row_indexes
+        .iter()
+        .enumerate()
+        .map(|(i, idx)| format_example(&rows[*idx], i + 1, spec))
+        .collect::<Vec<_>>()
+        .join(&separator);
+
+    Ok(FewShotSample {
+        strategy,
+        seed,
+        matched_rows: matched.len(),
+        row_indexes,
+        text,
+    })
+}
+
+fn row_matches_filter(
+    row: &serde_json::Value,
+    filter: &serde_json::Map<String, serde_json::Value>,
+) -> bool {
+    for (key, expected) in filter {
+        let Some(actual) = lookup_path(row, key) else {
+            return false;
+        };
+        let ok = match expected {
+            serde_json::Value::Array(options) => options.iter().any(|o| o == actual),
+            other => other == actual,
+        };
+        if !ok {
+            return false;
+        }
+    }
+    true
+}
+
+fn lookup_path<'a>(row: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    if let Some(v) = row.get(path) {
+        return Some(v);
+    }
+    let mut cur = row;
+    for part in path.split('.') {
+        cur = cur.get(part)?;
+    }
+    Some(cur)
+}
+
+fn row_variables(row: &serde_json::Value) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::<String, String>::new();
+    let Some(obj) = row.as_object() else {
+        return out;
+    };
+    for (k, v) in obj {
+        if k == "variables" {
+            continue;
+        }
+        out.insert(k.clone(), json_value_to_string(v));
+    }
+    if let Some(vars) = obj.get("variables").and_then(|v| v.as_object()) {
+        for (k, v) in vars {
+            out.insert(k.clone(), json_value_to_string(v));
+        }
+    }
+    out
+}
+
+fn format_example(row: &serde_json::Value, position: usize, spec: &FewShotSpec) -> String {
+    let vars = row_variables(row);
+    match spec.template.as_deref() {
+        Some(template) => {
+            let mut map = vars.into_iter().collect::<HashMap<_, _>>();
+            map.insert("_index".to_string(), position.to_string());
+            let node = EngineNode {
+                id: "few_shot".to_string(),
+                label: "few_shot".to_string(),
+                kind: NodeKind::Text,
+                content: template.to_string(),
+            };
+            render_with_trace(&[node], &map, OutputStyle::Plain, "few_shot", "").text
+        }
+        None => {
+            let keys = match &spec.fields {
+                Some(fields) => fields.clone(),
+                None => vars
+                    .keys()
+                    .filter(|k| !k.starts_with('_'))
+                    .cloned()
+                    .collect(),
+            };
+            keys.iter()
+                .filter_map(|k| vars.get(k).map(|v| format!("{k}: {v}")))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+fn shuffle(items: &mut [usize], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,244 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use context_engine::{TraceMessage, TraceSeverity};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum IsolatedNodePolicy {
+    #[default]
+    Include,
+    Skip,
+    Error,
+}
+
+impl IsolatedNodePolicy {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Include => "include",
+            Self::Skip => "skip",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FlowEdge {
+    pub(crate) source: String,
+    pub(crate) target: String,
+}
+
+pub(crate) struct FlowNodeRef<'a> {
+    pub(crate) id: &'a str,
+    pub(crate) disabled: bool,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct FlowOrder {
+    pub(crate) order: Vec<usize>,
+    pub(crate) messages: Vec<TraceMessage>,
+}
+
+impl FlowOrder {
+    pub(crate) fn apply<T: Clone>(&self, nodes: &[T]) -> Vec<T> {
+        self.order.iter().map(|&i| nodes[i].clone()).collect()
+    }
+
+    pub(crate) fn has_errors(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|m| m.severity == TraceSeverity::Error)
+    }
+}
+
+fn message(
+    severity: TraceSeverity,
+    code: &str,
+    text: String,
+    details: serde_json::Value,
+) -> TraceMessage {
+    TraceMessage {
+        severity,
+        code: code.to_string(),
+        message: text,
+        details: Some(details),
+    }
+}
+
+fn reachable(start: usize, out: &[Vec<usize>], alive: &HashSet<usize>) -> HashSet<usize> {
+    let mut seen = HashSet::new();
+    let mut stack = out[start].clone();
+    while let Some(i) = stack.pop() {
+        if !alive.contains(&i) || !seen.insert(i) {
+            continue;
+        }
+        stack.extend(out[i].iter().copied());
+    }
+    seen
+}
+
+pub(crate) fn topo_sort_nodes(
+    nodes: &[FlowNodeRef<'_>],
+    edges: &[(&str, &str)],
+    policy: IsolatedNodePolicy,
+) -> FlowOrder {
+    let mut result = FlowOrder::default();
+
+    let mut index = HashMap::<&str, usize>::new();
+    for (i, n) in nodes.iter().enumerate() {
+        index.entry(n.id).or_insert(i);
+    }
+
+    let mut indeg = vec![0usize; nodes.len()];
+    let mut out = vec![Vec::<usize>::new(); nodes.len()];
+    let mut connected = vec![false; nodes.len()];
+    let mut dangling = Vec::<String>::new();
+    let mut seen_edges = HashSet::<(usize, usize)>::new();
+    for (source, target) in edges {
+        let (Some(&s), Some(&t)) = (index.get(source), index.get(target)) else {
+            dangling.push(format!("{source}->{target}"));
+            continue;
+        };
+        if !seen_edges.insert((s, t)) {
+            continue;
+        }
+        indeg[t] += 1;
+        out[s].push(t);
+        connected[s] = true;
+        connected[t] = true;
+    }
+    for targets in &mut out {
+        targets.sort_unstable();
+    }
+
+    if !dangling.is_empty() {
+        result.messages.push(message(
+            TraceSeverity::Warn,
+            "edge_dangling",
+            format!("连线指向不存在的节点：{}", dangling.join(", ")),
+            json!({ "edges": dangling }),
+        ));
+    }
+
+    let mut ready = indeg
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| **d == 0)
+        .map(|(i, _)| Reverse(i))
+        .collect::<BinaryHeap<_>>();
+    let mut sorted = Vec::<usize>::with_capacity(nodes.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        sorted.push(i);
+        for &t in &out[i] {
+            indeg[t] -= 1;
+            if indeg[t] == 0 {
+                ready.push(Reverse(t));
+            }
+        }
+    }
+
+    if sorted.len() < nodes.len() {
+        let scheduled = sorted.iter().copied().collect::<HashSet<_>>();
+        let alive = (0..nodes.len())
+            .filter(|i| !scheduled.contains(i))
+            .collect::<HashSet<_>>();
+        let mut remaining = alive.iter().copied().collect::<Vec<_>>();
+        remaining.sort_unstable();
+        let reach = remaining
+            .iter()
+            .map(|&i| (i, reachable(i, &out, &alive)))
+            .collect::<HashMap<_, _>>();
+
+        let mut cycles = Vec::<Vec<String>>::new();
+        let mut assigned = HashSet::<usize>::new();
+        for &i in &remaining {
+            if assigned.contains(&i) || !reach[&i].contains(&i) {
+                continue;
+            }
+            let members = remaining
+                .iter()
+                .copied()
+                .filter(|j| reach[&i].contains(j) && reach[j].contains(&i))
+                .collect::<Vec<_>>();
+            assigned.extend(members.iter().copied());
+            cycles.push(members.iter().map(|&j| nodes[j].id.to_string()).collect());
+        }
+        let blocked = remaining
+            .iter()
+            .filter(|i| !assigned.contains(i))
+            .map(|&i| nodes[i].id.to_string())
+            .collect::<Vec<_>>();
+
+        for cycle in &cycles {
+            result.messages.push(message(
+                TraceSeverity::Error,
+                "cycle_detected",
+                format!("检测到循环依赖：{}", cycle.join(" → ")),
+                json!({ "nodeIds": cycle }),
+            ));
+        }
+        if !blocked.is_empty() {
+            result.messages.push(message(
+                TraceSeverity::Warn,
+                "node_blocked",
+                format!("节点依赖循环，未渲染：{}", blocked.join(", ")),
+                json!({ "nodeIds": blocked }),
+            ));
+        }
+    }
+
+    let has_edges = !seen_edges.is_empty();
+    let mut skipped = HashSet::<usize>::new();
+    if has_edges {
+        let isolated = (0..nodes.len())
+            .filter(|&i| !connected[i] && !nodes[i].disabled)
+            .collect::<Vec<_>>();
+        if !isolated.is_empty() {
+            let disconnected = isolated
+                .iter()
+                .map(|&i| nodes[i].id.to_string())
+                .collect::<Vec<_>>();
+            let severity = match policy {
+                IsolatedNodePolicy::Include => TraceSeverity::Warn,
+                IsolatedNodePolicy::Skip => TraceSeverity::Info,
+                IsolatedNodePolicy::Error => TraceSeverity::Error,
+            };
+            if policy != IsolatedNodePolicy::Include {
+                skipped.extend(isolated.iter().copied());
+            }
+            result.messages.push(message(
+                severity,
+                "node_disconnected",
+                format!("节点未连接到流程：{}", disconnected.join(", ")),
+                json!({ "nodeIds": disconnected, "policy": policy.as_str() }),
+            ));
+        }
+    }
+
+    let disabled = sorted
+        .iter()
+        .filter(|&&i| nodes[i].disabled)
+        .map(|&i| nodes[i].id.to_string())
+        .collect::<Vec<_>>();
+    if !disabled.is_empty() {
+        result.messages.push(message(
+            TraceSeverity::Info,
+            "node_disabled",
+            format!("节点已禁用：{}", disabled.join(", ")),
+            json!({ "nodeIds": disabled }),
+        ));
+    }
+
+    result.order = sorted
+        .into_iter()
+        .filter(|i| !nodes[*i].disabled && !skipped.contains(i))
+        .collect();
+    result
+}
@@ -1,17 +1,20 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::Infallible,
     io::Write as _,
     path::{Component, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{DefaultBodyLimit, Path, State},
     http::{header, HeaderValue, Method, Request, StatusCode, Uri},
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use bytes::Bytes;
@@ -26,21 +29,53 @@ use tower_http::{
     trace::TraceLayer,
 };
 
+mod assertions;
 pub mod connectors;
 mod crypto;
+mod evaluators;
+mod experiments;
+mod few_shot;
+mod flow_graph;
+mod providers;
 mod resolvers;
+mod retention;
+mod run_export;
+mod run_index;
 mod runs;
+mod snapshots;
 mod variable_library;
+mod vector_filter;
+mod vector_hybrid;
+mod vector_index;
+mod vector_ingest;
+mod vector_qdrant;
+mod vector_segments;
+mod vector_snapshot;
 mod vector_store;
-
+mod vector_text;
+
+use flow_graph::{topo_sort_nodes, FlowEdge, FlowNodeRef, IsolatedNodePolicy};
+use providers::{ProviderClient, ProviderError, ProviderKind};
+use vector_qdrant::{
+    qdrant_collection_exists, qdrant_create_collection, qdrant_create_index,
+    qdrant_delete_collection, qdrant_delete_index, qdrant_delete_points, qdrant_get_collection,
+    qdrant_get_point, qdrant_list_collections, qdrant_query_points, qdrant_retrieve_points,
+    qdrant_scroll_points, qdrant_search_points, qdrant_upsert_points,
+};
 use vector_store::{
-    create_vector_collection, delete_vector_points, list_vector_collections, search_vector,
-    upsert_vector_points, vector_upsert_points_internal, VectorPoint,
+    create_payload_index, create_vector_collection, delete_payload_index, delete_vector_points,
+    drop_vector_collection, get_vector_points, list_vector_collections, restore_vector_collection,
+    scroll_vector_points, search_vector, snapshot_vector_collection, upsert_vector_points,
+    vector_collection_embedding_model, vector_collection_stats, vector_upsert_points_internal,
+    VectorCollectionCache, VectorPoint, VECTOR_SNAPSHOT_MAX_BYTES,
 };
 
 #[derive(Clone)]
 struct AppState {
     data_dir: Arc<PathBuf>,
+    running_jobs: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    vector_collections: VectorCollectionCache,
+    run_index: run_index::RunIndexPool,
 }
 
 pub fn build_app(static_dir: PathBuf) -> Router {
@@ -50,7 +85,11 @@ pub fn build_app(static_dir: PathBuf) -> Router {
     let cors = cors_from_env();
     let state = AppState {
         data_dir: Arc::new(data_dir_from_env()),
+        running_jobs: Arc::default(),
+        vector_collections: Arc::default(),
+        run_index: Arc::default(),
     };
+    run_index::warm_up(&state);
     build_app_with_state(static_dir, index_file, cors, state)
 }
 
@@ -61,6 +100,9 @@ pub fn build_app_with_data_dir(static_dir: PathBuf, data_dir: PathBuf) -> Router
     let cors = cors_from_env();
     let state = AppState {
         data_dir: Arc::new(data_dir),
+        running_jobs: Arc::default(),
+        vector_collections: Arc::default(),
+        run_index: Arc::default(),
     };
     build_app_with_state(static_dir, index_file, cors, state)
 }
@@ -132,6 +174,11 @@ fn build_app_with_state(
         .route("/healthz", get(healthz))
         .route("/projects", get(list_projects).post(create_project))
         .route("/projects/{id}", get(get_project).put(upsert_project))
+        .route(
+            "/projects/{id}/snapshots",
+            get(snapshots::list_project_snapshots).post(snapshots::create_project_snapshot),
+        )
+        .route("/snapshots/{id}", get(snapshots::get_snapshot))
         .route(
             "/projects/{projectId}/variable-library",
             get(variable_library::list_variable_library)
@@ -178,11 +225,56 @@ fn build_app_with_state(
         .route("/datasets", get(list_datasets).post(create_dataset))
         .route("/datasets/{id}", get(get_dataset).delete(delete_dataset))
         .route("/datasets/{id}/replay", post(runs::replay_dataset))
+        .route("/datasets/{id}/replay-jobs", post(runs::start_replay_job))
         .route("/datasets/{id}/runs", get(runs::list_dataset_runs))
+        .route(
+            "/datasets/{id}/runs/export",
+            get(run_export::export_dataset_runs),
+        )
         .route("/runs/{id}", get(runs::get_run))
+        .route(
+            "/experiments",
+            get(experiments::list_experiments).post(experiments::create_experiment),
+        )
+        .route("/experiments/{id}", get(experiments::get_experiment))
+        .route(
+            "/experiments/{id}/summary",
+            get(experiments::get_experiment_summary),
+        )
+        .route(
+            "/experiments/{id}/evaluate",
+            post(experiments::evaluate_experiment),
+        )
+        .route(
+            "/experiments/{id}/scores",
+            get(experiments::get_experiment_scores),
+        )
+        .route(
+            "/evaluators",
+            get(evaluators::list_evaluators).post(evaluators::create_evaluator),
+        )
+        .route(
+            "/evaluators/{id}",
+            get(evaluators::get_evaluator)
+                .put(evaluators::update_evaluator)
+                .delete(evaluators::delete_evaluator),
+        )
+        .route(
+            "/runs/{id}/evaluate",
+            post(evaluators::evaluate_run_handler),
+        )
+        .route("/datasets/{id}/scores", get(evaluators::dataset_scores))
         .route("/jobs", get(list_jobs))
         .route("/jobs/{id}", get(get_job))
+        .route("/jobs/{id}/cancel", post(cancel_job))
+        .route("/jobs/{id}/resume", post(runs::resume_replay_job))
         .route("/jobs/embed-to-vector", post(job_embed_to_vector))
+        .route("/jobs/embed-to-milvus", post(job_embed_to_milvus))
+        .route(
+            "/jobs/ingest-documents",
+            post(vector_ingest::job_ingest_documents),
+        )
+        .route("/jobs/prune-runs", post(retention::job_prune_runs))
         .route("/operations", get(list_operations))
         .route(
             "/datasources/{id}",
@@ -196,6 +288,23 @@ fn build_app_with_state(
         )
         .route("/datasources/{id}/test", post(test_datasource))
         .route("/datasources/{id}/neo4j/labels", get(list_neo4j_labels))
+        .route(
+            "/datasources/{id}/neo4j/labels/{label}/properties",
+            get(list_neo4j_label_properties),
+        )
+        .route(
+            "/datasources/{id}/neo4j/labels/{label}/preview",
+            get(preview_neo4j_label_nodes),
+        )
+        .route(
+            "/datasources/{id}/neo4j/relationship-types",
+            get(list_neo4j_relationship_types),
+        )
+        .route("/datasources/{id}/neo4j/indexes", get(list_neo4j_indexes))
+        .route(
+            "/datasources/{id}/neo4j/constraints",
+            get(list_neo4j_constraints),
+        )
         .route(
             "/datasources/{id}/milvus/collections",
             get(list_milvus_collections),
@@ -212,6 +321,34 @@ fn build_app_with_state(
             "/datasources/{id}/milvus/query",
             post(milvus_query_entities),
         )
+        .route(
+            "/datasources/{id}/milvus/collections/create",
+            post(milvus_create_collection),
+        )
+        .route(
+            "/datasources/{id}/milvus/collections/drop",
+            post(milvus_drop_collection),
+        )
+        .route(
+            "/datasources/{id}/milvus/collections/describe",
+            post(milvus_describe_collection),
+        )
+        .route(
+            "/datasources/{id}/milvus/collections/load",
+            post(milvus_load_collection),
+        )
+        .route(
+            "/datasources/{id}/milvus/collections/release",
+            post(milvus_release_collection),
+        )
+        .route(
+            "/datasources/{id}/milvus/indexes/create",
+            post(milvus_create_index),
+        )
+        .route(
+            "/datasources/{id}/milvus/delete",
+            post(milvus_delete_entities),
+        )
         .route("/datasources/{id}/tables", get(list_datasource_tables))
         .route(
             "/datasources/{id}/tables/{table}/columns",
@@ -232,9 +369,66 @@ fn build_app_with_state(
         .route("/execute", post(execute))
         .route("/vector/collections", get(list_vector_collections))
         .route("/vector/collections/create", post(create_vector_collection))
+        .route("/vector/collections/index", post(create_payload_index))
+        .route(
+            "/vector/collections/index/delete",
+            post(delete_payload_index),
+        )
         .route("/vector/points/upsert", post(upsert_vector_points))
         .route("/vector/search", post(search_vector))
         .route("/vector/points/delete", post(delete_vector_points))
+        .route("/vector/points/scroll", post(scroll_vector_points))
+        .route("/vector/points/get", post(get_vector_points))
+        .route("/vector/collections/delete", post(drop_vector_collection))
+        .route("/vector/collections/stats", post(vector_collection_stats))
+        .route(
+            "/vector/collections/snapshot",
+            post(snapshot_vector_collection),
+        )
+        .route(
+            "/vector/collections/restore",
+            post(restore_vector_collection).layer(DefaultBodyLimit::max(VECTOR_SNAPSHOT_MAX_BYTES)),
+        )
+        .route("/qdrant/collections", get(qdrant_list_collections))
+        .route(
+            "/qdrant/collections/{name}",
+            get(qdrant_get_collection)
+                .put(qdrant_create_collection)
+                .delete(qdrant_delete_collection),
+        )
+        .route(
+            "/qdrant/collections/{name}/exists",
+            get(qdrant_collection_exists),
+        )
+        .route("/qdrant/collections/{name}/index", put(qdrant_create_index))
+        .route(
+            "/qdrant/collections/{name}/index/{field}",
+            delete(qdrant_delete_index),
+        )
+        .route(
+            "/qdrant/collections/{name}/points",
+            put(qdrant_upsert_points).post(qdrant_retrieve_points),
+        )
+        .route(
+            "/qdrant/collections/{name}/points/{id}",
+            get(qdrant_get_point),
+        )
+        .route(
+            "/qdrant/collections/{name}/points/search",
+            post(qdrant_search_points),
+        )
+        .route(
+            "/qdrant/collections/{name}/points/query",
+            post(qdrant_query_points),
+        )
+        .route(
+            "/qdrant/collections/{name}/points/scroll",
+            post(qdrant_scroll_points),
+        )
+        .route(
+            "/qdrant/collections/{name}/points/delete",
+            post(qdrant_delete_points),
+        )
         .route(
             "/sql/datasources/{id}/tables/{table}/rows",
             get(list_sqlite_table_rows),
@@ -409,6 +603,8 @@ struct ProjectNode {
     label: String,
     kind: String,
     content: String,
+    #[serde(default)]
+    disabled: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -429,6 +625,8 @@ struct ProjectState {
     nodes: Vec<serde_json::Value>,
     edges: Vec<serde_json::Value>,
     variables: Vec<ProjectVariable>,
+    #[serde(default, skip_serializing_if = "IsolatedNodePolicy::is_default")]
+    isolated_node_policy: IsolatedNodePolicy,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -541,6 +739,10 @@ fn default_true() -> bool {
 #[serde(rename_all = "camelCase")]
 struct ExecuteRequest {
     nodes: Vec<ProjectNode>,
+    #[serde(default)]
+    edges: Vec<FlowEdge>,
+    #[serde(default)]
+    isolated_node_policy: IsolatedNodePolicy,
     variables: Vec<Variable>,
     output_style: OutputStyle,
 }
@@ -559,6 +761,10 @@ pub(crate) struct VariableSpec {
 #[serde(rename_all = "camelCase")]
 struct ExecutePreviewRequest {
     nodes: Vec<ProjectNode>,
+    #[serde(default)]
+    edges: Vec<FlowEdge>,
+    #[serde(default)]
+    isolated_node_policy: IsolatedNodePolicy,
     variables: Vec<VariableSpec>,
     output_style: OutputStyle,
 }
@@ -810,8 +1016,10 @@ fn sqlite_url_for_path(path: &std::path::Path) -> String {
 struct ProviderPublic {
     id: String,
     name: String,
-    provider: String,
+    provider: ProviderKind,
     base_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auth_header: Option<String>,
     default_chat_model: Option<String>,
     default_embedding_model: Option<String>,
     updated_at: String,
@@ -822,9 +1030,11 @@ struct ProviderPublic {
 struct ProviderStored {
     id: String,
     name: String,
-    provider: String,
+    provider: ProviderKind,
     base_url: String,
     api_key_enc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auth_header: Option<String>,
     default_chat_model: Option<String>,
     default_embedding_model: Option<String>,
     updated_at: String,
@@ -835,8 +1045,11 @@ struct ProviderStored {
 struct ProviderCreateRequest {
     name: String,
     provider: String,
+    #[serde(default)]
     base_url: String,
+    #[serde(default)]
     api_key: String,
+    auth_header: Option<String>,
     default_chat_model: Option<String>,
     default_embedding_model: Option<String>,
 }
@@ -847,6 +1060,7 @@ struct ProviderUpdateRequest {
     name: Option<String>,
     base_url: Option<String>,
     api_key: Option<String>,
+    auth_header: Option<String>,
     default_chat_model: Option<String>,
     default_embedding_model: Option<String>,
 }
@@ -870,6 +1084,7 @@ async fn list_providers(State(state): State<AppState>) -> axum::response::Respon
                     name: p.name,
                     provider: p.provider,
                     base_url: p.base_url,
+                    auth_header: p.auth_header,
                     default_chat_model: p.default_chat_model,
                     default_embedding_model: p.default_embedding_model,
                     updated_at: p.updated_at,
@@ -885,17 +1100,25 @@ async fn create_provider(
     State(state): State<AppState>,
     Json(req): Json<ProviderCreateRequest>,
 ) -> axum::response::Response {
-    if req.name.trim().is_empty() || req.api_key.trim().is_empty() {
+    let Some(kind) = ProviderKind::parse(&req.provider) else {
         return (
             StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "validation_failed" })),
+            Json(serde_json::json!({ "error": "unsupported_provider" })),
         )
             .into_response();
-    }
-    if req.provider != "siliconflow" {
+    };
+    let base_url = match req.base_url.trim() {
+        "" => kind.default_base_url().unwrap_or_default().to_string(),
+        url => url.to_string(),
+    };
+    if req.name.trim().is_empty()
+        || base_url.is_empty()
+        || (kind.requires_api_key() && req.api_key.trim().is_empty())
+        || !is_valid_auth_header(req.auth_header.as_deref())
+    {
         return (
             StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "unsupported_provider" })),
+            Json(serde_json::json!({ "error": "validation_failed" })),
         )
             .into_response();
     }
@@ -928,9 +1151,10 @@ async fn create_provider(
     let stored = ProviderStored {
         id: id.clone(),
         name: req.name,
-        provider: req.provider,
-        base_url: req.base_url,
+        provider: kind,
+        base_url,
         api_key_enc,
+        auth_header: req.auth_header.filter(|h| !h.trim().is_empty()),
         default_chat_model: req.default_chat_model,
         default_embedding_model: req.default_embedding_model,
         updated_at: now_ms().to_string(),
@@ -949,6 +1173,7 @@ async fn create_provider(
             name: stored.name,
             provider: stored.provider,
             base_url: stored.base_url,
+            auth_header: stored.auth_header,
             default_chat_model: stored.default_chat_model,
             default_embedding_model: stored.default_embedding_model,
             updated_at: stored.updated_at,
@@ -978,6 +1203,7 @@ async fn get_provider(
             name: stored.name,
             provider: stored.provider,
             base_url: stored.base_url,
+            auth_header: stored.auth_header,
             default_chat_model: stored.default_chat_model,
             default_embedding_model: stored.default_embedding_model,
             updated_at: stored.updated_at,
@@ -1008,6 +1234,16 @@ async fn update_provider(
     if let Some(v) = req.base_url {
         stored.base_url = v;
     }
+    if !is_valid_auth_header(req.auth_header.as_deref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response();
+    }
+    if let Some(v) = req.auth_header {
+        stored.auth_header = Some(v).filter(|h| !h.trim().is_empty());
+    }
     stored.default_chat_model = req.default_chat_model.or(stored.default_chat_model);
     stored.default_embedding_model = req
         .default_embedding_model
@@ -1054,6 +1290,7 @@ async fn update_provider(
             name: stored.name,
             provider: stored.provider,
             base_url: stored.base_url,
+            auth_header: stored.auth_header,
             default_chat_model: stored.default_chat_model,
             default_embedding_model: stored.default_embedding_model,
             updated_at: stored.updated_at,
@@ -1077,6 +1314,10 @@ async fn delete_provider(
     }
 }
 
+fn is_valid_auth_header(name: Option<&str>) -> bool {
+    name.is_none_or(|n| n.is_empty() || header::HeaderName::from_bytes(n.as_bytes()).is_ok())
+}
+
 async fn decrypt_provider_api_key(state: &AppState, id: &str) -> anyhow::Result<String> {
     let (key, _source) = crypto::load_or_init_data_key(&state.data_dir)?;
     let stored = load_provider(state, id).await?;
@@ -1106,13 +1347,6 @@ async fn call_provider_embeddings(
                 .into_response()
         }
     };
-    if stored.provider != "siliconflow" {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "unsupported_provider" })),
-        )
-            .into_response();
-    }
     if req.input.is_empty() || req.input.len() > 2048 {
         return (
             StatusCode::BAD_REQUEST,
@@ -1129,72 +1363,35 @@ async fn call_provider_embeddings(
             )
                 .into_response(),
         };
-    let model = req
-        .model
-        .or(stored.default_embedding_model)
-        .unwrap_or_else(|| "BAAI/bge-large-zh-v1.5".to_string());
-
-    let url = format!("{}/embeddings", stored.base_url.trim_end_matches('/'));
-    let client = reqwest::Client::new();
-    let body = serde_json::json!({
-        "model": model,
-        "input": req.input,
-    });
-    let resp =
-        match client
-            .post(url)
-            .bearer_auth(api_key)
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(err) => return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "request_failed", "message": err.to_string() })),
-            )
-                .into_response(),
-        };
-    let status = resp.status();
-    let text = resp.text().await.unwrap_or_default();
-    if !status.is_success() {
+    let client = ProviderClient::new(&stored, api_key);
+    let Some(model) = req.model.or_else(|| client.embedding_model()) else {
         return (
             StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "upstream_failed", "status": status.as_u16(), "body": text })),
+            Json(serde_json::json!({ "error": "validation_failed", "message": "model_required" })),
         )
             .into_response();
+    };
+    match client.embeddings(&model, &req.input).await {
+        Ok(embeddings) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "embeddings": embeddings })),
+        )
+            .into_response(),
+        Err(err) => provider_error_response(err),
     }
-    let v: serde_json::Value = match serde_json::from_str(&text) {
-        Ok(v) => v,
-        Err(_) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "parse_failed" })),
-            )
-                .into_response();
+}
+
+fn provider_error_response(err: ProviderError) -> axum::response::Response {
+    let body = match err {
+        ProviderError::Request(message) => {
+            serde_json::json!({ "error": "request_failed", "message": message })
         }
-    };
-    let data = v
-        .get("data")
-        .and_then(|d| d.as_array())
-        .cloned()
-        .unwrap_or_default();
-    let mut embeddings = Vec::<Vec<f32>>::new();
-    for item in data {
-        let Some(arr) = item.get("embedding").and_then(|e| e.as_array()) else {
-            continue;
-        };
-        let mut vec = Vec::with_capacity(arr.len());
-        for n in arr {
-            vec.push(n.as_f64().unwrap_or(0.0) as f32);
+        ProviderError::Upstream { status, body } => {
+            serde_json::json!({ "error": "upstream_failed", "status": status, "body": body })
         }
-        embeddings.push(vec);
-    }
-    (
-        StatusCode::OK,
-        Json(serde_json::json!({ "embeddings": embeddings })),
-    )
-        .into_response()
+        ProviderError::Parse => serde_json::json!({ "error": "parse_failed" }),
+    };
+    (StatusCode::BAD_REQUEST, Json(body)).into_response()
 }
 
 #[derive(Deserialize)]
@@ -1220,13 +1417,6 @@ async fn call_provider_chat_completions(
                 .into_response()
         }
     };
-    if stored.provider != "siliconflow" {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "unsupported_provider" })),
-        )
-            .into_response();
-    }
     if req.messages.is_empty() || req.messages.len() > 128 {
         return (
             StatusCode::BAD_REQUEST,
@@ -1243,59 +1433,25 @@ async fn call_provider_chat_completions(
             )
                 .into_response(),
         };
-    let model = req
-        .model
-        .or(stored.default_chat_model)
-        .unwrap_or_else(|| "deepseek-ai/DeepSeek-V3".to_string());
+    let client = ProviderClient::new(&stored, api_key);
+    let Some(model) = req.model.or_else(|| client.chat_model()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed", "message": "model_required" })),
+        )
+            .into_response();
+    };
 
-    let url = format!("{}/chat/completions", stored.base_url.trim_end_matches('/'));
-    let client = reqwest::Client::new();
     let body = serde_json::json!({
         "model": model,
         "messages": req.messages,
         "stream": req.stream.unwrap_or(false),
     });
-    let resp =
-        match client
-            .post(url)
-            .bearer_auth(api_key)
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(err) => return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "request_failed", "message": err.to_string() })),
-            )
-                .into_response(),
-        };
-    let status = resp.status();
-    let text = resp.text().await.unwrap_or_default();
-    if !status.is_success() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "upstream_failed", "status": status.as_u16(), "body": text })),
-        )
-            .into_response();
-    }
-    let v: serde_json::Value = match serde_json::from_str(&text) {
+    let v = match client.chat_completions(&body).await {
         Ok(v) => v,
-        Err(_) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({ "error": "parse_failed" })),
-            )
-                .into_response();
-        }
+        Err(err) => return provider_error_response(err),
     };
-    let content = v["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-    let reasoning = v["choices"][0]["message"]["reasoning_content"]
-        .as_str()
-        .map(|s| s.to_string());
+    let (content, reasoning) = client.completion_content(&v);
     (
         StatusCode::OK,
         Json(serde_json::json!({ "content": content, "reasoningContent": reasoning })),
@@ -1483,13 +1639,27 @@ struct JobSummary {
     summary: Option<String>,
 }
 
+static JSON_WRITE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+async fn write_json_atomically<T: Serialize>(
+    path: &std::path::Path,
+    value: &T,
+) -> anyhow::Result<()> {
+    // Background workers and handlers can write the same record concurrently,
+    // so each write gets its own temp file and the last rename wins.
+    let seq = JSON_WRITE_SEQ.fetch_add(1, Ordering::Relaxed);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{seq}.tmp"));
+    let text = serde_json::to_string_pretty(value)?;
+    tokio::fs::write(&tmp, text).await?;
+    tokio::fs::rename(tmp, path).await?;
+    Ok(())
+}
+
 async fn write_job(state: &AppState, job: &JobRecord) -> anyhow::Result<()> {
     let dir = state.data_dir.join("jobs");
     tokio::fs::create_dir_all(&dir).await?;
-    let path = dir.join(format!("{}.json", job.id));
-    let text = serde_json::to_string_pretty(job)?;
-    tokio::fs::write(path, text).await?;
-    Ok(())
+    write_json_atomically(&dir.join(format!("{}.json", job.id)), job).await
 }
 
 async fn load_job(state: &AppState, id: &str) -> anyhow::Result<JobRecord> {
@@ -1541,6 +1711,68 @@ async fn get_job(
     }
 }
 
+/// Registers a worker for `id`, or returns `None` if one is already running.
+/// The check and insert share one lock so concurrent starts cannot both win.
+fn register_running_job(state: &AppState, id: &str) -> Option<Arc<AtomicBool>> {
+    let mut jobs = state.running_jobs.lock().ok()?;
+    if jobs.contains_key(id) {
+        return None;
+    }
+    let flag = Arc::new(AtomicBool::new(false));
+    jobs.insert(id.to_string(), flag.clone());
+    Some(flag)
+}
+
+fn unregister_running_job(state: &AppState, id: &str) {
+    if let Ok(mut jobs) = state.running_jobs.lock() {
+        jobs.remove(id);
+    }
+}
+
+async fn cancel_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let mut job = match load_job(&state, &id).await {
+        Ok(job) => job,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "not_found", "id": id })),
+            )
+                .into_response();
+        }
+    };
+    let flag = state
+        .running_jobs
+        .lock()
+        .ok()
+        .and_then(|jobs| jobs.get(&id).cloned());
+    if let Some(flag) = flag {
+        flag.store(true, Ordering::SeqCst);
+        return (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({ "job": job, "cancelRequested": true })),
+        )
+            .into_response();
+    }
+    if job.status != "running" {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "job_not_running", "id": id, "status": job.status })),
+        )
+            .into_response();
+    }
+    job.status = "cancelled".to_string();
+    job.finished_at = Some(now_ms().to_string());
+    let _ = write_job(&state, &job).await;
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "job": job, "cancelRequested": true })),
+    )
+        .into_response()
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct EmbedToVectorJobRequest {
@@ -1625,10 +1857,24 @@ async fn job_embed_to_vector(
                 .into_response();
         }
     };
-    let model = provider
-        .default_embedding_model
-        .clone()
-        .unwrap_or_else(|| "BAAI/bge-large-zh-v1.5".to_string());
+    let client = ProviderClient::new(&provider, api_key);
+    let model = match vector_collection_embedding_model(&state, &req.collection)
+        .await
+        .or_else(|| client.embedding_model())
+    {
+        Some(model) => model,
+        None => {
+            job.status = "failed".to_string();
+            job.finished_at = Some(now_ms().to_string());
+            job.error = Some("model_required".to_string());
+            let _ = write_job(&state, &job).await;
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "provider_failed", "message": "model_required", "jobId": job_id })),
+            )
+                .into_response();
+        }
+    };
 
     let payload_fields = req.payload_fields.clone().unwrap_or_default();
     let mut ids = Vec::<String>::new();
@@ -1701,9 +1947,7 @@ async fn job_embed_to_vector(
             .into_response();
     }
 
-    let embeddings = match siliconflow_embeddings(&provider.base_url, &api_key, &model, &texts)
-        .await
-    {
+    let embeddings = match client.embeddings(&model, &texts).await {
         Ok(v) => v,
         Err(err) => {
             job.status = "failed".to_string();
@@ -1732,8 +1976,8 @@ async fn job_embed_to_vector(
 
     let points = ids
         .into_iter()
-        .zip(embeddings.into_iter())
-        .zip(payloads.into_iter())
+        .zip(embeddings)
+        .zip(payloads)
         .map(|((id, vector), payload)| VectorPoint {
             id,
             vector,
@@ -1742,7 +1986,7 @@ async fn job_embed_to_vector(
         })
         .collect::<Vec<_>>();
 
-    match vector_upsert_points_internal(&state, &req.collection, points).await {
+    match vector_upsert_points_internal(&state, &req.collection, points, Some(&model)).await {
         Ok(inserted) => {
             job.status = "succeeded".to_string();
             job.finished_at = Some(now_ms().to_string());
@@ -1765,67 +2009,356 @@ async fn job_embed_to_vector(
     }
 }
 
-async fn load_dataset_record(state: &AppState, id: &str) -> anyhow::Result<DatasetRecord> {
-    let path = state.data_dir.join("datasets").join(format!("{id}.json"));
-    let text = tokio::fs::read_to_string(path).await?;
-    Ok(serde_json::from_str(&text)?)
-}
-
-async fn siliconflow_embeddings(
-    base_url: &str,
-    api_key: &str,
-    model: &str,
-    input: &[String],
-) -> anyhow::Result<Vec<Vec<f32>>> {
-    let url = format!("{}/embeddings", base_url.trim_end_matches('/'));
-    let client = reqwest::Client::new();
-    let body = serde_json::json!({
-        "model": model,
-        "input": input,
-    });
-    let resp = client
-        .post(url)
-        .bearer_auth(api_key)
-        .json(&body)
-        .send()
-        .await?;
-    let status = resp.status();
-    let text = resp.text().await.unwrap_or_default();
-    if !status.is_success() {
-        anyhow::bail!("upstream_failed status={} body={}", status.as_u16(), text);
-    }
-    let v: serde_json::Value = serde_json::from_str(&text)?;
-    let data = v
-        .get("data")
-        .and_then(|d| d.as_array())
-        .cloned()
-        .unwrap_or_default();
-    let mut embeddings = Vec::<Vec<f32>>::new();
-    for item in data {
-        let Some(arr) = item.get("embedding").and_then(|e| e.as_array()) else {
-            continue;
-        };
-        let mut vec = Vec::with_capacity(arr.len());
-        for n in arr {
-            vec.push(n.as_f64().unwrap_or(0.0) as f32);
-        }
-        embeddings.push(vec);
-    }
-    Ok(embeddings)
-}
+const MILVUS_JOB_DEFAULT_BATCH_SIZE: usize = 64;
+const MILVUS_JOB_MAX_BATCH_SIZE: usize = 512;
 
-#[derive(Serialize)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OperationDescriptor {
-    id: String,
-    name: String,
-    kind: String,
-    input_schema: serde_json::Value,
+struct EmbedToMilvusJobRequest {
+    dataset_id: String,
+    provider_id: String,
+    data_source_id: String,
+    collection: String,
+    text_field: String,
+    vector_field: Option<String>,
+    #[serde(default)]
+    field_map: BTreeMap<String, String>,
+    batch_size: Option<usize>,
 }
 
-async fn list_operations() -> axum::response::Response {
-    let ops = vec![
-        OperationDescriptor {
+async fn job_embed_to_milvus(
+    State(state): State<AppState>,
+    Json(req): Json<EmbedToMilvusJobRequest>,
+) -> axum::response::Response {
+    let vector_field = req
+        .vector_field
+        .clone()
+        .filter(|f| !f.trim().is_empty())
+        .unwrap_or_else(|| "vector".to_string());
+    if req.dataset_id.trim().is_empty()
+        || req.provider_id.trim().is_empty()
+        || req.data_source_id.trim().is_empty()
+        || req.collection.trim().is_empty()
+        || req.text_field.trim().is_empty()
+        || req.field_map.contains_key(&vector_field)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response();
+    }
+
+    let ds = match load_datasource(&state, &req.data_source_id).await {
+        Ok(ds) => ds,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "not_found", "id": req.data_source_id })),
+            )
+                .into_response();
+        }
+    };
+    if ds.driver != "milvus" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "unsupported_driver", "driver": ds.driver })),
+        )
+            .into_response();
+    }
+    if !cfg!(feature = "milvus") {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            Json(serde_json::json!({ "error": "feature_not_enabled", "feature": "milvus" })),
+        )
+            .into_response();
+    }
+    if !ds.allow_write {
+        return json_error(StatusCode::FORBIDDEN, "forbidden", "未授权：写入能力已关闭");
+    }
+
+    let prepared = async {
+        let dataset = load_dataset_record(&state, &req.dataset_id)
+            .await
+            .map_err(|err| ("dataset_failed", err.to_string()))?;
+        let provider = load_provider(&state, &req.provider_id)
+            .await
+            .map_err(|_| ("provider_failed", "not_found".to_string()))?;
+        let api_key = decrypt_provider_api_key(&state, &req.provider_id)
+            .await
+            .map_err(|err| ("provider_failed", err.to_string()))?;
+        let cfg = decrypt_milvus_config(&state, &req.data_source_id)
+            .await
+            .map_err(|err| ("decrypt_failed", err.to_string()))?;
+        let provider_client = ProviderClient::new(&provider, api_key);
+        let model = provider_client
+            .embedding_model()
+            .ok_or(("provider_failed", "model_required".to_string()))?;
+        Ok::<_, (&str, String)>((dataset, provider_client, model, cfg))
+    }
+    .await;
+    let (dataset, provider_client, model, cfg) = match prepared {
+        Ok(v) => v,
+        Err((error, message)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": error, "message": message })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut rows = Vec::<MilvusEmbedRow>::new();
+    let mut skipped = 0usize;
+    for (idx, row) in dataset.rows.iter().enumerate() {
+        let Some(obj) = row.as_object() else {
+            skipped += 1;
+            continue;
+        };
+        let text = match obj.get(&req.text_field) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(serde_json::Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        if text.trim().is_empty() {
+            skipped += 1;
+            continue;
+        }
+        let mut entity = serde_json::Map::new();
+        for (target, source) in &req.field_map {
+            if let Some(v) = obj.get(source) {
+                entity.insert(target.clone(), v.clone());
+            }
+        }
+        rows.push((idx, text, entity));
+    }
+    if rows.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed", "message": "no_rows" })),
+        )
+            .into_response();
+    }
+
+    let stats = MilvusEmbedStats {
+        data_source_id: req.data_source_id.clone(),
+        collection: req.collection.clone(),
+        vector_field,
+        batch_size: req
+            .batch_size
+            .unwrap_or(MILVUS_JOB_DEFAULT_BATCH_SIZE)
+            .clamp(1, MILVUS_JOB_MAX_BATCH_SIZE),
+        total_rows: dataset.rows.len(),
+        skipped,
+        ..MilvusEmbedStats::default()
+    };
+    let job = JobRecord {
+        id: format!("job_{}", now_ms()),
+        job_type: "embed_to_milvus".to_string(),
+        status: "running".to_string(),
+        created_at: now_ms().to_string(),
+        finished_at: None,
+        summary: None,
+        stats: serde_json::to_value(&stats).unwrap_or_default(),
+        error: None,
+    };
+    let Some(cancel) = register_running_job(&state, &job.id) else {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "job_running", "id": job.id })),
+        )
+            .into_response();
+    };
+    if write_job(&state, &job).await.is_err() {
+        unregister_running_job(&state, &job.id);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed" })),
+        )
+            .into_response();
+    }
+
+    let run = MilvusEmbedRun {
+        provider_client,
+        model,
+        client: connectors::milvus::MilvusRestClient::new(cfg.base_url, cfg.token),
+        rows,
+        stats,
+    };
+    let background = job.clone();
+    tokio::spawn(async move {
+        let job_id = background.id.clone();
+        run_embed_to_milvus_job(&state, background, run, &cancel).await;
+        unregister_running_job(&state, &job_id);
+    });
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "job": job })),
+    )
+        .into_response()
+}
+
+type MilvusEmbedRow = (usize, String, serde_json::Map<String, serde_json::Value>);
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MilvusEmbedStats {
+    data_source_id: String,
+    collection: String,
+    vector_field: String,
+    batch_size: usize,
+    batches: usize,
+    failed_batches: usize,
+    total_rows: usize,
+    skipped: usize,
+    inserted: u64,
+    failed: usize,
+    failures: Vec<serde_json::Value>,
+}
+
+struct MilvusEmbedRun {
+    provider_client: ProviderClient,
+    model: String,
+    client: connectors::milvus::MilvusRestClient,
+    rows: Vec<MilvusEmbedRow>,
+    stats: MilvusEmbedStats,
+}
+
+async fn run_embed_to_milvus_job(
+    state: &AppState,
+    mut job: JobRecord,
+    run: MilvusEmbedRun,
+    cancel: &AtomicBool,
+) {
+    let MilvusEmbedRun {
+        provider_client,
+        model,
+        client,
+        rows,
+        mut stats,
+    } = run;
+
+    for (batch_index, batch) in rows.chunks(stats.batch_size).enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            job.status = "cancelled".to_string();
+            job.finished_at = Some(now_ms().to_string());
+            break;
+        }
+        stats.batches += 1;
+        let row_indexes = batch.iter().map(|(idx, _, _)| *idx).collect::<Vec<_>>();
+        let texts = batch
+            .iter()
+            .map(|(_, text, _)| text.clone())
+            .collect::<Vec<_>>();
+
+        let embeddings = provider_client
+            .embeddings(&model, &texts)
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|v| {
+                if v.len() == texts.len() {
+                    Ok(v)
+                } else {
+                    Err("embedding_count_mismatch".to_string())
+                }
+            });
+        let result = match embeddings {
+            Ok(embeddings) => {
+                let data = batch
+                    .iter()
+                    .zip(embeddings)
+                    .map(|((_, _, entity), vector)| {
+                        let mut entity = entity.clone();
+                        entity.insert(stats.vector_field.clone(), serde_json::json!(vector));
+                        serde_json::Value::Object(entity)
+                    })
+                    .collect::<Vec<_>>();
+                let body = serde_json::json!({ "collectionName": stats.collection, "data": data });
+                client
+                    .insert_entities(body)
+                    .await
+                    .and_then(|v| match v.get("code").and_then(|c| c.as_i64()) {
+                        Some(0) | None => Ok(v),
+                        Some(code) => Err(anyhow::anyhow!(
+                            "milvus code {}: {}",
+                            code,
+                            v.get("message").and_then(|m| m.as_str()).unwrap_or("")
+                        )),
+                    })
+                    .map_err(|err| ("insert", err.to_string()))
+            }
+            Err(message) => Err(("embedding", message)),
+        };
+        match result {
+            Ok(v) => {
+                stats.inserted += v
+                    .get("data")
+                    .and_then(|d| d.get("insertCount"))
+                    .and_then(|n| n.as_u64())
+                    .unwrap_or(batch.len() as u64);
+            }
+            Err((stage, message)) => {
+                stats.failed += batch.len();
+                stats.failed_batches += 1;
+                stats.failures.push(serde_json::json!({
+                    "batch": batch_index,
+                    "stage": stage,
+                    "rowIndexes": row_indexes,
+                    "message": message,
+                }));
+            }
+        }
+        job.summary = Some(format!(
+            "inserted={} failed={}",
+            stats.inserted, stats.failed
+        ));
+        job.stats = serde_json::to_value(&stats).unwrap_or_default();
+        let _ = write_job(state, &job).await;
+    }
+
+    if job.status == "running" {
+        job.finished_at = Some(now_ms().to_string());
+        job.status = if stats.failures.is_empty() {
+            "succeeded"
+        } else if stats.inserted > 0 {
+            "partial"
+        } else {
+            "failed"
+        }
+        .to_string();
+    }
+    job.summary = Some(format!(
+        "inserted={} failed={}",
+        stats.inserted, stats.failed
+    ));
+    job.error = stats
+        .failures
+        .first()
+        .and_then(|f| f.get("message"))
+        .and_then(|m| m.as_str())
+        .map(|m| m.to_string());
+    job.stats = serde_json::to_value(&stats).unwrap_or_default();
+    let _ = write_job(state, &job).await;
+}
+
+async fn load_dataset_record(state: &AppState, id: &str) -> anyhow::Result<DatasetRecord> {
+    let path = state.data_dir.join("datasets").join(format!("{id}.json"));
+    let text = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OperationDescriptor {
+    id: String,
+    name: String,
+    kind: String,
+    input_schema: serde_json::Value,
+}
+
+async fn list_operations() -> axum::response::Response {
+    let ops = vec![
+        OperationDescriptor {
             id: "sql.create_table".to_string(),
             name: "Create SQLite Table".to_string(),
             kind: "sync".to_string(),
@@ -1852,7 +2385,9 @@ async fn list_operations() -> axum::response::Response {
             input_schema: serde_json::json!({
                 "name": "string",
                 "dimension": "number",
-                "distance": "cosine"
+                "distance": "cosine|dot|euclidean",
+                "quantization": "none|int8|binary",
+                "embeddingModel": "string?"
             }),
         },
         OperationDescriptor {
@@ -1862,13 +2397,16 @@ async fn list_operations() -> axum::response::Response {
             input_schema: serde_json::json!({
                 "collection": "string",
                 "vector": "number[]",
+                "query": "string",
+                "providerId": "string?",
+                "mode": "vector|keyword|hybrid",
                 "topK": "number",
                 "filter": "object"
             }),
         },
         OperationDescriptor {
             id: "provider.embeddings".to_string(),
-            name: "Embedding (SiliconFlow)".to_string(),
+            name: "Embedding (OpenAI-compatible)".to_string(),
             kind: "sync".to_string(),
             input_schema: serde_json::json!({
                 "providerId": "string",
@@ -1889,6 +2427,22 @@ async fn list_operations() -> axum::response::Response {
                 "payloadFields": "string[]?"
             }),
         },
+        OperationDescriptor {
+            id: "job.ingest_documents".to_string(),
+            name: "Ingest Documents To Vector".to_string(),
+            kind: "job".to_string(),
+            input_schema: serde_json::json!({
+                "providerId": "string",
+                "collection": "string",
+                "documents": "{id,text,title?,metadata?}[]?",
+                "dataSourceId": "string?",
+                "query": "string?",
+                "idField": "string?",
+                "textField": "string?",
+                "chunking": "{strategy:tokens|headings,chunkSize?,overlap?}?",
+                "batchSize": "number?"
+            }),
+        },
     ];
     (StatusCode::OK, Json(ops)).into_response()
 }
@@ -3220,9 +3774,9 @@ async fn list_neo4j_labels(
 
     #[cfg(feature = "neo4j")]
     {
-        use neo4rs::{query, Graph, Row as _};
+        use neo4rs::{query, Graph};
 
-        let graph = match Graph::new(&cfg.uri, &cfg.username, &cfg.password) {
+        let graph = match Graph::new(&cfg.uri, &cfg.username, &cfg.password).await {
             Ok(g) => g,
             Err(err) => {
                 return (
@@ -3275,6 +3829,208 @@ async fn list_neo4j_labels(
     }
 }
 
+async fn query_neo4j_schema_rows(
+    state: &AppState,
+    id: &str,
+    cypher: &str,
+    params: serde_json::Value,
+    row_limit: usize,
+) -> Result<Vec<connectors::neo4j::GraphRow>, axum::response::Response> {
+    let ds = match load_datasource(state, id).await {
+        Ok(ds) => ds,
+        Err(_) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "not_found", "id": id })),
+            )
+                .into_response());
+        }
+    };
+    if ds.driver != "neo4j" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "unsupported_driver", "driver": ds.driver })),
+        )
+            .into_response());
+    }
+    if !ds.allow_schema.unwrap_or(true) {
+        return Err(json_error(
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            "未授权：结构/读取能力已关闭",
+        ));
+    }
+    if !cfg!(feature = "neo4j") {
+        return Err((
+            StatusCode::NOT_IMPLEMENTED,
+            Json(serde_json::json!({ "error": "feature_not_enabled", "feature": "neo4j" })),
+        )
+            .into_response());
+    }
+    let cfg = match decrypt_neo4j_config(state, id).await {
+        Ok(v) => v,
+        Err(err) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "decrypt_failed", "message": err.to_string() })),
+            )
+                .into_response());
+        }
+    };
+    match connectors::neo4j::execute_rows(
+        &cfg.uri,
+        &cfg.username,
+        &cfg.password,
+        cypher,
+        &params,
+        row_limit,
+    )
+    .await
+    {
+        Ok((rows, _)) => Ok(rows),
+        Err(err) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "query_failed", "message": err.to_string() })),
+        )
+            .into_response()),
+    }
+}
+
+async fn list_neo4j_relationship_types(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    let rows = match query_neo4j_schema_rows(
+        &state,
+        &id,
+        "CALL db.relationshipTypes() YIELD relationshipType RETURN relationshipType",
+        serde_json::Value::Null,
+        NEO4J_SCHEMA_ROW_LIMIT,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(resp) => return resp,
+    };
+    let types = connectors::neo4j::relationship_types_from_rows(&rows);
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "relationshipTypes": types })),
+    )
+        .into_response()
+}
+
+async fn list_neo4j_label_properties(
+    State(state): State<AppState>,
+    Path((id, label)): Path<(String, String)>,
+) -> axum::response::Response {
+    let rows = match query_neo4j_schema_rows(
+        &state,
+        &id,
+        "CALL db.schema.nodeTypeProperties() \
+         YIELD nodeLabels, propertyName, propertyTypes, mandatory \
+         WHERE $label IN nodeLabels AND propertyName IS NOT NULL \
+         RETURN propertyName, propertyTypes, mandatory \
+         ORDER BY propertyName",
+        serde_json::json!({ "label": label }),
+        NEO4J_SCHEMA_ROW_LIMIT,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(resp) => return resp,
+    };
+    let properties = connectors::neo4j::label_properties_from_rows(&rows);
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "label": label, "properties": properties })),
+    )
+        .into_response()
+}
+
+async fn list_neo4j_indexes(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match query_neo4j_schema_rows(
+        &state,
+        &id,
+        "SHOW INDEXES YIELD name, type, entityType, labelsOrTypes, properties, state \
+         RETURN name, type, entityType, labelsOrTypes, properties, state",
+        serde_json::Value::Null,
+        NEO4J_SCHEMA_ROW_LIMIT,
+    )
+    .await
+    {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "indexes": connectors::neo4j::rows_to_json(&rows) })),
+        )
+            .into_response(),
+        Err(resp) => resp,
+    }
+}
+
+async fn list_neo4j_constraints(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match query_neo4j_schema_rows(
+        &state,
+        &id,
+        "SHOW CONSTRAINTS YIELD name, type, entityType, labelsOrTypes, properties \
+         RETURN name, type, entityType, labelsOrTypes, properties",
+        serde_json::Value::Null,
+        NEO4J_SCHEMA_ROW_LIMIT,
+    )
+    .await
+    {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "constraints": connectors::neo4j::rows_to_json(&rows) })),
+        )
+            .into_response(),
+        Err(resp) => resp,
+    }
+}
+
+async fn preview_neo4j_label_nodes(
+    State(state): State<AppState>,
+    Path((id, label)): Path<(String, String)>,
+    axum::extract::Query(q): axum::extract::Query<PreviewRowsQuery>,
+) -> axum::response::Response {
+    if label.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "invalid_label", "label": label })),
+        )
+            .into_response();
+    }
+    let limit = q.limit.unwrap_or(50).clamp(1, 200) as usize;
+    let cypher = format!(
+        "MATCH (n:{}) RETURN n LIMIT $limit",
+        connectors::neo4j::escape_label(&label)
+    );
+    let rows = match query_neo4j_schema_rows(
+        &state,
+        &id,
+        &cypher,
+        serde_json::json!({ "limit": limit }),
+        limit,
+    )
+    .await
+    {
+        Ok(rows) => rows,
+        Err(resp) => return resp,
+    };
+    let nodes = connectors::neo4j::preview_nodes_from_rows(&rows, "n");
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "label": label, "nodes": nodes })),
+    )
+        .into_response()
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MilvusCollectionsResponse {
@@ -3398,6 +4154,7 @@ struct DataSourceCapabilitiesResponse {
     supports_sqlite_rows_api: bool,
     supports_milvus_collections: bool,
     supports_milvus_ops: bool,
+    supports_neo4j_schema: bool,
 }
 
 async fn get_datasource_capabilities(
@@ -3434,6 +4191,7 @@ async fn get_datasource_capabilities(
     let supports_sqlite_rows_api = driver == "sqlite";
     let supports_milvus_collections = driver == "milvus" && cfg!(feature = "milvus");
     let supports_milvus_ops = driver == "milvus" && cfg!(feature = "milvus");
+    let supports_neo4j_schema = driver == "neo4j" && cfg!(feature = "neo4j");
 
     (
         StatusCode::OK,
@@ -3452,11 +4210,252 @@ async fn get_datasource_capabilities(
             supports_sqlite_rows_api,
             supports_milvus_collections,
             supports_milvus_ops,
+            supports_neo4j_schema,
         }),
     )
         .into_response()
 }
 
+#[derive(Clone, Copy)]
+enum MilvusAdminPermission {
+    Schema,
+    Write,
+    Delete,
+}
+
+#[derive(Clone, Copy)]
+enum MilvusAdminOp {
+    CreateCollection,
+    DropCollection,
+    DescribeCollection,
+    LoadCollection,
+    ReleaseCollection,
+    CreateIndex,
+    DeleteEntities,
+}
+
+impl MilvusAdminOp {
+    fn permission(self) -> MilvusAdminPermission {
+        match self {
+            MilvusAdminOp::DescribeCollection => MilvusAdminPermission::Schema,
+            MilvusAdminOp::CreateCollection
+            | MilvusAdminOp::LoadCollection
+            | MilvusAdminOp::ReleaseCollection
+            | MilvusAdminOp::CreateIndex => MilvusAdminPermission::Write,
+            MilvusAdminOp::DropCollection | MilvusAdminOp::DeleteEntities => {
+                MilvusAdminPermission::Delete
+            }
+        }
+    }
+}
+
+async fn milvus_admin_client(
+    state: &AppState,
+    id: &str,
+    permission: MilvusAdminPermission,
+) -> Result<connectors::milvus::MilvusRestClient, axum::response::Response> {
+    let ds = match load_datasource(state, id).await {
+        Ok(ds) => ds,
+        Err(_) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "not_found", "id": id })),
+            )
+                .into_response());
+        }
+    };
+    if ds.driver != "milvus" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "unsupported_driver", "driver": ds.driver })),
+        )
+            .into_response());
+    }
+    if !cfg!(feature = "milvus") {
+        return Err((
+            StatusCode::NOT_IMPLEMENTED,
+            Json(serde_json::json!({ "error": "feature_not_enabled", "feature": "milvus" })),
+        )
+            .into_response());
+    }
+    match permission {
+        MilvusAdminPermission::Schema if !ds.allow_schema.unwrap_or(true) => {
+            return Err(json_error(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "未授权：结构/读取能力已关闭",
+            ));
+        }
+        MilvusAdminPermission::Write if !ds.allow_write => {
+            return Err(json_error(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "未授权：写入能力已关闭",
+            ));
+        }
+        MilvusAdminPermission::Delete if !ds.allow_delete => {
+            return Err(json_error(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "未授权：删除能力已关闭",
+            ));
+        }
+        _ => {}
+    }
+    let cfg = match decrypt_milvus_config(state, id).await {
+        Ok(v) => v,
+        Err(err) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "decrypt_failed", "message": err.to_string() })),
+            )
+                .into_response());
+        }
+    };
+    Ok(connectors::milvus::MilvusRestClient::new(
+        cfg.base_url,
+        cfg.token,
+    ))
+}
+
+fn milvus_admin_response(result: anyhow::Result<serde_json::Value>) -> axum::response::Response {
+    match result {
+        Ok(v) => (StatusCode::OK, Json(v)).into_response(),
+        Err(err) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "request_failed", "message": err.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+fn validate_milvus_admin_body(op: MilvusAdminOp, body: &serde_json::Value) -> Result<(), String> {
+    let collection = body
+        .get("collectionName")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if collection.trim().is_empty() {
+        return Err("collectionName 不能为空".to_string());
+    }
+    match op {
+        MilvusAdminOp::CreateCollection => {
+            if body.get("dimension").and_then(|v| v.as_u64()).is_some() {
+                return Ok(());
+            }
+            let fields = body
+                .get("schema")
+                .and_then(|v| v.get("fields"))
+                .and_then(|v| v.as_array())
+                .filter(|fields| !fields.is_empty())
+                .ok_or_else(|| "需要 dimension 或 schema.fields".to_string())?;
+            for field in fields {
+                let name = field
+                    .get("fieldName")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let data_type = field.get("dataType").and_then(|v| v.as_str()).unwrap_or("");
+                if name.trim().is_empty() || data_type.trim().is_empty() {
+                    return Err("schema.fields 需要 fieldName 与 dataType".to_string());
+                }
+            }
+            Ok(())
+        }
+        MilvusAdminOp::CreateIndex => match body.get("indexParams").and_then(|v| v.as_array()) {
+            Some(params) if !params.is_empty() => Ok(()),
+            _ => Err("indexParams 不能为空".to_string()),
+        },
+        MilvusAdminOp::DeleteEntities => {
+            let filter = body.get("filter").and_then(|v| v.as_str()).unwrap_or("");
+            if filter.trim().is_empty() {
+                return Err("filter 不能为空".to_string());
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn milvus_admin(
+    state: &AppState,
+    id: &str,
+    op: MilvusAdminOp,
+    body: serde_json::Value,
+) -> axum::response::Response {
+    let client = match milvus_admin_client(state, id, op.permission()).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+    if let Err(msg) = validate_milvus_admin_body(op, &body) {
+        return json_error(StatusCode::BAD_REQUEST, "invalid_request", &msg);
+    }
+    let result = match op {
+        MilvusAdminOp::CreateCollection => client.create_collection(body).await,
+        MilvusAdminOp::DropCollection => client.drop_collection(body).await,
+        MilvusAdminOp::DescribeCollection => client.describe_collection(body).await,
+        MilvusAdminOp::LoadCollection => client.load_collection(body).await,
+        MilvusAdminOp::ReleaseCollection => client.release_collection(body).await,
+        MilvusAdminOp::CreateIndex => client.create_index(body).await,
+        MilvusAdminOp::DeleteEntities => client.delete_entities(body).await,
+    };
+    milvus_admin_response(result)
+}
+
+async fn milvus_create_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::CreateCollection, body).await
+}
+
+async fn milvus_drop_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::DropCollection, body).await
+}
+
+async fn milvus_describe_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::DescribeCollection, body).await
+}
+
+async fn milvus_load_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::LoadCollection, body).await
+}
+
+async fn milvus_release_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::ReleaseCollection, body).await
+}
+
+async fn milvus_create_index(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::CreateIndex, body).await
+}
+
+async fn milvus_delete_entities(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> axum::response::Response {
+    milvus_admin(&state, &id, MilvusAdminOp::DeleteEntities, body).await
+}
+
 async fn milvus_insert_entities(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -4371,14 +5370,27 @@ async fn write_session(state: &AppState, s: &Session) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn execute(Json(req): Json<ExecuteRequest>) -> axum::response::Response {
-    let mut vars = HashMap::<String, String>::new();
-    for v in req.variables.iter() {
-        vars.insert(v.name.clone(), v.value.clone());
-    }
-
-    let nodes = req
-        .nodes
+fn order_engine_nodes(
+    nodes: &[ProjectNode],
+    edges: &[FlowEdge],
+    policy: IsolatedNodePolicy,
+) -> (Vec<EngineNode>, Vec<TraceMessage>) {
+    let flow = topo_sort_nodes(
+        &nodes
+            .iter()
+            .map(|n| FlowNodeRef {
+                id: &n.id,
+                disabled: n.disabled,
+            })
+            .collect::<Vec<_>>(),
+        &edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect::<Vec<_>>(),
+        policy,
+    );
+    let engine_nodes = flow
+        .apply(nodes)
         .into_iter()
         .map(|n| EngineNode {
             id: n.id,
@@ -4387,9 +5399,21 @@ async fn execute(Json(req): Json<ExecuteRequest>) -> axum::response::Response {
             content: n.content,
         })
         .collect::<Vec<_>>();
+    (engine_nodes, flow.messages)
+}
+
+async fn execute(Json(req): Json<ExecuteRequest>) -> axum::response::Response {
+    let mut vars = HashMap::<String, String>::new();
+    for v in req.variables.iter() {
+        vars.insert(v.name.clone(), v.value.clone());
+    }
+
+    let (nodes, flow_messages) =
+        order_engine_nodes(&req.nodes, &req.edges, req.isolated_node_policy);
 
     let now = now_ms().to_string();
-    let trace = render_with_trace(&nodes, &vars, req.output_style, &format!("run_{now}"), &now);
+    let mut trace = render_with_trace(&nodes, &vars, req.output_style, &format!("run_{now}"), &now);
+    trace.messages.extend(flow_messages);
     (StatusCode::OK, Json(trace)).into_response()
 }
 
@@ -4413,19 +5437,12 @@ async fn execute_preview(
         messages.push(out.trace_message);
     }
 
-    let nodes = req
-        .nodes
-        .into_iter()
-        .map(|n| EngineNode {
-            id: n.id,
-            label: n.label,
-            kind: kind_from_string(&n.kind),
-            content: n.content,
-        })
-        .collect::<Vec<_>>();
+    let (nodes, flow_messages) =
+        order_engine_nodes(&req.nodes, &req.edges, req.isolated_node_policy);
 
     let now = now_ms().to_string();
     let mut trace = render_with_trace(&nodes, &vars, req.output_style, &format!("run_{now}"), &now);
+    trace.messages.extend(flow_messages);
     trace.messages.extend(messages);
     (StatusCode::OK, Json(trace)).into_response()
 }
@@ -4450,19 +5467,25 @@ async fn resolve_sql_value(url: &str, query: &str) -> anyhow::Result<String> {
         .unwrap_or_default())
 }
 
+#[cfg(feature = "neo4j")]
+const NEO4J_DEFAULT_ROW_LIMIT: usize = 100;
+#[cfg(feature = "neo4j")]
+const NEO4J_MAX_ROW_LIMIT: usize = 1000;
+
+const NEO4J_SCHEMA_ROW_LIMIT: usize = 1000;
+
 #[cfg(feature = "neo4j")]
 async fn resolve_neo4j_value(
     state: &AppState,
     data_source_id: &str,
     cypher: &str,
-) -> anyhow::Result<String> {
-    use neo4rs::{query, Graph, Row as _};
+) -> anyhow::Result<(String, serde_json::Value)> {
+    use connectors::neo4j::{format_rows, Neo4jOutputFormat};
 
     let cfg = decrypt_neo4j_config(state, data_source_id).await?;
-    let graph = Graph::new(&cfg.uri, &cfg.username, &cfg.password)?;
 
     let cypher = cypher.trim();
-    let (cypher, params) = if cypher.starts_with('{') {
+    let (cypher, params, format, row_limit) = if cypher.starts_with('{') {
         let json: serde_json::Value = serde_json::from_str(cypher)?;
         let cypher = json
             .get("cypher")
@@ -4473,55 +5496,44 @@ async fn resolve_neo4j_value(
             .get("params")
             .cloned()
             .unwrap_or(serde_json::Value::Null);
-        (cypher, params)
+        let format =
+            Neo4jOutputFormat::parse(json.get("format").and_then(|v| v.as_str()).unwrap_or(""))
+                .ok_or_else(|| anyhow::anyhow!("unsupported_format"))?;
+        let row_limit = json
+            .get("rowLimit")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(NEO4J_DEFAULT_ROW_LIMIT);
+        (cypher, params, format, row_limit)
     } else {
-        (cypher.to_string(), serde_json::Value::Null)
-    };
-
-    let mut q = query(&cypher);
-    if let Some(obj) = params.as_object() {
-        for (k, v) in obj {
-            match v {
-                serde_json::Value::Null => {}
-                serde_json::Value::Bool(b) => {
-                    q = q.param(k, *b);
-                }
-                serde_json::Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        q = q.param(k, i);
-                    } else if let Some(f) = n.as_f64() {
-                        q = q.param(k, f);
-                    }
-                }
-                serde_json::Value::String(s) => {
-                    q = q.param(k, s.as_str());
-                }
-                other => {
-                    q = q.param(k, other.to_string());
-                }
-            }
-        }
-    }
-
-    let mut result = graph.execute(q).await?;
-    let Some(row) = result.next().await? else {
-        return Ok(String::new());
+        (
+            cypher.to_string(),
+            serde_json::Value::Null,
+            Neo4jOutputFormat::Value,
+            NEO4J_DEFAULT_ROW_LIMIT,
+        )
     };
+    let row_limit = row_limit.clamp(1, NEO4J_MAX_ROW_LIMIT);
+
+    let (rows, truncated) = connectors::neo4j::execute_rows(
+        &cfg.uri,
+        &cfg.username,
+        &cfg.password,
+        &cypher,
+        &params,
+        row_limit,
+    )
+    .await?;
 
-    if let Ok(v) = row.get::<String>("value") {
-        return Ok(v);
-    }
-    if let Ok(v) = row.get::<i64>("value") {
-        return Ok(v.to_string());
-    }
-    if let Ok(v) = row.get::<f64>("value") {
-        return Ok(v.to_string());
-    }
-    if let Ok(v) = row.get::<bool>("value") {
-        return Ok(v.to_string());
-    }
-
-    Ok("<unprintable>".to_string())
+    Ok((
+        format_rows(&rows, format),
+        serde_json::json!({
+            "format": format.as_str(),
+            "rowLimit": row_limit,
+            "rows": rows.len(),
+            "truncated": truncated,
+        }),
+    ))
 }
 
 #[cfg(not(feature = "neo4j"))]
@@ -4529,7 +5541,7 @@ async fn resolve_neo4j_value(
     _state: &AppState,
     _data_source_id: &str,
     _cypher: &str,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<(String, serde_json::Value)> {
     anyhow::bail!("feature_not_enabled")
 }
 
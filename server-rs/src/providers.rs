@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ProviderStored;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProviderKind {
+    Siliconflow,
+    OpenaiCompatible,
+    Openai,
+    Deepseek,
+    Ollama,
+    Vllm,
+    Lmstudio,
+}
+
+impl ProviderKind {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(value.trim().to_string())).ok()
+    }
+
+    pub(crate) fn default_base_url(self) -> Option<&'static str> {
+        match self {
+            ProviderKind::Siliconflow => Some("https://api.siliconflow.cn/v1"),
+            ProviderKind::Openai => Some("https://api.openai.com/v1"),
+            ProviderKind::Deepseek => Some("https://api.deepseek.com/v1"),
+            ProviderKind::Ollama => Some("http://localhost:11434/v1"),
+            ProviderKind::Vllm => Some("http://localhost:8000/v1"),
+            ProviderKind::Lmstudio => Some("http://localhost:1234/v1"),
+            ProviderKind::OpenaiCompatible => None,
+        }
+    }
+
+    fn default_chat_model(self) -> Option<&'static str> {
+        match self {
+            ProviderKind::Siliconflow => Some("deepseek-ai/DeepSeek-V3"),
+            ProviderKind::Openai => Some("gpt-4o-mini"),
+            ProviderKind::Deepseek => Some("deepseek-chat"),
+            ProviderKind::Ollama => Some("llama3.1"),
+            _ => None,
+        }
+    }
+
+    fn default_embedding_model(self) -> Option<&'static str> {
+        match self {
+            ProviderKind::Siliconflow => Some("BAAI/bge-large-zh-v1.5"),
+            ProviderKind::Openai => Some("text-embedding-3-small"),
+            ProviderKind::Ollama => Some("nomic-embed-text"),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn requires_api_key(self) -> bool {
+        matches!(
+            self,
+            ProviderKind::Siliconflow | ProviderKind::Openai | ProviderKind::Deepseek
+        )
+    }
+
+    fn inline_think_tags(self) -> bool {
+        matches!(
+            self,
+            ProviderKind::OpenaiCompatible
+                | ProviderKind::Ollama
+                | ProviderKind::Vllm
+                | ProviderKind::Lmstudio
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ProviderError {
+    Request(String),
+    Upstream { status: u16, body: String },
+    Parse,
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderError::Request(message) => write!(f, "request_failed: {message}"),
+            ProviderError::Upstream { status, body } => {
+                write!(f, "upstream_failed status={status} body={body}")
+            }
+            ProviderError::Parse => write!(f, "parse_failed"),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ProviderClient {
+    kind: ProviderKind,
+    base_url: String,
+    api_key: String,
+    auth_header: Option<String>,
+    default_chat_model: Option<String>,
+    default_embedding_model: Option<String>,
+}
+
+impl ProviderClient {
+    pub(crate) fn new(stored: &ProviderStored, api_key: String) -> Self {
+        Self {
+            kind: stored.provider,
+            base_url: stored.base_url.trim_end_matches('/').to_string(),
+            api_key,
+            auth_header: stored.auth_header.clone(),
+            default_chat_model: stored.default_chat_model.clone(),
+            default_embedding_model: stored.default_embedding_model.clone(),
+        }
+    }
+
+    pub(crate) fn chat_model(&self) -> Option<String> {
+        self.default_chat_model
+            .clone()
+            .or_else(|| self.kind.default_chat_model().map(str::to_string))
+    }
+
+    pub(crate) fn embedding_model(&self) -> Option<String> {
+        self.default_embedding_model
+            .clone()
+            .or_else(|| self.kind.default_embedding_model().map(str::to_string))
+    }
+
+    async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, ProviderError> {
+        let mut request = reqwest::Client::new()
+            .post(format!("{}/{path}", self.base_url))
+            .json(body);
+        if !self.api_key.is_empty() {
+            request = match self.auth_header.as_deref() {
+                Some(name) if !name.eq_ignore_ascii_case("authorization") => {
+                    request.header(name, &self.api_key)
+                }
+                _ => request.bearer_auth(&self.api_key),
+            };
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|err| ProviderError::Request(err.to_string()))?;
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(ProviderError::Upstream {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+        serde_json::from_str(&text).map_err(|_| ProviderError::Parse)
+    }
+
+    pub(crate) async fn chat_completions(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, ProviderError> {
+        self.post("chat/completions", body).await
+    }
+
+    pub(crate) async fn embeddings(
+        &self,
+        model: &str,
+        input: &[String],
+    ) -> Result<Vec<Vec<f32>>, ProviderError> {
+        let body = serde_json::json!({
+            "model": model,
+            "input": input,
+        });
+        let v = self.post("embeddings", &body).await?;
+        let mut data = v
+            .get("data")
+            .and_then(|d| d.as_array())
+            .cloned()
+            .unwrap_or_default();
+        data.sort_by_key(|item| item.get("index").and_then(|i| i.as_u64()));
+        let mut embeddings = Vec::<Vec<f32>>::new();
+        for item in data {
+            let Some(arr) = item.get("embedding").and_then(|e| e.as_array()) else {
+                continue;
+            };
+            let mut vec = Vec::with_capacity(arr.len());
+            for n in arr {
+                vec.push(n.as_f64().unwrap_or(0.0) as f32);
+            }
+            embeddings.push(vec);
+        }
+        Ok(embeddings)
+    }
+
+    pub(crate) fn completion_content(&self, v: &serde_json::Value) -> (String, Option<String>) {
+        let message = &v["choices"][0]["message"];
+        let content = message["content"].as_str().unwrap_or("");
+        let reasoning = ["reasoning_content", "reasoning"]
+            .iter()
+            .find_map(|key| message[*key].as_str().filter(|s| !s.is_empty()))
+            .map(|s| s.to_string());
+        if reasoning.is_none() && self.kind.inline_think_tags() {
+            if let Some((thought, answer)) = split_think_tags(content) {
+                return (answer, Some(thought));
+            }
+        }
+        (content.to_string(), reasoning)
+    }
+}
+
+fn split_think_tags(content: &str) -> Option<(String, String)> {
+    let rest = content.trim_start();
+    let rest = rest.strip_prefix("<think>").unwrap_or(rest);
+    let (thought, answer) = rest.split_once("</think>")?;
+    Some((thought.trim().to_string(), answer.trim_start().to_string()))
+}
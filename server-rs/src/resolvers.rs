@@ -31,6 +31,8 @@ impl ResolverRegistry {
         by_scheme.insert("sqlite", resolve_sqlite);
         by_scheme.insert("neo4j", resolve_neo4j);
         by_scheme.insert("milvus", resolve_milvus);
+        by_scheme.insert("dataset", resolve_dataset);
+        by_scheme.insert("run", resolve_run);
         Self { by_scheme }
     }
 
@@ -141,7 +143,7 @@ pub(crate) async fn resolve_variable_with_trace(
         }
         Err(err) => {
             let err_string = err.to_string();
-            let error_code = classify_error_code(&err_string);
+            let error_code = classify_error_code(&err);
             ResolveWithTrace {
                 result: Err(err),
                 trace_message: TraceMessage {
@@ -181,7 +183,16 @@ fn clamp_string(s: &str, max_bytes: usize) -> (String, bool) {
     (s[..cut].to_string(), true)
 }
 
-fn classify_error_code(err: &str) -> String {
+fn classify_error_code(err: &anyhow::Error) -> String {
+    let not_found = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::NotFound)
+    });
+    if not_found {
+        return "not_found".to_string();
+    }
+    let err = err.to_string();
     let e = err.trim();
     if e == "resolver_missing" {
         return "resolver_missing".to_string();
@@ -195,6 +206,15 @@ fn classify_error_code(err: &str) -> String {
     if e == "unsupported_op" {
         return "unsupported_op".to_string();
     }
+    if e == "invalid_spec" || e == "unsupported_sample" || e == "missing_stratify_by" {
+        return "invalid_spec".to_string();
+    }
+    if e == "unsupported_format" || e == "missing_cypher" {
+        return "invalid_spec".to_string();
+    }
+    if e == "segment_not_found" {
+        return "segment_not_found".to_string();
+    }
     if e.contains("decrypt failed") || e.contains("missing DATA_KEY") {
         return "decrypt_failed".to_string();
     }
@@ -270,11 +290,12 @@ fn resolve_neo4j(state: AppState, resolver: String, v: VariableSpec) -> Resolver
             anyhow::bail!("Cypher 不能为空");
         }
         let data_source_id = resolver.trim_start_matches("neo4j://");
-        let out = crate::resolve_neo4j_value(&state, data_source_id, &v.value).await?;
+        let (out, debug) = crate::resolve_neo4j_value(&state, data_source_id, &v.value).await?;
         Ok(ResolvedValue {
             string_value: out,
             debug_json: Some(json!({
                 "dataSourceId": data_source_id,
+                "result": debug,
             })),
         })
     })
@@ -292,3 +313,57 @@ fn resolve_milvus(state: AppState, resolver: String, v: VariableSpec) -> Resolve
         })
     })
 }
+
+fn resolve_dataset(state: AppState, resolver: String, v: VariableSpec) -> ResolverFuture {
+    Box::pin(async move {
+        let dataset_id = resolver.trim_start_matches("dataset://");
+        let spec = crate::few_shot::FewShotSpec::parse(&v.value)?;
+        let dataset = crate::load_dataset_record(&state, dataset_id).await?;
+        let sample = crate::few_shot::sample_rows(&dataset.rows, &spec, now_ms() as u64)?;
+        Ok(ResolvedValue {
+            string_value: sample.text,
+            debug_json: Some(json!({
+                "datasetId": dataset_id,
+                "strategy": sample.strategy.as_str(),
+                "seed": sample.seed,
+                "totalRows": dataset.rows.len(),
+                "matchedRows": sample.matched_rows,
+                "rowIndexes": sample.row_indexes,
+            })),
+        })
+    })
+}
+
+fn resolve_run(state: AppState, resolver: String, v: VariableSpec) -> ResolverFuture {
+    Box::pin(async move {
+        let run_id = resolver.trim_start_matches("run://");
+        let run = crate::runs::load_run_record(&state, run_id).await?;
+        let selector = v.value.trim();
+        if selector.is_empty() {
+            return Ok(ResolvedValue {
+                string_value: run.trace.text,
+                debug_json: Some(json!({
+                    "runId": run_id,
+                    "datasetId": run.dataset_id,
+                    "rowIndex": run.row_index,
+                })),
+            });
+        }
+        let segment = run
+            .trace
+            .segments
+            .iter()
+            .find(|s| s.node_id == selector)
+            .or_else(|| run.trace.segments.iter().find(|s| s.label == selector))
+            .ok_or_else(|| anyhow::anyhow!("segment_not_found"))?;
+        Ok(ResolvedValue {
+            string_value: segment.rendered.clone(),
+            debug_json: Some(json!({
+                "runId": run_id,
+                "datasetId": run.dataset_id,
+                "rowIndex": run.row_index,
+                "nodeId": segment.node_id,
+            })),
+        })
+    })
+}
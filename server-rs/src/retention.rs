@@ -0,0 +1,240 @@
+use std::io::Write as _;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    experiments::pinned_run_ids,
+    now_ms,
+    run_index::{list_indexed_runs, remove_runs},
+    write_job, AppState, JobRecord,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PruneRunsRequest {
+    #[serde(default)]
+    dataset_id: Option<String>,
+    #[serde(default)]
+    keep_last_per_row: Option<u32>,
+    #[serde(default)]
+    max_age_seconds: Option<u64>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Upper bound on run ids and failures kept in the job record; the archive
+/// holds the full list of pruned runs.
+const PRUNE_STATS_MAX_IDS: usize = 200;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PruneRunsStats {
+    dataset_id: Option<String>,
+    keep_last_per_row: Option<u32>,
+    max_age_seconds: Option<u64>,
+    dry_run: bool,
+    scanned: u64,
+    pinned: u64,
+    candidates: u64,
+    pruned: u64,
+    failed: u64,
+    run_ids: Vec<String>,
+    run_ids_truncated: bool,
+    failures: Vec<serde_json::Value>,
+    archive: Option<String>,
+    archive_bytes: u64,
+}
+
+fn write_archive(
+    runs_dir: std::path::PathBuf,
+    archive_path: std::path::PathBuf,
+    run_ids: Vec<String>,
+) -> anyhow::Result<(u64, Vec<(String, String)>)> {
+    if let Some(parent) = archive_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(&archive_path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    let mut invalid = Vec::new();
+    for run_id in run_ids {
+        let Ok(text) = std::fs::read_to_string(runs_dir.join(format!("{run_id}.json"))) else {
+            continue;
+        };
+        let value = match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(value) => value,
+            Err(err) => {
+                invalid.push((run_id, err.to_string()));
+                continue;
+            }
+        };
+        serde_json::to_writer(&mut encoder, &value)?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()?.sync_all()?;
+    Ok((std::fs::metadata(&archive_path)?.len(), invalid))
+}
+
+fn record_failure(stats: &mut PruneRunsStats, run_id: String, message: String) {
+    stats.failed += 1;
+    if stats.failures.len() < PRUNE_STATS_MAX_IDS {
+        stats.failures.push(serde_json::json!({
+            "runId": run_id,
+            "message": message,
+        }));
+    }
+}
+
+pub(crate) async fn job_prune_runs(
+    State(state): State<AppState>,
+    Json(req): Json<PruneRunsRequest>,
+) -> axum::response::Response {
+    if req.keep_last_per_row.is_none() && req.max_age_seconds.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response();
+    }
+
+    let job_id = format!("job_{}", now_ms());
+    let mut job = JobRecord {
+        id: job_id.clone(),
+        job_type: "prune_runs".to_string(),
+        status: "running".to_string(),
+        created_at: now_ms().to_string(),
+        finished_at: None,
+        summary: None,
+        stats: serde_json::json!({}),
+        error: None,
+    };
+    let _ = write_job(&state, &job).await;
+
+    let mut stats = PruneRunsStats {
+        dataset_id: req.dataset_id.clone(),
+        keep_last_per_row: req.keep_last_per_row,
+        max_age_seconds: req.max_age_seconds,
+        dry_run: req.dry_run,
+        ..PruneRunsStats::default()
+    };
+
+    let runs = match list_indexed_runs(&state, req.dataset_id.as_deref()).await {
+        Ok(runs) => runs,
+        Err(err) => {
+            job.status = "failed".to_string();
+            job.finished_at = Some(now_ms().to_string());
+            job.error = Some(err.to_string());
+            let _ = write_job(&state, &job).await;
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "index_failed", "job": job })),
+            )
+                .into_response();
+        }
+    };
+    let pinned = pinned_run_ids(&state).await;
+    let cutoff = req
+        .max_age_seconds
+        .map(|secs| (now_ms() as u64).saturating_sub(secs * 1000));
+
+    let mut candidates = Vec::<String>::new();
+    let mut rank = 0u32;
+    let mut prev_key: Option<(&str, u64)> = None;
+    for run in &runs {
+        let key = (run.dataset_id.as_str(), run.row_index);
+        rank = if prev_key == Some(key) { rank + 1 } else { 0 };
+        prev_key = Some(key);
+        stats.scanned += 1;
+
+        let over_count = req.keep_last_per_row.is_some_and(|n| rank >= n);
+        let too_old = cutoff.is_some_and(|c| run.created_at < c);
+        if !over_count && !too_old {
+            continue;
+        }
+        if pinned.contains(&run.run_id) {
+            stats.pinned += 1;
+            continue;
+        }
+        candidates.push(run.run_id.clone());
+    }
+    stats.candidates = candidates.len() as u64;
+    stats.run_ids = candidates
+        .iter()
+        .take(PRUNE_STATS_MAX_IDS)
+        .cloned()
+        .collect();
+    stats.run_ids_truncated = candidates.len() > PRUNE_STATS_MAX_IDS;
+
+    if !req.dry_run && !candidates.is_empty() {
+        let archive_name = format!("runs_{job_id}.jsonl.gz");
+        let archive_path = state.data_dir.join("archives").join(&archive_name);
+        let runs_dir = state.data_dir.join("runs");
+        let run_ids = candidates.clone();
+        let archived =
+            tokio::task::spawn_blocking(move || write_archive(runs_dir, archive_path, run_ids))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|r| r);
+        match archived {
+            Ok((bytes, invalid)) => {
+                stats.archive = Some(format!("archives/{archive_name}"));
+                stats.archive_bytes = bytes;
+                for (run_id, message) in invalid {
+                    candidates.retain(|id| *id != run_id);
+                    record_failure(&mut stats, run_id, format!("invalid_run: {message}"));
+                }
+            }
+            Err(err) => {
+                job.status = "failed".to_string();
+                job.finished_at = Some(now_ms().to_string());
+                job.error = Some(err.to_string());
+                job.stats = serde_json::to_value(&stats).unwrap_or_default();
+                let _ = write_job(&state, &job).await;
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": "archive_failed", "job": job })),
+                )
+                    .into_response();
+            }
+        }
+
+        let mut removed = Vec::<String>::with_capacity(candidates.len());
+        for run_id in candidates {
+            let path = state.data_dir.join("runs").join(format!("{run_id}.json"));
+            match tokio::fs::remove_file(path).await {
+                Ok(()) => removed.push(run_id),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => removed.push(run_id),
+                Err(err) => record_failure(&mut stats, run_id, err.to_string()),
+            }
+        }
+        stats.pruned = removed.len() as u64;
+        let mut unindexed = remove_runs(&state, &removed).await;
+        if unindexed.is_err() {
+            unindexed = remove_runs(&state, &removed).await;
+        }
+        if let Err(err) = unindexed {
+            job.error = Some(format!("index_failed: {err}"));
+        }
+    }
+
+    job.status = if job.error.is_some() || (stats.failed > 0 && stats.pruned == 0) {
+        "failed"
+    } else if stats.failed > 0 {
+        "partial"
+    } else {
+        "succeeded"
+    }
+    .to_string();
+    job.finished_at = Some(now_ms().to_string());
+    job.summary = Some(if req.dry_run {
+        format!("candidates={}", stats.candidates)
+    } else if stats.failed > 0 {
+        format!("pruned={} failed={}", stats.pruned, stats.failed)
+    } else {
+        format!("pruned={}", stats.pruned)
+    });
+    job.stats = serde_json::to_value(&stats).unwrap_or_default();
+    let _ = write_job(&state, &job).await;
+    (StatusCode::OK, Json(serde_json::json!({ "job": job }))).into_response()
+}
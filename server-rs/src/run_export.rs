@@ -0,0 +1,157 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use bytes::Bytes;
+use serde::Deserialize;
+
+use crate::{
+    run_index::{query_runs, RunFilter},
+    runs::{load_run_record, RunRecord},
+    AppState,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RunExportFormat {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunExportQuery {
+    format: Option<RunExportFormat>,
+    #[serde(default)]
+    include_completion: bool,
+    row_index: Option<u64>,
+    status: Option<String>,
+    project_id: Option<String>,
+    has_missing_variables: Option<bool>,
+    created_after: Option<u64>,
+    created_before: Option<u64>,
+}
+
+const CSV_COLUMNS: [&str; 9] = [
+    "runId",
+    "createdAt",
+    "projectId",
+    "rowIndex",
+    "status",
+    "outputDigest",
+    "missingVariablesCount",
+    "text",
+    "completion",
+];
+
+fn csv_line(fields: &[String]) -> Bytes {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let _ = writer.write_record(fields);
+    Bytes::from(writer.into_inner().unwrap_or_default())
+}
+
+fn csv_header(include_completion: bool) -> Bytes {
+    let n = if include_completion { 9 } else { 8 };
+    csv_line(
+        &CSV_COLUMNS[..n]
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn csv_row(run: &RunRecord, include_completion: bool) -> Bytes {
+    let mut fields = vec![
+        run.run_id.clone(),
+        run.created_at.clone(),
+        run.project_id.clone(),
+        run.row_index.to_string(),
+        run.status.clone(),
+        run.output_digest.clone(),
+        run.missing_variables_count.to_string(),
+        run.trace.text.clone(),
+    ];
+    if include_completion {
+        fields.push(
+            run.execution
+                .as_ref()
+                .and_then(|e| e.completion.clone())
+                .unwrap_or_default(),
+        );
+    }
+    csv_line(&fields)
+}
+
+fn jsonl_row(run: &RunRecord) -> Bytes {
+    let mut line = serde_json::to_vec(run).unwrap_or_default();
+    line.push(b'\n');
+    Bytes::from(line)
+}
+
+pub(crate) async fn export_dataset_runs(
+    State(state): State<AppState>,
+    Path(dataset_id): Path<String>,
+    Query(query): Query<RunExportQuery>,
+) -> axum::response::Response {
+    let format = query.format.unwrap_or(RunExportFormat::Jsonl);
+    let filter = RunFilter {
+        dataset_id: Some(dataset_id.clone()),
+        project_id: query.project_id,
+        row_index: query.row_index,
+        status: query.status,
+        has_missing_variables: query.has_missing_variables,
+        created_after: query.created_after,
+        created_before: query.created_before,
+    };
+    let summaries = match query_runs(&state, &filter, None, None).await {
+        Ok((summaries, _)) => summaries,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "index_failed", "message": err.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let include_completion = query.include_completion;
+    let header_line = (format == RunExportFormat::Csv).then(|| csv_header(include_completion));
+    let rows = futures_util::stream::unfold(
+        (state, summaries.into_iter()),
+        move |(state, mut summaries)| async move {
+            for summary in summaries.by_ref() {
+                let Ok(run) = load_run_record(&state, &summary.run_id).await else {
+                    continue;
+                };
+                let bytes = match format {
+                    RunExportFormat::Jsonl => jsonl_row(&run),
+                    RunExportFormat::Csv => csv_row(&run, include_completion),
+                };
+                return Some((Ok::<_, Infallible>(bytes), (state, summaries)));
+            }
+            None
+        },
+    );
+    let header_stream =
+        futures_util::stream::iter(header_line.into_iter().map(Ok::<_, Infallible>));
+    let body = Body::from_stream(futures_util::StreamExt::chain(header_stream, rows));
+
+    let (content_type, ext) = match format {
+        RunExportFormat::Jsonl => ("application/x-ndjson", "jsonl"),
+        RunExportFormat::Csv => ("text/csv; charset=utf-8", "csv"),
+    };
+    let mut res = axum::response::Response::new(body);
+    let headers = res.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Ok(v) =
+        HeaderValue::from_str(&format!("attachment; filename=\"{dataset_id}_runs.{ext}\""))
+    {
+        headers.insert(header::CONTENT_DISPOSITION, v);
+    }
+    res
+}
@@ -0,0 +1,274 @@
+use std::{path::PathBuf, sync::Arc};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions},
+    Executor, QueryBuilder, Row, Sqlite, SqliteConnection,
+};
+use tokio::sync::OnceCell;
+
+use crate::{
+    now_ms,
+    runs::{RunRecord, RunSummary},
+    AppState,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    run_id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    project_id TEXT NOT NULL,
+    dataset_id TEXT NOT NULL,
+    row_index INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    output_digest TEXT NOT NULL,
+    missing_variables_count INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS runs_dataset_created ON runs (dataset_id, created_at DESC, run_id DESC);
+CREATE INDEX IF NOT EXISTS runs_project_created ON runs (project_id, created_at DESC, run_id DESC);
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunFilter {
+    pub(crate) dataset_id: Option<String>,
+    pub(crate) project_id: Option<String>,
+    pub(crate) row_index: Option<u64>,
+    pub(crate) status: Option<String>,
+    pub(crate) has_missing_variables: Option<bool>,
+    pub(crate) created_after: Option<u64>,
+    pub(crate) created_before: Option<u64>,
+}
+
+fn index_path(state: &AppState) -> PathBuf {
+    state.data_dir.join("run_index.db")
+}
+
+/// Process-wide pool for `run_index.db`, shared through `AppState`. It is
+/// opened, migrated and backfilled once, on startup or on first use.
+pub(crate) type RunIndexPool = Arc<OnceCell<SqlitePool>>;
+
+async fn connect(state: &AppState) -> anyhow::Result<SqlitePool> {
+    tokio::fs::create_dir_all(state.data_dir.as_ref()).await?;
+    let options = SqliteConnectOptions::new()
+        .filename(index_path(state))
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(std::time::Duration::from_secs(5));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(4)
+        .connect_with(options)
+        .await?;
+    pool.execute(SCHEMA).await?;
+    let backfilled = sqlx::query("SELECT value FROM meta WHERE key = 'backfilled'")
+        .fetch_optional(&pool)
+        .await?
+        .is_some();
+    if !backfilled {
+        backfill(state, &pool).await?;
+    }
+    Ok(pool)
+}
+
+async fn open(state: &AppState) -> anyhow::Result<&SqlitePool> {
+    state.run_index.get_or_try_init(|| connect(state)).await
+}
+
+/// Opens the index in the background so the migration and backfill run at
+/// startup rather than inside the first request that touches runs.
+pub(crate) fn warm_up(state: &AppState) {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let state = state.clone();
+    handle.spawn(async move {
+        let _ = open(&state).await;
+    });
+}
+
+async fn backfill(state: &AppState, pool: &SqlitePool) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    if let Ok(mut rd) = tokio::fs::read_dir(state.data_dir.join("runs")).await {
+        while let Ok(Some(entry)) = rd.next_entry().await {
+            let path = entry.path();
+            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(text) = tokio::fs::read_to_string(&path).await else {
+                continue;
+            };
+            let Ok(run) = serde_json::from_str::<RunRecord>(&text) else {
+                continue;
+            };
+            upsert(&mut tx, &run.summary(), &run.project_id, &run.dataset_id).await?;
+        }
+    }
+    sqlx::query("INSERT OR REPLACE INTO meta (key, value) VALUES ('backfilled', ?)")
+        .bind(now_ms().to_string())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn upsert(
+    conn: &mut SqliteConnection,
+    summary: &RunSummary,
+    project_id: &str,
+    dataset_id: &str,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO runs (run_id, created_at, project_id, dataset_id, row_index, status, output_digest, missing_variables_count) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&summary.run_id)
+    .bind(summary.created_at.parse::<i64>().unwrap_or(0))
+    .bind(project_id)
+    .bind(dataset_id)
+    .bind(summary.row_index as i64)
+    .bind(&summary.status)
+    .bind(&summary.output_digest)
+    .bind(summary.missing_variables_count as i64)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn index_run(state: &AppState, run: &RunRecord) -> anyhow::Result<()> {
+    let mut conn = open(state).await?.acquire().await?;
+    upsert(&mut conn, &run.summary(), &run.project_id, &run.dataset_id).await
+}
+
+fn encode_cursor(summary: &RunSummary) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", summary.created_at, summary.run_id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<(i64, String)> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    let text = String::from_utf8(bytes).ok()?;
+    let (created_at, run_id) = text.split_once(':')?;
+    Some((created_at.parse().ok()?, run_id.to_string()))
+}
+
+pub(crate) async fn query_runs(
+    state: &AppState,
+    filter: &RunFilter,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> anyhow::Result<(Vec<RunSummary>, Option<String>)> {
+    let after = match cursor {
+        Some(c) => Some(decode_cursor(c).ok_or_else(|| anyhow::anyhow!("invalid_cursor"))?),
+        None => None,
+    };
+    let pool = open(state).await?;
+    let mut qb = QueryBuilder::<Sqlite>::new(
+        "SELECT run_id, created_at, row_index, status, output_digest, missing_variables_count FROM runs WHERE 1 = 1",
+    );
+    if let Some(v) = &filter.dataset_id {
+        qb.push(" AND dataset_id = ").push_bind(v.clone());
+    }
+    if let Some(v) = &filter.project_id {
+        qb.push(" AND project_id = ").push_bind(v.clone());
+    }
+    if let Some(v) = filter.row_index {
+        qb.push(" AND row_index = ").push_bind(v as i64);
+    }
+    if let Some(v) = &filter.status {
+        qb.push(" AND status = ").push_bind(v.clone());
+    }
+    match filter.has_missing_variables {
+        Some(true) => {
+            qb.push(" AND missing_variables_count > 0");
+        }
+        Some(false) => {
+            qb.push(" AND missing_variables_count = 0");
+        }
+        None => {}
+    }
+    if let Some(v) = filter.created_after {
+        qb.push(" AND created_at > ").push_bind(v as i64);
+    }
+    if let Some(v) = filter.created_before {
+        qb.push(" AND created_at < ").push_bind(v as i64);
+    }
+    if let Some((created_at, run_id)) = after {
+        qb.push(" AND (created_at < ")
+            .push_bind(created_at)
+            .push(" OR (created_at = ")
+            .push_bind(created_at)
+            .push(" AND run_id < ")
+            .push_bind(run_id)
+            .push("))");
+    }
+    qb.push(" ORDER BY created_at DESC, run_id DESC");
+    if let Some(limit) = limit {
+        qb.push(" LIMIT ").push_bind(limit as i64 + 1);
+    }
+
+    let rows = qb.build().fetch_all(pool).await?;
+    let mut out = rows
+        .iter()
+        .map(|row| RunSummary {
+            run_id: row.get("run_id"),
+            created_at: row.get::<i64, _>("created_at").to_string(),
+            row_index: row.get::<i64, _>("row_index") as u64,
+            status: row.get("status"),
+            output_digest: row.get("output_digest"),
+            missing_variables_count: row.get::<i64, _>("missing_variables_count") as u64,
+        })
+        .collect::<Vec<_>>();
+    let next = match limit {
+        Some(limit) if out.len() > limit => {
+            out.truncate(limit);
+            out.last().map(encode_cursor)
+        }
+        _ => None,
+    };
+    Ok((out, next))
+}
+
+pub(crate) struct IndexedRun {
+    pub(crate) run_id: String,
+    pub(crate) dataset_id: String,
+    pub(crate) row_index: u64,
+    pub(crate) created_at: u64,
+}
+
+pub(crate) async fn list_indexed_runs(
+    state: &AppState,
+    dataset_id: Option<&str>,
+) -> anyhow::Result<Vec<IndexedRun>> {
+    let pool = open(state).await?;
+    let mut qb = QueryBuilder::<Sqlite>::new(
+        "SELECT run_id, dataset_id, row_index, created_at FROM runs WHERE 1 = 1",
+    );
+    if let Some(v) = dataset_id {
+        qb.push(" AND dataset_id = ").push_bind(v.to_string());
+    }
+    qb.push(" ORDER BY dataset_id, row_index, created_at DESC, run_id DESC");
+    let rows = qb.build().fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| IndexedRun {
+            run_id: row.get("run_id"),
+            dataset_id: row.get("dataset_id"),
+            row_index: row.get::<i64, _>("row_index") as u64,
+            created_at: row.get::<i64, _>("created_at") as u64,
+        })
+        .collect())
+}
+
+pub(crate) async fn remove_runs(state: &AppState, run_ids: &[String]) -> anyhow::Result<()> {
+    let mut tx = open(state).await?.begin().await?;
+    for run_id in run_ids {
+        sqlx::query("DELETE FROM runs WHERE run_id = ?")
+            .bind(run_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
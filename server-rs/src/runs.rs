@@ -1,19 +1,40 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use axum::{
     extract::Query,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
 };
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine as _;
-use context_engine::{render_with_trace, EngineNode, NodeKind, OutputStyle, TraceRun};
+use context_engine::{
+    render_with_trace, EngineNode, NodeKind, OutputStyle, TraceMessage, TraceRun,
+};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::{now_ms, resolvers::resolve_variable_with_trace, AppState, VariableSpec};
+use crate::{
+    assertions::{AssertionInput, AssertionResult, RowExpectations},
+    decrypt_provider_api_key,
+    evaluators::EvaluatorScore,
+    flow_graph::{topo_sort_nodes, FlowNodeRef, IsolatedNodePolicy},
+    load_job, load_provider, now_ms,
+    providers::ProviderClient,
+    register_running_job,
+    resolvers::resolve_variable_with_trace,
+    run_index::{index_run, query_runs, RunFilter},
+    snapshots::{resolve_replay_snapshot, ProjectSnapshot},
+    unregister_running_job, write_job, AppState, JobRecord,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +48,37 @@ pub(crate) struct RunRecord {
     pub(crate) output_digest: String,
     pub(crate) missing_variables_count: u64,
     pub(crate) trace: TraceRun,
+    #[serde(default)]
+    pub(crate) snapshot_id: Option<String>,
+    #[serde(default)]
+    pub(crate) execution: Option<RunExecution>,
+    #[serde(default)]
+    pub(crate) assertions: Vec<AssertionResult>,
+    #[serde(default)]
+    pub(crate) scores: Vec<EvaluatorScore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunExecution {
+    pub(crate) provider_id: String,
+    pub(crate) model: String,
+    pub(crate) completion: Option<String>,
+    pub(crate) reasoning_content: Option<String>,
+    pub(crate) latency_ms: u64,
+    pub(crate) usage: Option<TokenUsage>,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TokenUsage {
+    #[serde(default, alias = "prompt_tokens")]
+    pub(crate) prompt_tokens: u64,
+    #[serde(default, alias = "completion_tokens")]
+    pub(crate) completion_tokens: u64,
+    #[serde(default, alias = "total_tokens")]
+    pub(crate) total_tokens: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,47 +95,62 @@ pub(crate) struct RunSummary {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ReplayDatasetRequest {
+    #[serde(default)]
     pub(crate) project_id: String,
+    #[serde(default)]
+    pub(crate) snapshot_id: Option<String>,
     pub(crate) limit: Option<u32>,
     pub(crate) offset: Option<u32>,
+    #[serde(default)]
+    pub(crate) execute: bool,
+    pub(crate) provider_id: Option<String>,
+    pub(crate) model: Option<String>,
+}
+
+impl ReplayDatasetRequest {
+    fn requested_id(&self) -> &str {
+        self.snapshot_id.as_deref().unwrap_or(&self.project_id)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct DatasetRecord {
-    id: String,
+pub(crate) struct DatasetRecord {
+    pub(crate) id: String,
     name: String,
-    rows: Vec<serde_json::Value>,
+    pub(crate) rows: Vec<serde_json::Value>,
     created_at: String,
     updated_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct StoredProjectDoc {
-    id: String,
-    name: String,
-    state: StoredProjectState,
+pub(crate) struct StoredProjectDoc {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) state: StoredProjectState,
     updated_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct StoredProjectState {
-    nodes: Vec<StoredFlowNode>,
-    edges: Vec<StoredFlowEdge>,
-    variables: Vec<StoredProjectVariable>,
+pub(crate) struct StoredProjectState {
+    pub(crate) nodes: Vec<StoredFlowNode>,
+    pub(crate) edges: Vec<StoredFlowEdge>,
+    pub(crate) variables: Vec<StoredProjectVariable>,
+    #[serde(default)]
+    pub(crate) isolated_node_policy: IsolatedNodePolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct StoredProjectVariable {
-    id: String,
-    name: String,
-    r#type: String,
-    value: String,
+pub(crate) struct StoredProjectVariable {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) r#type: String,
+    pub(crate) value: String,
     #[serde(default)]
-    resolver: Option<String>,
+    pub(crate) resolver: Option<String>,
     #[serde(default)]
     description: Option<String>,
     #[serde(default)]
@@ -92,7 +159,7 @@ struct StoredProjectVariable {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct StoredFlowNode {
+pub(crate) struct StoredFlowNode {
     id: String,
     data: StoredFlowNodeData,
 }
@@ -105,11 +172,13 @@ struct StoredFlowNodeData {
     node_type: String,
     #[serde(default)]
     content: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    disabled: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct StoredFlowEdge {
+pub(crate) struct StoredFlowEdge {
     source: String,
     target: String,
 }
@@ -118,16 +187,25 @@ fn runs_dir(state: &AppState) -> PathBuf {
     state.data_dir.join("runs")
 }
 
-async fn write_run_record(state: &AppState, run: &RunRecord) -> anyhow::Result<()> {
+pub(crate) async fn write_run_record(state: &AppState, run: &RunRecord) -> anyhow::Result<()> {
     let dir = runs_dir(state);
     tokio::fs::create_dir_all(&dir).await?;
     let path = dir.join(format!("{}.json", run.run_id));
     let text = serde_json::to_string_pretty(run)?;
     tokio::fs::write(path, text).await?;
-    Ok(())
+    index_run(state, run).await
 }
 
-async fn load_dataset_record(state: &AppState, dataset_id: &str) -> anyhow::Result<DatasetRecord> {
+pub(crate) async fn load_run_record(state: &AppState, run_id: &str) -> anyhow::Result<RunRecord> {
+    let path = runs_dir(state).join(format!("{run_id}.json"));
+    let text = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+pub(crate) async fn load_dataset_record(
+    state: &AppState,
+    dataset_id: &str,
+) -> anyhow::Result<DatasetRecord> {
     let path = state
         .data_dir
         .join("datasets")
@@ -136,7 +214,10 @@ async fn load_dataset_record(state: &AppState, dataset_id: &str) -> anyhow::Resu
     Ok(serde_json::from_str(&text)?)
 }
 
-async fn load_project_doc(state: &AppState, project_id: &str) -> anyhow::Result<StoredProjectDoc> {
+pub(crate) async fn load_project_doc(
+    state: &AppState,
+    project_id: &str,
+) -> anyhow::Result<StoredProjectDoc> {
     let path = state
         .data_dir
         .join("projects")
@@ -157,65 +238,6 @@ fn node_type_to_kind(node_type: &str) -> NodeKind {
     }
 }
 
-fn topo_sort_nodes(nodes: &[StoredFlowNode], edges: &[StoredFlowEdge]) -> Vec<StoredFlowNode> {
-    if edges.is_empty() {
-        return nodes.to_vec();
-    }
-
-    let mut by_id = HashMap::<String, StoredFlowNode>::new();
-    for n in nodes {
-        by_id.insert(n.id.clone(), n.clone());
-    }
-
-    let mut indeg = HashMap::<String, usize>::new();
-    let mut out = HashMap::<String, Vec<String>>::new();
-    for id in by_id.keys() {
-        indeg.insert(id.clone(), 0);
-        out.insert(id.clone(), Vec::new());
-    }
-
-    for e in edges {
-        if !by_id.contains_key(&e.source) || !by_id.contains_key(&e.target) {
-            continue;
-        }
-        *indeg.entry(e.target.clone()).or_insert(0) += 1;
-        out.entry(e.source.clone())
-            .or_default()
-            .push(e.target.clone());
-    }
-
-    let mut ready = indeg
-        .iter()
-        .filter_map(|(id, d)| if *d == 0 { Some(id.clone()) } else { None })
-        .collect::<Vec<_>>();
-    ready.sort();
-
-    let mut result = Vec::<StoredFlowNode>::new();
-    while let Some(id) = ready.first().cloned() {
-        ready.remove(0);
-        if let Some(node) = by_id.get(&id) {
-            result.push(node.clone());
-        }
-        let mut nexts = out.get(&id).cloned().unwrap_or_default();
-        nexts.sort();
-        for to in nexts {
-            let d = indeg.entry(to.clone()).or_insert(0);
-            *d = d.saturating_sub(1);
-            if *d == 0 {
-                ready.push(to);
-                ready.sort();
-            }
-        }
-    }
-
-    if result.len() != nodes.len() {
-        let mut fallback = nodes.to_vec();
-        fallback.sort_by(|a, b| a.id.cmp(&b.id));
-        return fallback;
-    }
-    result
-}
-
 fn row_to_variable_overrides(row: &serde_json::Value) -> anyhow::Result<HashMap<String, String>> {
     let Some(obj) = row.as_object() else {
         anyhow::bail!("row_invalid");
@@ -245,7 +267,7 @@ fn row_to_variable_overrides(row: &serde_json::Value) -> anyhow::Result<HashMap<
     Ok(out)
 }
 
-fn json_value_to_string(v: &serde_json::Value) -> String {
+pub(crate) fn json_value_to_string(v: &serde_json::Value) -> String {
     if let Some(s) = v.as_str() {
         return s.to_string();
     }
@@ -280,12 +302,320 @@ fn missing_variables_count(trace: &TraceRun) -> u64 {
     set.len() as u64
 }
 
+const SYNC_REPLAY_MAX_ROWS: usize = 200;
+
+pub(crate) struct ReplayPlan {
+    pub(crate) snapshot: ProjectSnapshot,
+    dataset_id: String,
+    engine_nodes: Vec<EngineNode>,
+    flow_messages: Vec<TraceMessage>,
+    flow_failed: bool,
+    executor: Option<ReplayExecutor>,
+    run_suffix: String,
+}
+
+#[derive(Clone)]
+pub(crate) struct ReplayExecutor {
+    pub(crate) provider_id: String,
+    pub(crate) client: ProviderClient,
+    pub(crate) model: String,
+}
+
+pub(crate) async fn load_replay_executor(
+    state: &AppState,
+    provider_id: Option<&str>,
+    model: Option<&str>,
+) -> Result<ReplayExecutor, (StatusCode, &'static str)> {
+    let Some(provider_id) = provider_id.filter(|id| !id.trim().is_empty()) else {
+        return Err((StatusCode::BAD_REQUEST, "validation_failed"));
+    };
+    let provider = load_provider(state, provider_id)
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "provider_not_found"))?;
+    let api_key = decrypt_provider_api_key(state, provider_id)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "decrypt_failed"))?;
+    let client = ProviderClient::new(&provider, api_key);
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .map(|m| m.to_string())
+        .or_else(|| client.chat_model())
+        .ok_or((StatusCode::BAD_REQUEST, "model_required"))?;
+    Ok(ReplayExecutor {
+        provider_id: provider_id.to_string(),
+        client,
+        model,
+    })
+}
+
+fn trace_to_chat_messages(trace: &TraceRun) -> Vec<serde_json::Value> {
+    let mut out = Vec::<(&str, String)>::new();
+    for seg in &trace.segments {
+        let header = format!("--- {} ---\n", seg.label);
+        let body = match trace.output_style {
+            OutputStyle::Labeled => seg.rendered.strip_prefix(&header).unwrap_or(&seg.rendered),
+            OutputStyle::Plain => &seg.rendered,
+        };
+        if body.trim().is_empty() {
+            continue;
+        }
+        let role = match seg.kind {
+            NodeKind::System => "system",
+            NodeKind::Assistant => "assistant",
+            _ => "user",
+        };
+        match out.last_mut() {
+            Some((last_role, content)) if *last_role == role => {
+                content.push_str("\n\n");
+                content.push_str(body);
+            }
+            _ => out.push((role, body.to_string())),
+        }
+    }
+    out.into_iter()
+        .map(|(role, content)| serde_json::json!({ "role": role, "content": content }))
+        .collect()
+}
+
+async fn execute_trace(executor: &ReplayExecutor, trace: &TraceRun) -> RunExecution {
+    let body = serde_json::json!({
+        "model": executor.model,
+        "messages": trace_to_chat_messages(trace),
+        "stream": false,
+    });
+    let started = std::time::Instant::now();
+    let result = executor.client.chat_completions(&body).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let mut execution = RunExecution {
+        provider_id: executor.provider_id.clone(),
+        model: executor.model.clone(),
+        completion: None,
+        reasoning_content: None,
+        latency_ms,
+        usage: None,
+        error: None,
+    };
+    match result {
+        Ok(v) => {
+            let (content, reasoning) = executor.client.completion_content(&v);
+            execution.completion = Some(content);
+            execution.reasoning_content = reasoning;
+            execution.usage = v
+                .get("usage")
+                .and_then(|u| serde_json::from_value::<TokenUsage>(u.clone()).ok());
+            if let Some(model) = v.get("model").and_then(|m| m.as_str()) {
+                execution.model = model.to_string();
+            }
+        }
+        Err(err) => execution.error = Some(err.to_string()),
+    }
+    execution
+}
+
+impl ReplayPlan {
+    fn new(snapshot: ProjectSnapshot, dataset_id: String) -> Self {
+        let flow = topo_sort_nodes(
+            &snapshot
+                .nodes
+                .iter()
+                .map(|n| FlowNodeRef {
+                    id: &n.id,
+                    disabled: n.data.disabled,
+                })
+                .collect::<Vec<_>>(),
+            &snapshot
+                .edges
+                .iter()
+                .map(|e| (e.source.as_str(), e.target.as_str()))
+                .collect::<Vec<_>>(),
+            snapshot.isolated_node_policy,
+        );
+        let engine_nodes = flow
+            .apply(&snapshot.nodes)
+            .into_iter()
+            .map(|n| EngineNode {
+                id: n.id,
+                label: n.data.label,
+                kind: node_type_to_kind(&n.data.node_type),
+                content: n.data.content,
+            })
+            .collect::<Vec<_>>();
+        Self {
+            flow_failed: flow.has_errors(),
+            flow_messages: flow.messages,
+            snapshot,
+            dataset_id,
+            engine_nodes,
+            executor: None,
+            run_suffix: String::new(),
+        }
+    }
+
+    pub(crate) async fn load(
+        state: &AppState,
+        project_id: &str,
+        snapshot_id: Option<&str>,
+        dataset_id: &str,
+        executor: Option<ReplayExecutor>,
+        run_suffix: &str,
+    ) -> Result<Self, (StatusCode, &'static str)> {
+        let snapshot = resolve_replay_snapshot(state, project_id, snapshot_id).await?;
+        let mut plan = Self::new(snapshot, dataset_id.to_string());
+        plan.executor = executor;
+        plan.run_suffix = run_suffix.to_string();
+        Ok(plan)
+    }
+}
+
+impl RunRecord {
+    pub(crate) fn output_text(&self) -> &str {
+        self.execution
+            .as_ref()
+            .and_then(|e| e.completion.as_deref())
+            .unwrap_or(&self.trace.text)
+    }
+
+    pub(crate) fn summary(&self) -> RunSummary {
+        RunSummary {
+            run_id: self.run_id.clone(),
+            created_at: self.created_at.clone(),
+            row_index: self.row_index,
+            status: self.status.clone(),
+            output_digest: self.output_digest.clone(),
+            missing_variables_count: self.missing_variables_count,
+        }
+    }
+}
+
+pub(crate) async fn replay_row(
+    state: &AppState,
+    plan: &ReplayPlan,
+    row_index: u64,
+    row: &serde_json::Value,
+) -> anyhow::Result<RunRecord> {
+    let run_id = format!("run_{}_{}{}", now_ms(), row_index, plan.run_suffix);
+    let created_at = now_ms().to_string();
+
+    let overrides = match row_to_variable_overrides(row) {
+        Ok(m) => m,
+        Err(_) => {
+            let mut trace = render_with_trace(
+                &plan.engine_nodes,
+                &HashMap::new(),
+                OutputStyle::Labeled,
+                &run_id,
+                &created_at,
+            );
+            trace.messages.extend(plan.flow_messages.iter().cloned());
+            let record = RunRecord {
+                run_id: run_id.clone(),
+                created_at: created_at.clone(),
+                project_id: plan.snapshot.project_id.clone(),
+                dataset_id: plan.dataset_id.clone(),
+                row_index,
+                status: "failed".to_string(),
+                output_digest: digest_text(""),
+                missing_variables_count: missing_variables_count(&trace),
+                trace,
+                snapshot_id: Some(plan.snapshot.id.clone()),
+                execution: None,
+                assertions: Vec::new(),
+                scores: Vec::new(),
+            };
+            let _ = write_run_record(state, &record).await;
+            return Ok(record);
+        }
+    };
+
+    let mut resolved_map = HashMap::<String, String>::new();
+    let mut messages = Vec::new();
+
+    for v in &plan.snapshot.variables {
+        let r = resolve_variable_with_trace(state.clone(), v.clone()).await;
+        messages.push(r.trace_message);
+        if let Ok(value) = r.result {
+            resolved_map.insert(v.name.clone(), value.string_value);
+        }
+    }
+
+    for (k, v) in overrides {
+        resolved_map.insert(k, v);
+    }
+
+    let trace = {
+        let mut trace = render_with_trace(
+            &plan.engine_nodes,
+            &resolved_map,
+            OutputStyle::Labeled,
+            &run_id,
+            &created_at,
+        );
+        trace.messages.extend(plan.flow_messages.iter().cloned());
+        trace.messages.extend(messages);
+        trace
+    };
+
+    let digest = digest_text(&trace.text);
+    let missing = missing_variables_count(&trace);
+    let execution = match &plan.executor {
+        Some(executor) if !plan.flow_failed => Some(execute_trace(executor, &trace).await),
+        _ => None,
+    };
+    let execution_failed =
+        plan.flow_failed || execution.as_ref().is_some_and(|e| e.error.is_some());
+    let (status, assertions) = match RowExpectations::from_row(row) {
+        Ok(None) if execution_failed => ("failed".to_string(), Vec::new()),
+        Ok(None) => ("succeeded".to_string(), Vec::new()),
+        Ok(Some(expect)) => {
+            let text = execution
+                .as_ref()
+                .and_then(|e| e.completion.as_deref())
+                .unwrap_or(&trace.text);
+            let assertions = expect.evaluate(&AssertionInput {
+                text,
+                digest: &digest_text(text),
+                usage: execution.as_ref().and_then(|e| e.usage.as_ref()),
+            });
+            let passed = !execution_failed && assertions.iter().all(|a| a.passed);
+            let status = if passed { "passed" } else { "failed" };
+            (status.to_string(), assertions)
+        }
+        Err(err) => (
+            "failed".to_string(),
+            vec![AssertionResult {
+                kind: "expect".to_string(),
+                expected: row.get("_expect").cloned().unwrap_or_default(),
+                passed: false,
+                message: Some(format!("invalid_expect: {err}")),
+            }],
+        ),
+    };
+
+    let record = RunRecord {
+        run_id,
+        created_at,
+        project_id: plan.snapshot.project_id.clone(),
+        dataset_id: plan.dataset_id.clone(),
+        row_index,
+        status,
+        output_digest: digest,
+        missing_variables_count: missing,
+        trace,
+        snapshot_id: Some(plan.snapshot.id.clone()),
+        execution,
+        assertions,
+        scores: Vec::new(),
+    };
+    write_run_record(state, &record).await?;
+    Ok(record)
+}
+
 pub(crate) async fn replay_dataset(
     State(state): State<AppState>,
     Path(dataset_id): Path<String>,
     Json(req): Json<ReplayDatasetRequest>,
 ) -> axum::response::Response {
-    if req.project_id.trim().is_empty() {
+    if req.project_id.trim().is_empty() && req.snapshot_id.is_none() {
         return (
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({ "error": "validation_failed" })),
@@ -293,7 +623,7 @@ pub(crate) async fn replay_dataset(
             .into_response();
     }
 
-    let limit = req.limit.unwrap_or(20).min(200) as usize;
+    let limit = (req.limit.unwrap_or(20) as usize).min(SYNC_REPLAY_MAX_ROWS);
     let offset = req.offset.unwrap_or(0) as usize;
 
     let dataset = match load_dataset_record(&state, &dataset_id).await {
@@ -307,187 +637,362 @@ pub(crate) async fn replay_dataset(
         }
     };
 
-    let project = match load_project_doc(&state, &req.project_id).await {
-        Ok(p) => p,
-        Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({ "error": "project_not_found", "id": req.project_id })),
-            )
-                .into_response();
-        }
-    };
-
-    let sorted_nodes = topo_sort_nodes(&project.state.nodes, &project.state.edges);
-    let engine_nodes = sorted_nodes
-        .into_iter()
-        .map(|n| EngineNode {
-            id: n.id,
-            label: n.data.label,
-            kind: node_type_to_kind(&n.data.node_type),
-            content: n.data.content,
-        })
-        .collect::<Vec<_>>();
+    let snapshot =
+        match resolve_replay_snapshot(&state, &req.project_id, req.snapshot_id.as_deref()).await {
+            Ok(s) => s,
+            Err((status, error)) => {
+                return (
+                    status,
+                    Json(serde_json::json!({ "error": error, "id": req.requested_id() })),
+                )
+                    .into_response();
+            }
+        };
 
+    let mut plan = ReplayPlan::new(snapshot, dataset.id.clone());
+    if req.execute {
+        match load_replay_executor(&state, req.provider_id.as_deref(), req.model.as_deref()).await {
+            Ok(executor) => plan.executor = Some(executor),
+            Err((status, error)) => {
+                return (
+                    status,
+                    Json(serde_json::json!({ "error": error, "id": req.provider_id })),
+                )
+                    .into_response();
+            }
+        }
+    }
     let start = offset.min(dataset.rows.len());
     let end = (start + limit).min(dataset.rows.len());
     let mut summaries = Vec::<RunSummary>::new();
 
     for (i, row) in dataset.rows[start..end].iter().enumerate() {
-        let row_index = (start + i) as u64;
-        let run_id = format!("run_{}_{}", now_ms(), row_index);
-        let created_at = now_ms().to_string();
-
-        let overrides = match row_to_variable_overrides(row) {
-            Ok(m) => m,
+        match replay_row(&state, &plan, (start + i) as u64, row).await {
+            Ok(record) => summaries.push(record.summary()),
             Err(_) => {
-                let trace = render_with_trace(
-                    &engine_nodes,
-                    &HashMap::new(),
-                    OutputStyle::Labeled,
-                    &run_id,
-                    &created_at,
-                );
-                let record = RunRecord {
-                    run_id: run_id.clone(),
-                    created_at: created_at.clone(),
-                    project_id: project.id.clone(),
-                    dataset_id: dataset.id.clone(),
-                    row_index,
-                    status: "failed".to_string(),
-                    output_digest: digest_text(""),
-                    missing_variables_count: missing_variables_count(&trace),
-                    trace,
-                };
-                let _ = write_run_record(&state, &record).await;
-                summaries.push(RunSummary {
-                    run_id,
-                    created_at,
-                    row_index,
-                    status: "failed".to_string(),
-                    output_digest: record.output_digest,
-                    missing_variables_count: record.missing_variables_count,
-                });
-                continue;
-            }
-        };
-
-        let mut resolved_map = HashMap::<String, String>::new();
-        let mut messages = Vec::new();
-
-        for v in &project.state.variables {
-            let spec = VariableSpec {
-                id: v.id.clone(),
-                name: v.name.clone(),
-                r#type: v.r#type.clone(),
-                value: v.value.clone(),
-                resolver: v.resolver.clone(),
-            };
-            let r = resolve_variable_with_trace(state.clone(), spec).await;
-            messages.push(r.trace_message);
-            if let Ok(value) = r.result {
-                resolved_map.insert(v.name.clone(), value.string_value);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": "write_failed" })),
+                )
+                    .into_response();
             }
         }
+    }
 
-        for (k, v) in overrides {
-            resolved_map.insert(k, v);
-        }
+    (StatusCode::OK, Json(summaries)).into_response()
+}
 
-        let trace = {
-            let mut trace = render_with_trace(
-                &engine_nodes,
-                &resolved_map,
-                OutputStyle::Labeled,
-                &run_id,
-                &created_at,
-            );
-            trace.messages.extend(messages);
-            trace
-        };
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayJobStats {
+    dataset_id: String,
+    project_id: String,
+    #[serde(default)]
+    snapshot_id: Option<String>,
+    offset: u64,
+    limit: Option<u64>,
+    end_row_index: u64,
+    total: u64,
+    processed: u64,
+    succeeded: u64,
+    failed: u64,
+    next_row_index: u64,
+    #[serde(default)]
+    execute: bool,
+    #[serde(default)]
+    provider_id: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+pub(crate) async fn start_replay_job(
+    State(state): State<AppState>,
+    Path(dataset_id): Path<String>,
+    Json(req): Json<ReplayDatasetRequest>,
+) -> axum::response::Response {
+    if req.project_id.trim().is_empty() && req.snapshot_id.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response();
+    }
 
-        let digest = digest_text(&trace.text);
-        let missing = missing_variables_count(&trace);
-        let status = "succeeded".to_string();
-
-        let record = RunRecord {
-            run_id: run_id.clone(),
-            created_at: created_at.clone(),
-            project_id: project.id.clone(),
-            dataset_id: dataset.id.clone(),
-            row_index,
-            status: status.clone(),
-            output_digest: digest.clone(),
-            missing_variables_count: missing,
-            trace: trace.clone(),
+    let dataset = match load_dataset_record(&state, &dataset_id).await {
+        Ok(ds) => ds,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "dataset_not_found", "id": dataset_id })),
+            )
+                .into_response();
+        }
+    };
+    let snapshot =
+        match resolve_replay_snapshot(&state, &req.project_id, req.snapshot_id.as_deref()).await {
+            Ok(s) => s,
+            Err((status, error)) => {
+                return (
+                    status,
+                    Json(serde_json::json!({ "error": error, "id": req.requested_id() })),
+                )
+                    .into_response();
+            }
         };
-        if write_run_record(&state, &record).await.is_err() {
+    if req.execute {
+        if let Err((status, error)) =
+            load_replay_executor(&state, req.provider_id.as_deref(), req.model.as_deref()).await
+        {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": "write_failed" })),
+                status,
+                Json(serde_json::json!({ "error": error, "id": req.provider_id })),
             )
                 .into_response();
         }
+    }
+
+    let rows = dataset.rows.len() as u64;
+    let offset = (req.offset.unwrap_or(0) as u64).min(rows);
+    let limit = req.limit.map(|n| n as u64);
+    let end = match limit {
+        Some(n) => (offset + n).min(rows),
+        None => rows,
+    };
+    let stats = ReplayJobStats {
+        dataset_id: dataset.id.clone(),
+        project_id: snapshot.project_id.clone(),
+        snapshot_id: Some(snapshot.id.clone()),
+        offset,
+        limit,
+        end_row_index: end,
+        total: end - offset,
+        next_row_index: offset,
+        execute: req.execute,
+        provider_id: req.provider_id.clone(),
+        model: req.model.clone(),
+        ..ReplayJobStats::default()
+    };
 
-        summaries.push(RunSummary {
-            run_id,
-            created_at,
-            row_index,
-            status,
-            output_digest: digest,
-            missing_variables_count: missing,
-        });
+    let job = JobRecord {
+        id: format!("job_{}", now_ms()),
+        job_type: "dataset_replay".to_string(),
+        status: "running".to_string(),
+        created_at: now_ms().to_string(),
+        finished_at: None,
+        summary: None,
+        stats: serde_json::to_value(&stats).unwrap_or_default(),
+        error: None,
+    };
+
+    let Some(cancel) = register_running_job(&state, &job.id) else {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "job_running", "id": job.id })),
+        )
+            .into_response();
+    };
+    if write_job(&state, &job).await.is_err() {
+        unregister_running_job(&state, &job.id);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed" })),
+        )
+            .into_response();
     }
 
-    (StatusCode::OK, Json(summaries)).into_response()
+    spawn_replay_job(state, job.clone(), cancel);
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "job": job })),
+    )
+        .into_response()
 }
 
-pub(crate) async fn list_dataset_runs(
+pub(crate) async fn resume_replay_job(
     State(state): State<AppState>,
-    Path(dataset_id): Path<String>,
-    Query(query): Query<ListDatasetRunsQuery>,
+    Path(job_id): Path<String>,
 ) -> axum::response::Response {
-    let dir = runs_dir(&state);
-    let mut out = Vec::<RunSummary>::new();
-    let mut rd = match tokio::fs::read_dir(&dir).await {
-        Ok(rd) => rd,
+    let mut job = match load_job(&state, &job_id).await {
+        Ok(job) => job,
         Err(_) => {
-            return (StatusCode::OK, Json(out)).into_response();
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "not_found", "id": job_id })),
+            )
+                .into_response();
         }
     };
-    while let Ok(Some(entry)) = rd.next_entry().await {
-        let path = entry.path();
-        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
-            continue;
+    if job.job_type != "dataset_replay" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "unsupported_job_type", "jobType": job.job_type })),
+        )
+            .into_response();
+    }
+    if job.status == "succeeded" {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "job_finished", "id": job.id })),
+        )
+            .into_response();
+    }
+    let Some(cancel) = register_running_job(&state, &job.id) else {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "job_running", "id": job.id })),
+        )
+            .into_response();
+    };
+
+    job.status = "running".to_string();
+    job.finished_at = None;
+    job.error = None;
+    let _ = write_job(&state, &job).await;
+    spawn_replay_job(state, job.clone(), cancel);
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "job": job })),
+    )
+        .into_response()
+}
+
+fn spawn_replay_job(state: AppState, job: JobRecord, cancel: Arc<AtomicBool>) {
+    tokio::spawn(async move {
+        let job_id = job.id.clone();
+        run_replay_job(&state, job, &cancel).await;
+        unregister_running_job(&state, &job_id);
+    });
+}
+
+async fn run_replay_job(state: &AppState, mut job: JobRecord, cancel: &AtomicBool) {
+    let mut stats = serde_json::from_value::<ReplayJobStats>(job.stats.clone()).unwrap_or_default();
+
+    let loaded = async {
+        let dataset = load_dataset_record(state, &stats.dataset_id).await?;
+        let snapshot =
+            resolve_replay_snapshot(state, &stats.project_id, stats.snapshot_id.as_deref())
+                .await
+                .map_err(|(_, error)| anyhow::anyhow!(error))?;
+        anyhow::Ok((dataset, snapshot))
+    }
+    .await;
+    let (dataset, snapshot) = match loaded {
+        Ok(v) => v,
+        Err(err) => {
+            job.status = "failed".to_string();
+            job.finished_at = Some(now_ms().to_string());
+            job.error = Some(err.to_string());
+            let _ = write_job(state, &job).await;
+            return;
         }
-        let Ok(text) = tokio::fs::read_to_string(&path).await else {
-            continue;
-        };
-        let Ok(run) = serde_json::from_str::<RunRecord>(&text) else {
-            continue;
-        };
-        if run.dataset_id != dataset_id {
-            continue;
+    };
+
+    let mut plan = ReplayPlan::new(snapshot, dataset.id.clone());
+    if stats.execute {
+        match load_replay_executor(state, stats.provider_id.as_deref(), stats.model.as_deref())
+            .await
+        {
+            Ok(executor) => plan.executor = Some(executor),
+            Err((_, error)) => {
+                job.status = "failed".to_string();
+                job.finished_at = Some(now_ms().to_string());
+                job.error = Some(error.to_string());
+                let _ = write_job(state, &job).await;
+                return;
+            }
         }
-        if let Some(row_index) = query.row_index {
-            if run.row_index != row_index {
-                continue;
+    }
+    let end = stats.end_row_index.min(dataset.rows.len() as u64);
+
+    while stats.next_row_index < end {
+        if cancel.load(Ordering::SeqCst) {
+            job.status = "cancelled".to_string();
+            job.finished_at = Some(now_ms().to_string());
+            break;
+        }
+        let row_index = stats.next_row_index;
+        match replay_row(state, &plan, row_index, &dataset.rows[row_index as usize]).await {
+            Ok(record) => {
+                if record.status == "failed" {
+                    stats.failed += 1;
+                } else {
+                    stats.succeeded += 1;
+                }
+            }
+            Err(err) => {
+                job.status = "failed".to_string();
+                job.finished_at = Some(now_ms().to_string());
+                job.error = Some(err.to_string());
+                break;
             }
         }
-        out.push(RunSummary {
-            run_id: run.run_id,
-            created_at: run.created_at,
-            row_index: run.row_index,
-            status: run.status,
-            output_digest: run.output_digest,
-            missing_variables_count: run.missing_variables_count,
-        });
+        stats.processed += 1;
+        stats.next_row_index = row_index + 1;
+        job.summary = Some(format!("processed={}/{}", stats.processed, stats.total));
+        job.stats = serde_json::to_value(&stats).unwrap_or_default();
+        let _ = write_job(state, &job).await;
+    }
+
+    if job.status == "running" {
+        job.status = "succeeded".to_string();
+        job.finished_at = Some(now_ms().to_string());
     }
-    out.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-    if let Some(limit) = query.limit {
-        out.truncate(limit as usize);
+    job.summary = Some(format!("processed={}/{}", stats.processed, stats.total));
+    job.stats = serde_json::to_value(&stats).unwrap_or_default();
+    let _ = write_job(state, &job).await;
+}
+
+pub(crate) async fn list_run_records(state: &AppState, dataset_id: &str) -> Vec<RunRecord> {
+    let filter = RunFilter {
+        dataset_id: Some(dataset_id.to_string()),
+        ..RunFilter::default()
+    };
+    let Ok((summaries, _)) = query_runs(state, &filter, None, None).await else {
+        return Vec::new();
+    };
+    let mut out = Vec::<RunRecord>::new();
+    for summary in summaries {
+        if let Ok(run) = load_run_record(state, &summary.run_id).await {
+            out.push(run);
+        }
+    }
+    out
+}
+
+pub(crate) async fn list_dataset_runs(
+    State(state): State<AppState>,
+    Path(dataset_id): Path<String>,
+    Query(query): Query<ListDatasetRunsQuery>,
+) -> axum::response::Response {
+    let filter = RunFilter {
+        dataset_id: Some(dataset_id),
+        project_id: query.project_id,
+        row_index: query.row_index,
+        status: query.status,
+        has_missing_variables: query.has_missing_variables,
+        created_after: query.created_after,
+        created_before: query.created_before,
+    };
+    let limit = query.limit.map(|l| l as usize);
+    match query_runs(&state, &filter, query.cursor.as_deref(), limit).await {
+        Ok((out, next)) => {
+            let mut resp = (StatusCode::OK, Json(out)).into_response();
+            if let Some(next) = next.and_then(|n| HeaderValue::from_str(&n).ok()) {
+                resp.headers_mut().insert("x-next-cursor", next);
+            }
+            resp
+        }
+        Err(err) if err.to_string() == "invalid_cursor" => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "invalid_cursor" })),
+        )
+            .into_response(),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "index_failed", "message": err.to_string() })),
+        )
+            .into_response(),
     }
-    (StatusCode::OK, Json(out)).into_response()
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -495,6 +1000,12 @@ pub(crate) async fn list_dataset_runs(
 pub(crate) struct ListDatasetRunsQuery {
     pub(crate) row_index: Option<u64>,
     pub(crate) limit: Option<u32>,
+    pub(crate) cursor: Option<String>,
+    pub(crate) status: Option<String>,
+    pub(crate) project_id: Option<String>,
+    pub(crate) has_missing_variables: Option<bool>,
+    pub(crate) created_after: Option<u64>,
+    pub(crate) created_before: Option<u64>,
 }
 
 pub(crate) async fn get_run(
@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    flow_graph::IsolatedNodePolicy,
+    now_ms,
+    runs::{load_project_doc, StoredFlowEdge, StoredFlowNode},
+    write_json_atomically, AppState, VariableSpec,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProjectSnapshot {
+    pub(crate) id: String,
+    pub(crate) project_id: String,
+    project_name: String,
+    pub(crate) nodes: Vec<StoredFlowNode>,
+    pub(crate) edges: Vec<StoredFlowEdge>,
+    pub(crate) variables: Vec<VariableSpec>,
+    #[serde(default, skip_serializing_if = "IsolatedNodePolicy::is_default")]
+    pub(crate) isolated_node_policy: IsolatedNodePolicy,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectSnapshotSummary {
+    id: String,
+    project_id: String,
+    project_name: String,
+    nodes: u64,
+    variables: u64,
+    created_at: String,
+}
+
+fn snapshots_dir(state: &AppState) -> PathBuf {
+    state.data_dir.join("snapshots")
+}
+
+fn snapshot_id(
+    project_id: &str,
+    nodes: &[StoredFlowNode],
+    edges: &[StoredFlowEdge],
+    variables: &[VariableSpec],
+    isolated_node_policy: IsolatedNodePolicy,
+) -> String {
+    let mut content = serde_json::json!({
+        "projectId": project_id,
+        "nodes": nodes,
+        "edges": edges,
+        "variables": variables,
+    });
+    if !isolated_node_policy.is_default() {
+        content["isolatedNodePolicy"] = serde_json::json!(isolated_node_policy);
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(content.to_string().as_bytes());
+    let hex = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    format!("snap_{hex}")
+}
+
+pub(crate) async fn load_snapshot(state: &AppState, id: &str) -> anyhow::Result<ProjectSnapshot> {
+    let path = snapshots_dir(state).join(format!("{id}.json"));
+    let text = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+pub(crate) async fn snapshot_project(
+    state: &AppState,
+    project_id: &str,
+) -> Result<ProjectSnapshot, (StatusCode, &'static str)> {
+    let project = load_project_doc(state, project_id)
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "project_not_found"))?;
+    let variables = project
+        .state
+        .variables
+        .iter()
+        .map(|v| VariableSpec {
+            id: v.id.clone(),
+            name: v.name.clone(),
+            r#type: v.r#type.clone(),
+            value: v.value.clone(),
+            resolver: v.resolver.clone(),
+        })
+        .collect::<Vec<_>>();
+    let id = snapshot_id(
+        &project.id,
+        &project.state.nodes,
+        &project.state.edges,
+        &variables,
+        project.state.isolated_node_policy,
+    );
+    if let Ok(existing) = load_snapshot(state, &id).await {
+        return Ok(existing);
+    }
+    let snapshot = ProjectSnapshot {
+        id,
+        project_id: project.id,
+        project_name: project.name,
+        nodes: project.state.nodes,
+        edges: project.state.edges,
+        variables,
+        isolated_node_policy: project.state.isolated_node_policy,
+        created_at: now_ms().to_string(),
+    };
+    let write = async {
+        let dir = snapshots_dir(state);
+        tokio::fs::create_dir_all(&dir).await?;
+        write_json_atomically(&dir.join(format!("{}.json", snapshot.id)), &snapshot).await
+    };
+    write
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "write_failed"))?;
+    Ok(snapshot)
+}
+
+pub(crate) async fn resolve_replay_snapshot(
+    state: &AppState,
+    project_id: &str,
+    snapshot_id: Option<&str>,
+) -> Result<ProjectSnapshot, (StatusCode, &'static str)> {
+    match snapshot_id.filter(|id| !id.trim().is_empty()) {
+        Some(id) => {
+            let snapshot = load_snapshot(state, id)
+                .await
+                .map_err(|_| (StatusCode::NOT_FOUND, "snapshot_not_found"))?;
+            if !project_id.trim().is_empty() && snapshot.project_id != project_id {
+                return Err((StatusCode::BAD_REQUEST, "snapshot_project_mismatch"));
+            }
+            Ok(snapshot)
+        }
+        None if project_id.trim().is_empty() => Err((StatusCode::BAD_REQUEST, "validation_failed")),
+        None => snapshot_project(state, project_id).await,
+    }
+}
+
+pub(crate) async fn create_project_snapshot(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+) -> axum::response::Response {
+    match snapshot_project(&state, &project_id).await {
+        Ok(snapshot) => (StatusCode::CREATED, Json(snapshot)).into_response(),
+        Err((status, error)) => (
+            status,
+            Json(serde_json::json!({ "error": error, "id": project_id })),
+        )
+            .into_response(),
+    }
+}
+
+pub(crate) async fn list_project_snapshots(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+) -> axum::response::Response {
+    let mut out = Vec::<ProjectSnapshotSummary>::new();
+    let mut rd = match tokio::fs::read_dir(snapshots_dir(&state)).await {
+        Ok(rd) => rd,
+        Err(_) => return (StatusCode::OK, Json(out)).into_response(),
+    };
+    while let Ok(Some(entry)) = rd.next_entry().await {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(text) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        let Ok(snapshot) = serde_json::from_str::<ProjectSnapshot>(&text) else {
+            continue;
+        };
+        if snapshot.project_id != project_id {
+            continue;
+        }
+        out.push(ProjectSnapshotSummary {
+            id: snapshot.id,
+            project_id: snapshot.project_id,
+            project_name: snapshot.project_name,
+            nodes: snapshot.nodes.len() as u64,
+            variables: snapshot.variables.len() as u64,
+            created_at: snapshot.created_at,
+        });
+    }
+    out.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+pub(crate) async fn get_snapshot(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> axum::response::Response {
+    match load_snapshot(&state, &id).await {
+        Ok(snapshot) => (StatusCode::OK, Json(snapshot)).into_response(),
+        Err(_) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "not_found", "id": id })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,626 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::vector_text::TextIndex;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct VectorFilter {
+    must: Option<Vec<VectorCondition>>,
+    should: Option<Vec<VectorCondition>>,
+    #[serde(alias = "must_not")]
+    must_not: Option<Vec<VectorCondition>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum VectorCondition {
+    Field(FieldCondition),
+    HasId(HasIdCondition),
+    IsEmpty(IsEmptyCondition),
+    IsNull(IsNullCondition),
+    Filter(VectorFilter),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct HasIdCondition {
+    #[serde(alias = "has_id")]
+    has_id: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct IsEmptyCondition {
+    #[serde(alias = "is_empty")]
+    is_empty: KeyRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct IsNullCondition {
+    #[serde(alias = "is_null")]
+    is_null: KeyRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyRef {
+    key: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct FieldCondition {
+    key: String,
+    r#match: Option<MatchCondition>,
+    range: Option<RangeCondition>,
+    exists: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct MatchCondition {
+    value: Option<Value>,
+    #[serde(alias = "in")]
+    any: Option<Vec<Value>>,
+    except: Option<Vec<Value>>,
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RangeCondition {
+    gt: Option<Value>,
+    gte: Option<Value>,
+    lt: Option<Value>,
+    lte: Option<Value>,
+}
+
+const MAX_FILTER_DEPTH: usize = 8;
+
+impl VectorFilter {
+    fn conditions(&self) -> impl Iterator<Item = &VectorCondition> {
+        self.must
+            .iter()
+            .chain(self.should.iter())
+            .chain(self.must_not.iter())
+            .flatten()
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        self.validate_at(0)
+    }
+
+    fn validate_at(&self, depth: usize) -> Result<(), &'static str> {
+        if depth > MAX_FILTER_DEPTH {
+            return Err("filter_too_deep");
+        }
+        for condition in self.conditions() {
+            match condition {
+                VectorCondition::Filter(filter) => filter.validate_at(depth + 1)?,
+                VectorCondition::Field(field) => field.validate()?,
+                VectorCondition::HasId(has_id) => {
+                    if has_id.has_id.iter().any(|id| point_id(id).is_none()) {
+                        return Err("invalid_filter_has_id");
+                    }
+                }
+                VectorCondition::IsEmpty(IsEmptyCondition { is_empty: key })
+                | VectorCondition::IsNull(IsNullCondition { is_null: key }) => {
+                    validate_key(&key.key)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn matches(&self, id: &str, payload: &Value) -> bool {
+        self.must.iter().flatten().all(|c| c.matches(id, payload))
+            && self.should.as_ref().is_none_or(|should| {
+                should.is_empty() || should.iter().any(|c| c.matches(id, payload))
+            })
+            && !self
+                .must_not
+                .iter()
+                .flatten()
+                .any(|c| c.matches(id, payload))
+    }
+}
+
+impl VectorCondition {
+    fn matches(&self, id: &str, payload: &Value) -> bool {
+        match self {
+            Self::Filter(filter) => filter.matches(id, payload),
+            Self::Field(field) => field.matches(payload),
+            Self::HasId(has_id) => has_id
+                .has_id
+                .iter()
+                .any(|v| point_id(v).as_deref() == Some(id)),
+            Self::IsEmpty(c) => values_at(payload, &c.is_empty.key)
+                .iter()
+                .all(|v| v.is_null()),
+            Self::IsNull(c) => values_at(payload, &c.is_null.key)
+                .iter()
+                .any(|v| v.is_null()),
+        }
+    }
+}
+
+pub(crate) fn point_id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.clone()),
+        Value::Number(n) if n.is_u64() => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn validate_key(key: &str) -> Result<(), &'static str> {
+    if key.trim().is_empty() || key.split('.').any(|s| key_segment(s).is_empty()) {
+        return Err("invalid_filter_key");
+    }
+    Ok(())
+}
+
+impl FieldCondition {
+    fn validate(&self) -> Result<(), &'static str> {
+        validate_key(&self.key)?;
+        let kinds = [
+            self.r#match.is_some(),
+            self.range.is_some(),
+            self.exists.is_some(),
+        ];
+        if kinds.iter().filter(|k| **k).count() != 1 {
+            return Err("invalid_filter_condition");
+        }
+        if let Some(m) = &self.r#match {
+            let kinds = [
+                m.value.is_some(),
+                m.any.is_some(),
+                m.except.is_some(),
+                m.text.is_some(),
+            ];
+            if kinds.iter().filter(|k| **k).count() != 1 {
+                return Err("invalid_filter_match");
+            }
+        }
+        if let Some(range) = &self.range {
+            let bounds = [&range.gt, &range.gte, &range.lt, &range.lte];
+            if bounds.iter().all(|b| b.is_none())
+                || bounds
+                    .iter()
+                    .any(|b| b.as_ref().is_some_and(|v| ordinal(v).is_none()))
+            {
+                return Err("invalid_filter_range");
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(&self, payload: &Value) -> bool {
+        let values = values_at(payload, &self.key);
+        if let Some(exists) = self.exists {
+            return values.iter().any(|v| !v.is_null()) == exists;
+        }
+        if let Some(range) = &self.range {
+            return values
+                .iter()
+                .filter_map(|v| ordinal(v))
+                .any(|x| range.contains(x));
+        }
+        let Some(m) = &self.r#match else {
+            return false;
+        };
+        if let Some(value) = &m.value {
+            return values.iter().any(|v| values_equal(v, value));
+        }
+        if let Some(any) = &m.any {
+            return values
+                .iter()
+                .any(|v| any.iter().any(|a| values_equal(v, a)));
+        }
+        if let Some(except) = &m.except {
+            return !values.is_empty()
+                && values
+                    .iter()
+                    .all(|v| !except.iter().any(|e| values_equal(v, e)));
+        }
+        if let Some(text) = &m.text {
+            let needle = text.to_lowercase();
+            return values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .any(|s| s.to_lowercase().contains(&needle));
+        }
+        false
+    }
+}
+
+impl RangeCondition {
+    fn bound(value: &Option<Value>) -> Option<f64> {
+        value.as_ref().and_then(ordinal)
+    }
+
+    fn contains(&self, x: f64) -> bool {
+        Self::bound(&self.gt).is_none_or(|b| x > b)
+            && Self::bound(&self.gte).is_none_or(|b| x >= b)
+            && Self::bound(&self.lt).is_none_or(|b| x < b)
+            && Self::bound(&self.lte).is_none_or(|b| x <= b)
+    }
+
+    fn bounds(&self) -> (Bound<OrdF64>, Bound<OrdF64>) {
+        let lower = match (Self::bound(&self.gt), Self::bound(&self.gte)) {
+            (Some(gt), Some(gte)) if gt >= gte => Bound::Excluded(OrdF64(gt)),
+            (_, Some(gte)) => Bound::Included(OrdF64(gte)),
+            (Some(gt), None) => Bound::Excluded(OrdF64(gt)),
+            (None, None) => Bound::Unbounded,
+        };
+        let upper = match (Self::bound(&self.lt), Self::bound(&self.lte)) {
+            (Some(lt), Some(lte)) if lt <= lte => Bound::Excluded(OrdF64(lt)),
+            (_, Some(lte)) => Bound::Included(OrdF64(lte)),
+            (Some(lt), None) => Bound::Excluded(OrdF64(lt)),
+            (None, None) => Bound::Unbounded,
+        };
+        (lower, upper)
+    }
+}
+
+pub(crate) fn matches_filter(id: &str, payload: &Value, filter: Option<&VectorFilter>) -> bool {
+    filter.is_none_or(|f| f.matches(id, payload))
+}
+
+fn key_segment(segment: &str) -> &str {
+    segment.strip_suffix("[]").unwrap_or(segment)
+}
+
+fn values_at<'a>(payload: &'a Value, key: &str) -> Vec<&'a Value> {
+    let mut current = vec![payload];
+    for segment in key.split('.') {
+        let segment = key_segment(segment);
+        current = current
+            .into_iter()
+            .flat_map(|v| match v {
+                Value::Array(items) => items.iter().collect::<Vec<_>>(),
+                other => vec![other],
+            })
+            .filter_map(|v| v.as_object()?.get(segment))
+            .collect();
+    }
+    current
+        .into_iter()
+        .flat_map(|v| match v {
+            Value::Array(items) => items.iter().collect::<Vec<_>>(),
+            other => vec![other],
+        })
+        .collect()
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) if a.is_number() && b.is_number() => x == y,
+        _ => a == b,
+    }
+}
+
+fn ordinal(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_datetime_ms(s).map(|ms| ms as f64),
+        _ => None,
+    }
+}
+
+fn digits(s: &str, range: std::ops::Range<usize>) -> Option<i64> {
+    let part = s.get(range)?;
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    part.parse().ok()
+}
+
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+pub(crate) fn parse_datetime_ms(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let bytes = s.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let (year, month, day) = (digits(s, 0..4)?, digits(s, 5..7)?, digits(s, 8..10)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut ms = days_from_civil(year, month, day) * 86_400_000;
+    if bytes.len() == 10 {
+        return Some(ms);
+    }
+    if !matches!(bytes[10], b'T' | b't' | b' ') || bytes.len() < 19 {
+        return None;
+    }
+    if bytes[13] != b':' || bytes[16] != b':' {
+        return None;
+    }
+    let (hour, minute, second) = (digits(s, 11..13)?, digits(s, 14..16)?, digits(s, 17..19)?);
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    ms += (hour * 3600 + minute * 60 + second) * 1000;
+    let mut rest = &s[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(|b| b.is_ascii_digit()).count();
+        if len == 0 {
+            return None;
+        }
+        let millis = format!("{:0<3}", &fraction[..len.min(3)]);
+        ms += millis.parse::<i64>().ok()?;
+        rest = &fraction[len..];
+    }
+    match rest {
+        "" | "Z" | "z" => Some(ms),
+        offset if offset.len() == 6 && offset.as_bytes()[3] == b':' => {
+            let sign = match offset.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let minutes = digits(offset, 1..3)? * 60 + digits(offset, 4..6)?;
+            Some(ms - sign * minutes * 60_000)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PayloadSchema {
+    Keyword,
+    Number,
+    Datetime,
+    Text,
+}
+
+impl PayloadSchema {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "keyword" | "bool" => Some(Self::Keyword),
+            "number" | "integer" | "float" => Some(Self::Number),
+            "datetime" => Some(Self::Datetime),
+            "text" | "fulltext" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PayloadIndexSpec {
+    pub(crate) field: String,
+    pub(crate) schema: PayloadSchema,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrdF64(f64);
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+enum FieldIndex {
+    Keyword(HashMap<String, HashSet<String>>),
+    Ordered(BTreeMap<OrdF64, HashSet<String>>),
+    Text(TextIndex),
+}
+
+fn keyword_key(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(format!("s:{s}")),
+        Value::Bool(b) => Some(format!("b:{b}")),
+        Value::Number(n) => Some(format!("n:{}", n.as_f64()?)),
+        _ => None,
+    }
+}
+
+impl FieldIndex {
+    fn new(schema: PayloadSchema) -> Self {
+        match schema {
+            PayloadSchema::Keyword => Self::Keyword(HashMap::new()),
+            PayloadSchema::Number | PayloadSchema::Datetime => Self::Ordered(BTreeMap::new()),
+            PayloadSchema::Text => Self::Text(TextIndex::default()),
+        }
+    }
+
+    fn update(&mut self, id: &str, values: &[&Value], insert: bool) {
+        if let Self::Text(index) = self {
+            let text = values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if insert {
+                index.insert(id, &text);
+            } else {
+                index.remove(id, &text);
+            }
+            return;
+        }
+        for value in values {
+            match self {
+                Self::Keyword(map) => {
+                    let Some(key) = keyword_key(value) else {
+                        continue;
+                    };
+                    if insert {
+                        map.entry(key).or_default().insert(id.to_string());
+                    } else if let Some(ids) = map.get_mut(&key) {
+                        ids.remove(id);
+                        if ids.is_empty() {
+                            map.remove(&key);
+                        }
+                    }
+                }
+                Self::Ordered(map) => {
+                    let Some(key) = ordinal(value).map(OrdF64) else {
+                        continue;
+                    };
+                    if insert {
+                        map.entry(key).or_default().insert(id.to_string());
+                    } else if let Some(ids) = map.get_mut(&key) {
+                        ids.remove(id);
+                        if ids.is_empty() {
+                            map.remove(&key);
+                        }
+                    }
+                }
+                Self::Text(_) => {}
+            }
+        }
+    }
+
+    fn lookup(&self, values: &[Value]) -> Option<HashSet<String>> {
+        let mut out = HashSet::new();
+        for value in values {
+            let ids = match self {
+                Self::Keyword(map) => map.get(&keyword_key(value)?),
+                Self::Ordered(map) => map.get(&OrdF64(ordinal(value)?)),
+                Self::Text(_) => return None,
+            };
+            out.extend(ids.into_iter().flatten().cloned());
+        }
+        Some(out)
+    }
+
+    fn range(&self, range: &RangeCondition) -> Option<HashSet<String>> {
+        let Self::Ordered(map) = self else {
+            return None;
+        };
+        let (lower, upper) = range.bounds();
+        if let (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) =
+            (lower, upper)
+        {
+            if l > u {
+                return Some(HashSet::new());
+            }
+        }
+        Some(
+            map.range((lower, upper))
+                .flat_map(|(_, ids)| ids.iter().cloned())
+                .collect(),
+        )
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct PayloadIndex {
+    fields: HashMap<String, FieldIndex>,
+}
+
+impl PayloadIndex {
+    pub(crate) fn build<'a>(
+        specs: &[PayloadIndexSpec],
+        points: impl IntoIterator<Item = (&'a str, &'a Value)>,
+    ) -> Self {
+        let mut index = Self {
+            fields: specs
+                .iter()
+                .map(|spec| (spec.field.clone(), FieldIndex::new(spec.schema)))
+                .collect(),
+        };
+        for (id, payload) in points {
+            index.insert(id, payload);
+        }
+        index
+    }
+
+    pub(crate) fn insert(&mut self, id: &str, payload: &Value) {
+        for (field, index) in &mut self.fields {
+            index.update(id, &values_at(payload, field), true);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: &str, payload: &Value) {
+        for (field, index) in &mut self.fields {
+            index.update(id, &values_at(payload, field), false);
+        }
+    }
+
+    pub(crate) fn candidates(&self, filter: &VectorFilter) -> Option<HashSet<String>> {
+        let mut sets = filter
+            .must
+            .iter()
+            .flatten()
+            .filter_map(|c| self.condition_candidates(c))
+            .collect::<Vec<_>>();
+        if let Some(should) = filter.should.as_ref().filter(|s| !s.is_empty()) {
+            let union = should
+                .iter()
+                .map(|c| self.condition_candidates(c))
+                .collect::<Option<Vec<_>>>();
+            if let Some(union) = union {
+                sets.push(union.into_iter().flatten().collect());
+            }
+        }
+        sets.sort_by_key(|s| s.len());
+        let mut sets = sets.into_iter();
+        let first = sets.next()?;
+        Some(sets.fold(first, |acc, s| acc.intersection(&s).cloned().collect()))
+    }
+
+    pub(crate) fn text_search(
+        &self,
+        field: Option<&str>,
+        query: &str,
+        k: usize,
+        accept: impl Fn(&str) -> bool,
+    ) -> Result<Vec<(String, f32)>, &'static str> {
+        let mut text_fields = self.fields.iter().filter_map(|(name, index)| match index {
+            FieldIndex::Text(text) if field.is_none_or(|f| f == name) => Some(text),
+            _ => None,
+        });
+        let index = text_fields.next().ok_or("text_index_missing")?;
+        if text_fields.next().is_some() {
+            return Err("text_field_required");
+        }
+        Ok(index.search(query, k, accept))
+    }
+
+    fn condition_candidates(&self, condition: &VectorCondition) -> Option<HashSet<String>> {
+        let field = match condition {
+            VectorCondition::Filter(filter) => return self.candidates(filter),
+            VectorCondition::HasId(has_id) => {
+                return Some(has_id.has_id.iter().filter_map(point_id).collect())
+            }
+            VectorCondition::IsEmpty(_) | VectorCondition::IsNull(_) => return None,
+            VectorCondition::Field(field) => field,
+        };
+        let index = self.fields.get(&field.key)?;
+        if let Some(range) = &field.range {
+            return index.range(range);
+        }
+        let m = field.r#match.as_ref()?;
+        if let Some(value) = &m.value {
+            return index.lookup(std::slice::from_ref(value));
+        }
+        index.lookup(m.any.as_ref()?)
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+pub(crate) const DEFAULT_RRF_K: f32 = 60.0;
+pub(crate) const DEFAULT_ALPHA: f32 = 0.5;
+pub(crate) const DEFAULT_MMR_LAMBDA: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SearchMode {
+    Vector,
+    Keyword,
+    Hybrid,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Fusion {
+    #[default]
+    Rrf,
+    Weighted,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MmrOptions {
+    pub(crate) lambda: Option<f32>,
+    pub(crate) fetch_k: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Ranked {
+    pub(crate) id: String,
+    pub(crate) relevance: f32,
+    pub(crate) vector_score: Option<f32>,
+    pub(crate) keyword_score: Option<f32>,
+}
+
+impl Ranked {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            relevance: 0.0,
+            vector_score: None,
+            keyword_score: None,
+        }
+    }
+}
+
+fn min_max(values: &[(String, f32)]) -> HashMap<&str, f32> {
+    let lo = values.iter().map(|(_, s)| *s).fold(f32::INFINITY, f32::min);
+    let hi = values
+        .iter()
+        .map(|(_, s)| *s)
+        .fold(f32::NEG_INFINITY, f32::max);
+    values
+        .iter()
+        .map(|(id, s)| {
+            let norm = if hi > lo { (s - lo) / (hi - lo) } else { 1.0 };
+            (id.as_str(), norm)
+        })
+        .collect()
+}
+
+pub(crate) fn fuse(
+    vector: &[(String, f32)],
+    keyword: &[(String, f32)],
+    fusion: Fusion,
+    alpha: f32,
+    rrf_k: f32,
+) -> Vec<Ranked> {
+    let mut fused = HashMap::<&str, Ranked>::new();
+    match fusion {
+        Fusion::Rrf => {
+            for (weight, list) in [(alpha, vector), (1.0 - alpha, keyword)] {
+                for (rank, (id, _)) in list.iter().enumerate() {
+                    fused
+                        .entry(id.as_str())
+                        .or_insert_with(|| Ranked::new(id))
+                        .relevance += 2.0 * weight / (rrf_k + rank as f32 + 1.0);
+                }
+            }
+        }
+        Fusion::Weighted => {
+            for (weight, list) in [(alpha, vector), (1.0 - alpha, keyword)] {
+                for (id, norm) in min_max(list) {
+                    fused.entry(id).or_insert_with(|| Ranked::new(id)).relevance += weight * norm;
+                }
+            }
+        }
+    }
+    for (id, score) in vector {
+        if let Some(r) = fused.get_mut(id.as_str()) {
+            r.vector_score = Some(*score);
+        }
+    }
+    for (id, score) in keyword {
+        if let Some(r) = fused.get_mut(id.as_str()) {
+            r.keyword_score = Some(*score);
+        }
+    }
+    let mut ranked = fused.into_values().collect::<Vec<_>>();
+    ranked.sort_by(|a, b| {
+        b.relevance
+            .total_cmp(&a.relevance)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    ranked
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let mut dot = 0.0f32;
+    let mut na = 0.0f32;
+    let mut nb = 0.0f32;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        na += x * x;
+        nb += y * y;
+    }
+    if na == 0.0 || nb == 0.0 {
+        return 0.0;
+    }
+    dot / (na.sqrt() * nb.sqrt())
+}
+
+pub(crate) fn mmr(
+    candidates: Vec<Ranked>,
+    vectors: &HashMap<String, Vec<f32>>,
+    lambda: f32,
+    k: usize,
+) -> Vec<Ranked> {
+    let pairs = candidates
+        .iter()
+        .map(|r| (r.id.clone(), r.relevance))
+        .collect::<Vec<_>>();
+    let relevance = min_max(&pairs)
+        .into_iter()
+        .map(|(id, s)| (id.to_string(), s))
+        .collect::<HashMap<_, _>>();
+    let mut remaining = candidates;
+    let mut selected = Vec::<Ranked>::with_capacity(k);
+    while selected.len() < k && !remaining.is_empty() {
+        let mut best = 0;
+        let mut best_score = f32::NEG_INFINITY;
+        for (i, candidate) in remaining.iter().enumerate() {
+            let redundancy = vectors
+                .get(&candidate.id)
+                .map(|v| {
+                    selected
+                        .iter()
+                        .filter_map(|s| vectors.get(&s.id))
+                        .map(|s| cosine(v, s))
+                        .fold(0.0f32, f32::max)
+                })
+                .unwrap_or(0.0);
+            let score = lambda * relevance[&candidate.id] - (1.0 - lambda) * redundancy;
+            if score > best_score {
+                best = i;
+                best_score = score;
+            }
+        }
+        selected.push(remaining.remove(best));
+    }
+    selected
+}
@@ -0,0 +1,682 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+const MAGIC: &[u8; 8] = b"CEHNSW03";
+const MAX_LEVEL: usize = 16;
+
+pub(crate) const DEFAULT_M: usize = 16;
+pub(crate) const DEFAULT_EF_CONSTRUCTION: usize = 200;
+pub(crate) const DEFAULT_EF_SEARCH: usize = 128;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Distance {
+    #[default]
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl Distance {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "cosine" => Some(Self::Cosine),
+            "dot" | "ip" | "inner_product" => Some(Self::Dot),
+            "euclidean" | "euclid" | "l2" => Some(Self::Euclidean),
+            _ => None,
+        }
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            Self::Cosine => 0,
+            Self::Dot => 1,
+            Self::Euclidean => 2,
+        }
+    }
+
+    fn prepare(&self, v: &[f32]) -> Vec<f32> {
+        match self {
+            Self::Cosine => normalize(v),
+            Self::Dot | Self::Euclidean => v.to_vec(),
+        }
+    }
+
+    fn raw(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::Cosine | Self::Dot => dot(a, b),
+            Self::Euclidean => -a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>(),
+        }
+    }
+
+    pub(crate) fn similarity(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Self::Cosine => dot(&normalize(a), &normalize(b)),
+            Self::Dot | Self::Euclidean => self.raw(a, b),
+        }
+    }
+
+    pub(crate) fn score(&self, similarity: f32) -> f32 {
+        match self {
+            Self::Cosine | Self::Dot => similarity,
+            Self::Euclidean => (-similarity).max(0.0).sqrt(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Quantization {
+    #[default]
+    None,
+    Int8,
+    Binary,
+}
+
+impl Quantization {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "none" => Some(Self::None),
+            "int8" | "scalar" => Some(Self::Int8),
+            "binary" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    pub(crate) fn supports(&self, distance: Distance) -> bool {
+        !(*self == Self::Binary && distance == Distance::Euclidean)
+    }
+
+    pub(crate) fn default_oversampling(&self) -> f32 {
+        match self {
+            Self::None => 1.0,
+            Self::Int8 => 2.0,
+            Self::Binary => 4.0,
+        }
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Int8 => 1,
+            Self::Binary => 2,
+        }
+    }
+}
+
+enum Codes {
+    F32(Vec<f32>),
+    Int8 { codes: Vec<i8>, scales: Vec<f32> },
+    Binary(Vec<u64>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored {
+    sim: f32,
+    slot: u32,
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sim
+            .total_cmp(&other.sim)
+            .then_with(|| other.slot.cmp(&self.slot))
+    }
+}
+
+pub(crate) struct HnswIndex {
+    dim: usize,
+    distance: Distance,
+    quantization: Quantization,
+    m: usize,
+    ef_construction: usize,
+    ids: Vec<String>,
+    slots: HashMap<String, u32>,
+    codes: Codes,
+    links: Vec<Vec<Vec<u32>>>,
+    locators: Vec<u64>,
+    deleted: Vec<bool>,
+    entry: Option<u32>,
+    rng: u64,
+}
+
+fn normalize(v: &[f32]) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vec![0.0; v.len()];
+    }
+    v.iter().map(|x| x / norm).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+impl HnswIndex {
+    pub(crate) fn new(dim: usize, distance: Distance, quantization: Quantization) -> Self {
+        Self {
+            dim,
+            distance,
+            quantization,
+            m: DEFAULT_M,
+            ef_construction: DEFAULT_EF_CONSTRUCTION,
+            ids: Vec::new(),
+            slots: HashMap::new(),
+            codes: match quantization {
+                Quantization::None => Codes::F32(Vec::new()),
+                Quantization::Int8 => Codes::Int8 {
+                    codes: Vec::new(),
+                    scales: Vec::new(),
+                },
+                Quantization::Binary => Codes::Binary(Vec::new()),
+            },
+            links: Vec::new(),
+            locators: Vec::new(),
+            deleted: Vec::new(),
+            entry: None,
+            rng: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    pub(crate) fn build<'a>(
+        dim: usize,
+        distance: Distance,
+        quantization: Quantization,
+        points: impl IntoIterator<Item = (&'a str, &'a [f32], u64)>,
+    ) -> Self {
+        let mut points = points.into_iter().collect::<Vec<_>>();
+        points.sort_by(|a, b| a.0.cmp(b.0));
+        let mut index = Self::new(dim, distance, quantization);
+        for (id, vector, locator) in points {
+            index.upsert(id, vector, locator);
+        }
+        index
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub(crate) fn tombstones(&self) -> usize {
+        self.ids.len() - self.slots.len()
+    }
+
+    pub(crate) fn needs_compaction(&self) -> bool {
+        self.tombstones() >= 64 && self.tombstones() > self.len()
+    }
+
+    pub(crate) fn locator(&self, id: &str) -> Option<u64> {
+        self.slots.get(id).map(|&slot| self.locators[slot as usize])
+    }
+
+    fn words(&self) -> usize {
+        self.dim.div_ceil(64)
+    }
+
+    fn push_code(&mut self, v: &[f32]) {
+        let words = self.words();
+        match &mut self.codes {
+            Codes::F32(vectors) => vectors.extend_from_slice(v),
+            Codes::Int8 { codes, scales } => {
+                let max = v.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+                let scale = if max == 0.0 { 1.0 } else { max / 127.0 };
+                codes.extend(
+                    v.iter()
+                        .map(|x| (x / scale).round().clamp(-127.0, 127.0) as i8),
+                );
+                scales.push(scale);
+            }
+            Codes::Binary(bits) => {
+                let start = bits.len();
+                bits.resize(start + words, 0);
+                for (i, x) in v.iter().enumerate() {
+                    if *x > 0.0 {
+                        bits[start + i / 64] |= 1 << (i % 64);
+                    }
+                }
+            }
+        }
+    }
+
+    fn decode(&self, slot: u32) -> Vec<f32> {
+        let start = slot as usize * self.dim;
+        match &self.codes {
+            Codes::F32(vectors) => vectors[start..start + self.dim].to_vec(),
+            Codes::Int8 { codes, scales } => {
+                let scale = scales[slot as usize];
+                codes[start..start + self.dim]
+                    .iter()
+                    .map(|&c| c as f32 * scale)
+                    .collect()
+            }
+            Codes::Binary(bits) => {
+                let unit = 1.0 / (self.dim as f32).sqrt();
+                let start = slot as usize * self.words();
+                (0..self.dim)
+                    .map(|i| {
+                        if bits[start + i / 64] & (1 << (i % 64)) != 0 {
+                            unit
+                        } else {
+                            -unit
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn sim(&self, query: &[f32], slot: u32) -> f32 {
+        let start = slot as usize * self.dim;
+        match &self.codes {
+            Codes::F32(vectors) => self.distance.raw(query, &vectors[start..start + self.dim]),
+            Codes::Int8 { codes, scales } => {
+                let scale = scales[slot as usize];
+                let codes = &codes[start..start + self.dim];
+                match self.distance {
+                    Distance::Cosine | Distance::Dot => {
+                        scale
+                            * query
+                                .iter()
+                                .zip(codes)
+                                .map(|(q, &c)| q * c as f32)
+                                .sum::<f32>()
+                    }
+                    Distance::Euclidean => -query
+                        .iter()
+                        .zip(codes)
+                        .map(|(q, &c)| {
+                            let d = q - c as f32 * scale;
+                            d * d
+                        })
+                        .sum::<f32>(),
+                }
+            }
+            Codes::Binary(bits) => {
+                let unit = 1.0 / (self.dim as f32).sqrt();
+                let start = slot as usize * self.words();
+                unit * query
+                    .iter()
+                    .enumerate()
+                    .map(|(i, q)| {
+                        if bits[start + i / 64] & (1 << (i % 64)) != 0 {
+                            *q
+                        } else {
+                            -q
+                        }
+                    })
+                    .sum::<f32>()
+            }
+        }
+    }
+
+    fn next_level(&mut self) -> usize {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        let u = ((z >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.m as f64).ln();
+        ((-u.ln() * ml).floor() as usize).min(MAX_LEVEL)
+    }
+
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry: &[u32],
+        ef: usize,
+        level: usize,
+        accept: &dyn Fn(u32) -> bool,
+    ) -> Vec<Scored> {
+        let mut visited = HashSet::<u32>::with_capacity(ef * 8);
+        let mut candidates = BinaryHeap::<Scored>::new();
+        let mut results = BinaryHeap::<Reverse<Scored>>::new();
+        for &slot in entry {
+            if !visited.insert(slot) {
+                continue;
+            }
+            let scored = Scored {
+                sim: self.sim(query, slot),
+                slot,
+            };
+            candidates.push(scored);
+            if accept(slot) {
+                results.push(Reverse(scored));
+            }
+        }
+        while let Some(current) = candidates.pop() {
+            if results.len() >= ef {
+                if let Some(Reverse(worst)) = results.peek() {
+                    if current.sim < worst.sim {
+                        break;
+                    }
+                }
+            }
+            let Some(neighbors) = self.links[current.slot as usize].get(level) else {
+                continue;
+            };
+            for &n in neighbors {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let scored = Scored {
+                    sim: self.sim(query, n),
+                    slot: n,
+                };
+                let worst = results.peek().map(|Reverse(w)| w.sim);
+                if results.len() < ef || worst.is_some_and(|w| scored.sim > w) {
+                    candidates.push(scored);
+                    if accept(n) {
+                        results.push(Reverse(scored));
+                        if results.len() > ef {
+                            results.pop();
+                        }
+                    }
+                }
+            }
+        }
+        let mut out = results.into_iter().map(|Reverse(s)| s).collect::<Vec<_>>();
+        out.sort_by(|a, b| b.cmp(a));
+        out
+    }
+
+    fn select_neighbors(&self, candidates: &[Scored], m: usize) -> Vec<u32> {
+        let mut selected = Vec::<u32>::with_capacity(m);
+        let mut pruned = Vec::<u32>::new();
+        for c in candidates {
+            if selected.len() >= m {
+                break;
+            }
+            let candidate = self.decode(c.slot);
+            let diverse = selected.iter().all(|&s| self.sim(&candidate, s) < c.sim);
+            if diverse {
+                selected.push(c.slot);
+            } else {
+                pruned.push(c.slot);
+            }
+        }
+        for slot in pruned {
+            if selected.len() >= m {
+                break;
+            }
+            selected.push(slot);
+        }
+        selected
+    }
+
+    fn greedy_descend(&self, query: &[f32], from_level: usize, to_level: usize) -> Vec<u32> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        let mut eps = vec![entry];
+        for level in (to_level..=from_level).rev() {
+            if let Some(best) = self.search_layer(query, &eps, 1, level, &|_| true).first() {
+                eps = vec![best.slot];
+            }
+        }
+        eps
+    }
+
+    fn top_level(&self) -> usize {
+        self.entry
+            .map(|e| self.links[e as usize].len() - 1)
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn upsert(&mut self, id: &str, vector: &[f32], locator: u64) {
+        self.remove(id);
+        let prepared = self.distance.prepare(vector);
+        self.push_code(&prepared);
+        let slot = self.ids.len() as u32;
+        let query = self.decode(slot);
+        let level = self.next_level();
+        self.ids.push(id.to_string());
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.locators.push(locator);
+        self.deleted.push(false);
+        self.slots.insert(id.to_string(), slot);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(slot);
+            return;
+        };
+        let top = self.top_level();
+        let mut eps = if level < top {
+            self.greedy_descend(&query, top, level + 1)
+        } else {
+            vec![entry]
+        };
+        for lev in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &eps, self.ef_construction, lev, &|_| true);
+            let neighbors = self.select_neighbors(&found, self.m);
+            let max_links = if lev == 0 { self.m * 2 } else { self.m };
+            self.links[slot as usize][lev] = neighbors.clone();
+            for n in neighbors {
+                self.links[n as usize][lev].push(slot);
+                if self.links[n as usize][lev].len() > max_links {
+                    let base = self.decode(n);
+                    let mut scored = self.links[n as usize][lev]
+                        .iter()
+                        .map(|&s| Scored {
+                            sim: self.sim(&base, s),
+                            slot: s,
+                        })
+                        .collect::<Vec<_>>();
+                    scored.sort_by(|a, b| b.cmp(a));
+                    self.links[n as usize][lev] = self.select_neighbors(&scored, max_links);
+                }
+            }
+            eps = found.iter().map(|s| s.slot).collect();
+        }
+        if level > top {
+            self.entry = Some(slot);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: &str) -> bool {
+        match self.slots.remove(id) {
+            Some(slot) => {
+                self.deleted[slot as usize] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn relocate(&mut self, locator_of: impl Fn(&str) -> Option<u64>) {
+        for (slot, id) in self.ids.iter().enumerate() {
+            if self.deleted[slot] {
+                continue;
+            }
+            if let Some(locator) = locator_of(id) {
+                self.locators[slot] = locator;
+            }
+        }
+    }
+
+    pub(crate) fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        ef: usize,
+        accept: impl Fn(&str) -> bool,
+    ) -> Vec<(String, f32)> {
+        if self.entry.is_none() || k == 0 {
+            return Vec::new();
+        }
+        let query = self.distance.prepare(query);
+        let eps = self.greedy_descend(&query, self.top_level(), 1);
+        let accept = |slot: u32| !self.deleted[slot as usize] && accept(&self.ids[slot as usize]);
+        self.search_layer(&query, &eps, ef.max(k), 0, &accept)
+            .into_iter()
+            .take(k)
+            .map(|s| (self.ids[s.slot as usize].clone(), s.sim))
+            .collect()
+    }
+
+    pub(crate) fn to_bytes(&self, generation: u64, checkpoint: u64) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64 + self.ids.len() * (self.m * 12 + 24));
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&generation.to_le_bytes());
+        out.extend_from_slice(&checkpoint.to_le_bytes());
+        out.extend_from_slice(&(self.dim as u32).to_le_bytes());
+        out.push(self.distance.code());
+        out.push(self.quantization.code());
+        out.extend_from_slice(&(self.m as u32).to_le_bytes());
+        out.extend_from_slice(&(self.ef_construction as u32).to_le_bytes());
+        out.extend_from_slice(&self.rng.to_le_bytes());
+        out.extend_from_slice(&self.entry.map(i64::from).unwrap_or(-1).to_le_bytes());
+        out.extend_from_slice(&(self.ids.len() as u32).to_le_bytes());
+        for (slot, id) in self.ids.iter().enumerate() {
+            out.extend_from_slice(&(id.len() as u32).to_le_bytes());
+            out.extend_from_slice(id.as_bytes());
+            out.push(u8::from(self.deleted[slot]));
+            if self.deleted[slot] {
+                for x in self.decode(slot as u32) {
+                    out.extend_from_slice(&x.to_le_bytes());
+                }
+            } else {
+                out.extend_from_slice(&self.locators[slot].to_le_bytes());
+            }
+            out.push(self.links[slot].len() as u8);
+            for level in &self.links[slot] {
+                out.extend_from_slice(&(level.len() as u32).to_le_bytes());
+                for n in level {
+                    out.extend_from_slice(&n.to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+
+    pub(crate) fn from_bytes(
+        bytes: &[u8],
+        dim: usize,
+        distance: Distance,
+        quantization: Quantization,
+        vector_at: impl Fn(u64) -> Option<Vec<f32>>,
+    ) -> Option<(Self, u64, u64)> {
+        let mut r = Reader::new(bytes);
+        if r.take(8)? != MAGIC {
+            return None;
+        }
+        let generation = r.u64()?;
+        let checkpoint = r.u64()?;
+        if r.u32()? as usize != dim || r.u8()? != distance.code() || r.u8()? != quantization.code()
+        {
+            return None;
+        }
+        let mut index = Self::new(dim, distance, quantization);
+        index.m = r.u32()? as usize;
+        index.ef_construction = r.u32()? as usize;
+        index.rng = r.u64()?;
+        let entry = r.u64()? as i64;
+        let count = r.u32()? as usize;
+        for slot in 0..count {
+            let len = r.u32()? as usize;
+            let id = String::from_utf8(r.take(len)?.to_vec()).ok()?;
+            let deleted = r.u8()? != 0;
+            let locator = if deleted {
+                let mut vector = Vec::with_capacity(dim);
+                for _ in 0..dim {
+                    vector.push(r.f32()?);
+                }
+                index.push_code(&vector);
+                0
+            } else {
+                r.u64()?
+            };
+            let levels = r.u8()? as usize;
+            let mut links = Vec::with_capacity(levels);
+            for _ in 0..levels {
+                let n = r.u32()? as usize;
+                let mut level = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let target = r.u32()?;
+                    if target as usize >= count {
+                        return None;
+                    }
+                    level.push(target);
+                }
+                links.push(level);
+            }
+            if !deleted {
+                let vector = vector_at(locator)?;
+                if vector.len() != dim {
+                    return None;
+                }
+                index.push_code(&index.distance.prepare(&vector));
+                index.slots.insert(id.clone(), slot as u32);
+            }
+            index.locators.push(locator);
+            index.ids.push(id);
+            index.links.push(links);
+            index.deleted.push(deleted);
+        }
+        index.entry = if entry < 0 {
+            None
+        } else {
+            Some(
+                u32::try_from(entry)
+                    .ok()
+                    .filter(|e| (*e as usize) < count)?,
+            )
+        };
+        Some((index, generation, checkpoint))
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub(crate) fn at(bytes: &'a [u8], pos: usize) -> Self {
+        Self { bytes, pos }
+    }
+
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let out = self.bytes.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(out)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    pub(crate) fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+}
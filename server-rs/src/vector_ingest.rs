@@ -0,0 +1,778 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    decrypt_datasource_url, decrypt_provider_api_key, is_readonly_sql, is_sql_driver,
+    load_datasource, load_provider, now_ms,
+    providers::ProviderClient,
+    query_any_rows, register_running_job, unregister_running_job,
+    vector_store::{
+        vector_collection_embedding_model, vector_delete_points_internal, vector_stored_documents,
+        vector_upsert_points_internal, StoredDocument, VectorPoint,
+    },
+    vector_text::is_cjk,
+    write_job, AppState, JobRecord,
+};
+
+const INGEST_MAX_DOCUMENTS: usize = 10_000;
+const INGEST_DEFAULT_CHUNK_SIZE: usize = 512;
+const INGEST_DEFAULT_OVERLAP: usize = 64;
+const INGEST_DEFAULT_BATCH_SIZE: usize = 32;
+const INGEST_MAX_BATCH_SIZE: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ChunkStrategy {
+    #[default]
+    Tokens,
+    Headings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChunkingOptions {
+    #[serde(default)]
+    strategy: ChunkStrategy,
+    chunk_size: Option<usize>,
+    overlap: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IngestDocument {
+    id: String,
+    text: String,
+    title: Option<String>,
+    #[serde(default)]
+    metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IngestDocumentsJobRequest {
+    provider_id: String,
+    collection: String,
+    documents: Option<Vec<IngestDocument>>,
+    data_source_id: Option<String>,
+    query: Option<String>,
+    id_field: Option<String>,
+    text_field: Option<String>,
+    title_field: Option<String>,
+    #[serde(default)]
+    metadata_fields: Vec<String>,
+    #[serde(default)]
+    chunking: ChunkingOptions,
+    batch_size: Option<usize>,
+    /// Off by default: a collection may also hold documents ingested from other sources.
+    #[serde(default)]
+    prune_missing: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IngestStats {
+    collection: String,
+    model: String,
+    strategy: ChunkStrategy,
+    chunk_size: usize,
+    overlap: usize,
+    batch_size: usize,
+    documents: u64,
+    skipped: u64,
+    ingested: u64,
+    failed: u64,
+    chunks: u64,
+    deleted_chunks: u64,
+    pruned: u64,
+    batches: u64,
+    failures: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    text: String,
+    start: usize,
+    end: usize,
+    headings: Vec<String>,
+}
+
+struct Token {
+    bytes: (usize, usize),
+    chars: (usize, usize),
+}
+
+fn scan_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word: Option<Token> = None;
+    for (char_idx, (byte_idx, c)) in text.char_indices().enumerate() {
+        let end = (byte_idx + c.len_utf8(), char_idx + 1);
+        if c.is_alphanumeric() && !is_cjk(c) {
+            match word.as_mut() {
+                Some(w) => {
+                    w.bytes.1 = end.0;
+                    w.chars.1 = end.1;
+                }
+                None => {
+                    word = Some(Token {
+                        bytes: (byte_idx, end.0),
+                        chars: (char_idx, end.1),
+                    })
+                }
+            }
+            continue;
+        }
+        tokens.extend(word.take());
+        if !c.is_whitespace() {
+            tokens.push(Token {
+                bytes: (byte_idx, end.0),
+                chars: (char_idx, end.1),
+            });
+        }
+    }
+    tokens.extend(word);
+    tokens
+}
+
+fn token_chunks(
+    text: &str,
+    char_base: usize,
+    size: usize,
+    overlap: usize,
+    headings: &[String],
+) -> Vec<Chunk> {
+    let tokens = scan_tokens(text);
+    let step = size.saturating_sub(overlap).max(1);
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let j = (i + size).min(tokens.len());
+        let (first, last) = (&tokens[i], &tokens[j - 1]);
+        out.push(Chunk {
+            text: text[first.bytes.0..last.bytes.1].to_string(),
+            start: char_base + first.chars.0,
+            end: char_base + last.chars.1,
+            headings: headings.to_vec(),
+        });
+        if j == tokens.len() {
+            break;
+        }
+        i += step;
+    }
+    out
+}
+
+fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = rest.trim();
+    let title = match title.trim_end_matches('#') {
+        "" => "",
+        t if t.ends_with([' ', '\t']) => t.trim_end(),
+        _ => title,
+    };
+    Some((level, title))
+}
+
+fn heading_chunks(text: &str, size: usize, overlap: usize) -> Vec<Chunk> {
+    let mut sections = Vec::<(usize, Vec<String>)>::new();
+    let mut path = Vec::<(usize, String)>::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        match (fence, marker) {
+            (Some(open), Some(m)) if open == m => fence = None,
+            (None, Some(m)) => fence = Some(m),
+            (None, None) => {
+                if let Some((level, title)) = markdown_heading(line.trim_end_matches(['\n', '\r']))
+                {
+                    path.retain(|(l, _)| *l < level);
+                    path.push((level, title.to_string()));
+                    sections.push((offset, path.iter().map(|(_, t)| t.clone()).collect()));
+                }
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if sections.first().is_none_or(|(start, _)| *start > 0) {
+        sections.insert(0, (0, Vec::new()));
+    }
+
+    let mut out = Vec::new();
+    let mut char_base = 0;
+    let mut prev = 0;
+    for (idx, (start, headings)) in sections.iter().enumerate() {
+        let end = sections.get(idx + 1).map_or(text.len(), |(s, _)| *s);
+        char_base += text[prev..*start].chars().count();
+        prev = *start;
+        out.extend(token_chunks(
+            &text[*start..end],
+            char_base,
+            size,
+            overlap,
+            headings,
+        ));
+    }
+    out
+}
+
+fn chunk_document(text: &str, strategy: ChunkStrategy, size: usize, overlap: usize) -> Vec<Chunk> {
+    match strategy {
+        ChunkStrategy::Tokens => token_chunks(text, 0, size, overlap, &[]),
+        ChunkStrategy::Headings => heading_chunks(text, size, overlap),
+    }
+}
+
+fn content_hash(doc: &IngestDocument, stats: &IngestStats) -> String {
+    let content = serde_json::json!({
+        "strategy": stats.strategy,
+        "chunkSize": stats.chunk_size,
+        "overlap": stats.overlap,
+        "model": stats.model,
+        "title": doc.title,
+        "text": doc.text,
+        "metadata": doc.metadata,
+    });
+    let mut hasher = Sha256::new();
+    hasher.update(content.to_string().as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>()
+}
+
+fn value_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn check_documents(documents: &[IngestDocument]) -> Result<(), (StatusCode, &'static str, String)> {
+    if documents.len() > INGEST_MAX_DOCUMENTS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "limit_exceeded",
+            "too_many_documents".to_string(),
+        ));
+    }
+    let mut seen = HashSet::new();
+    if let Some(doc) = documents
+        .iter()
+        .find(|d| d.id.trim().is_empty() || !seen.insert(d.id.as_str()))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            format!("invalid_document_id: {}", doc.id),
+        ));
+    }
+    Ok(())
+}
+
+async fn datasource_url(
+    state: &AppState,
+    req: &IngestDocumentsJobRequest,
+    data_source_id: &str,
+) -> Result<String, (StatusCode, &'static str, String)> {
+    let query = req.query.as_deref().unwrap_or_default().trim();
+    if req.id_field.is_none() || req.text_field.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+            "idField and textField are required".to_string(),
+        ));
+    }
+    if !is_readonly_sql(query) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "readonly_required",
+            "仅允许只读 SELECT/WITH 查询".to_string(),
+        ));
+    }
+    let ds = load_datasource(state, data_source_id)
+        .await
+        .map_err(|err| {
+            (
+                StatusCode::BAD_REQUEST,
+                "datasource_failed",
+                err.to_string(),
+            )
+        })?;
+    if !is_sql_driver(&ds.driver) {
+        return Err((StatusCode::BAD_REQUEST, "unsupported_driver", ds.driver));
+    }
+    if !ds.allow_schema.unwrap_or(true) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            "未授权：结构/读取能力已关闭".to_string(),
+        ));
+    }
+    decrypt_datasource_url(state, data_source_id)
+        .await
+        .map_err(|err| (StatusCode::BAD_REQUEST, "decrypt_failed", err.to_string()))
+}
+
+async fn query_documents(
+    url: &str,
+    req: &IngestDocumentsJobRequest,
+) -> Result<Vec<IngestDocument>, String> {
+    let query = req.query.as_deref().unwrap_or_default().trim();
+    let id_field = req.id_field.as_deref().unwrap_or_default();
+    let text_field = req.text_field.as_deref().unwrap_or_default();
+    let rows = query_any_rows(url, query, INGEST_MAX_DOCUMENTS as i64 + 1)
+        .await
+        .map_err(|err| format!("query_failed: {err}"))?;
+    let documents = rows
+        .into_iter()
+        .map(|row| IngestDocument {
+            id: value_text(row.get(id_field)),
+            text: value_text(row.get(text_field)),
+            title: req
+                .title_field
+                .as_deref()
+                .map(|f| value_text(row.get(f)))
+                .filter(|t| !t.is_empty()),
+            metadata: req
+                .metadata_fields
+                .iter()
+                .filter_map(|f| Some((f.clone(), row.get(f)?.clone())))
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    check_documents(&documents).map_err(|(_, error, message)| format!("{error}: {message}"))?;
+    Ok(documents)
+}
+
+enum IngestSource {
+    Documents(Vec<IngestDocument>),
+    Query { url: String },
+}
+
+struct IngestRun {
+    req: IngestDocumentsJobRequest,
+    client: ProviderClient,
+    source: IngestSource,
+    stored: HashMap<String, StoredDocument>,
+    stats: IngestStats,
+}
+
+struct PendingDocument {
+    doc: IngestDocument,
+    hash: String,
+    chunks: Vec<Chunk>,
+    vectors: Vec<Vec<f32>>,
+    failed: bool,
+    stale: Vec<String>,
+}
+
+impl PendingDocument {
+    fn points(&self, job_id: &str) -> Vec<VectorPoint> {
+        self.chunks
+            .iter()
+            .zip(&self.vectors)
+            .enumerate()
+            .map(|(idx, (chunk, vector))| {
+                let mut payload = self.doc.metadata.clone();
+                if let Some(title) = &self.doc.title {
+                    payload.insert("title".to_string(), serde_json::json!(title));
+                }
+                payload.insert("text".to_string(), serde_json::json!(chunk.text));
+                payload.insert("_documentId".to_string(), serde_json::json!(self.doc.id));
+                payload.insert("_chunkIndex".to_string(), serde_json::json!(idx));
+                payload.insert(
+                    "_chunkCount".to_string(),
+                    serde_json::json!(self.chunks.len()),
+                );
+                payload.insert("_start".to_string(), serde_json::json!(chunk.start));
+                payload.insert("_end".to_string(), serde_json::json!(chunk.end));
+                if !chunk.headings.is_empty() {
+                    payload.insert("_headings".to_string(), serde_json::json!(chunk.headings));
+                }
+                payload.insert("_contentHash".to_string(), serde_json::json!(self.hash));
+                payload.insert("_jobId".to_string(), serde_json::json!(job_id));
+                VectorPoint {
+                    id: format!("{}#{idx}", self.doc.id),
+                    vector: vector.clone(),
+                    payload: serde_json::Value::Object(payload),
+                    batch_id: Some(job_id.to_string()),
+                }
+            })
+            .collect()
+    }
+}
+
+async fn write_documents(
+    state: &AppState,
+    collection: &str,
+    model: &str,
+    job_id: &str,
+    docs: &[PendingDocument],
+    stats: &mut IngestStats,
+) {
+    for pending in docs.iter().filter(|d| !d.failed) {
+        let written = async {
+            if !pending.chunks.is_empty() {
+                vector_upsert_points_internal(
+                    state,
+                    collection,
+                    pending.points(job_id),
+                    Some(model),
+                )
+                .await?;
+            }
+            vector_delete_points_internal(state, collection, &pending.stale).await
+        }
+        .await;
+        match written {
+            Ok(deleted) => {
+                stats.ingested += 1;
+                stats.chunks += pending.chunks.len() as u64;
+                stats.deleted_chunks += deleted;
+            }
+            Err(err) => {
+                stats.failed += 1;
+                stats.failures.push(serde_json::json!({
+                    "stage": "write",
+                    "documentIds": [pending.doc.id],
+                    "message": err.to_string(),
+                }));
+            }
+        }
+    }
+}
+
+fn ingest_summary(stats: &IngestStats, prune_missing: bool) -> String {
+    let mut summary = format!(
+        "ingested={} skipped={} chunks={}",
+        stats.ingested, stats.skipped, stats.chunks
+    );
+    if prune_missing {
+        summary.push_str(&format!(" pruned={}", stats.pruned));
+    }
+    summary
+}
+
+pub(crate) async fn job_ingest_documents(
+    State(state): State<AppState>,
+    Json(req): Json<IngestDocumentsJobRequest>,
+) -> axum::response::Response {
+    let chunk_size = req.chunking.chunk_size.unwrap_or(INGEST_DEFAULT_CHUNK_SIZE);
+    let overlap = req.chunking.overlap.unwrap_or(INGEST_DEFAULT_OVERLAP);
+    let from_datasource = req.data_source_id.clone().filter(|s| !s.trim().is_empty());
+    if req.provider_id.trim().is_empty()
+        || req.collection.trim().is_empty()
+        || !(16..=8192).contains(&chunk_size)
+        || overlap >= chunk_size
+        || req.documents.is_some() == from_datasource.is_some()
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "validation_failed" })),
+        )
+            .into_response();
+    }
+
+    let prepared = async {
+        let stored = vector_stored_documents(&state, &req.collection)
+            .await
+            .map_err(|(status, error)| (status, error, req.collection.clone()))?;
+        let provider = load_provider(&state, &req.provider_id).await.map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                "provider_failed",
+                "not_found".to_string(),
+            )
+        })?;
+        let api_key = decrypt_provider_api_key(&state, &req.provider_id)
+            .await
+            .map_err(|err| (StatusCode::BAD_REQUEST, "provider_failed", err.to_string()))?;
+        let client = ProviderClient::new(&provider, api_key);
+        let model = vector_collection_embedding_model(&state, &req.collection)
+            .await
+            .or_else(|| client.embedding_model())
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                "provider_failed",
+                "model_required".to_string(),
+            ))?;
+        let source = match (req.documents.clone(), from_datasource.as_deref()) {
+            (Some(documents), _) => {
+                check_documents(&documents)?;
+                IngestSource::Documents(documents)
+            }
+            (None, Some(id)) => IngestSource::Query {
+                url: datasource_url(&state, &req, id).await?,
+            },
+            (None, None) => IngestSource::Documents(Vec::new()),
+        };
+        Ok::<_, (StatusCode, &str, String)>((stored, client, model, source))
+    }
+    .await;
+    let (stored, client, model, source) = match prepared {
+        Ok(v) => v,
+        Err((status, error, message)) => {
+            return (
+                status,
+                Json(serde_json::json!({ "error": error, "message": message })),
+            )
+                .into_response();
+        }
+    };
+    let stats = IngestStats {
+        collection: req.collection.clone(),
+        model,
+        strategy: req.chunking.strategy,
+        chunk_size,
+        overlap,
+        batch_size: req
+            .batch_size
+            .unwrap_or(INGEST_DEFAULT_BATCH_SIZE)
+            .clamp(1, INGEST_MAX_BATCH_SIZE),
+        documents: match &source {
+            IngestSource::Documents(documents) => documents.len() as u64,
+            IngestSource::Query { .. } => 0,
+        },
+        ..IngestStats::default()
+    };
+
+    let job = JobRecord {
+        id: format!("job_{}", now_ms()),
+        job_type: "ingest_documents".to_string(),
+        status: "running".to_string(),
+        created_at: now_ms().to_string(),
+        finished_at: None,
+        summary: None,
+        stats: serde_json::to_value(&stats).unwrap_or_default(),
+        error: None,
+    };
+    let Some(cancel) = register_running_job(&state, &job.id) else {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "job_running", "id": job.id })),
+        )
+            .into_response();
+    };
+    if write_job(&state, &job).await.is_err() {
+        unregister_running_job(&state, &job.id);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "write_failed" })),
+        )
+            .into_response();
+    }
+
+    let run = IngestRun {
+        req,
+        client,
+        source,
+        stored,
+        stats,
+    };
+    let background = job.clone();
+    tokio::spawn(async move {
+        let job_id = background.id.clone();
+        run_ingest_job(&state, background, run, &cancel).await;
+        unregister_running_job(&state, &job_id);
+    });
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({ "job": job })),
+    )
+        .into_response()
+}
+
+async fn run_ingest_job(state: &AppState, mut job: JobRecord, run: IngestRun, cancel: &AtomicBool) {
+    let IngestRun {
+        req,
+        client,
+        source,
+        mut stored,
+        mut stats,
+    } = run;
+    let documents = match source {
+        IngestSource::Documents(documents) => documents,
+        IngestSource::Query { url } => match query_documents(&url, &req).await {
+            Ok(documents) => documents,
+            Err(message) => {
+                job.status = "failed".to_string();
+                job.finished_at = Some(now_ms().to_string());
+                job.error = Some(message);
+                let _ = write_job(state, &job).await;
+                return;
+            }
+        },
+    };
+    stats.documents = documents.len() as u64;
+    let (chunk_size, overlap, batch_size) = (stats.chunk_size, stats.overlap, stats.batch_size);
+    let model = stats.model.clone();
+
+    let mut pending = Vec::<PendingDocument>::new();
+    for doc in documents {
+        let hash = content_hash(&doc, &stats);
+        let previous = stored.remove(&doc.id).unwrap_or_default();
+        if previous.content_hash.as_deref() == Some(hash.as_str()) {
+            stats.skipped += 1;
+            continue;
+        }
+        let chunks = chunk_document(&doc.text, stats.strategy, chunk_size, overlap);
+        let ids = (0..chunks.len())
+            .map(|idx| format!("{}#{idx}", doc.id))
+            .collect::<HashSet<_>>();
+        let stale = previous
+            .chunk_ids
+            .into_iter()
+            .filter(|id| !ids.contains(id))
+            .collect();
+        pending.push(PendingDocument {
+            doc,
+            hash,
+            chunks,
+            vectors: Vec::new(),
+            failed: false,
+            stale,
+        });
+    }
+
+    let queue = pending
+        .iter()
+        .enumerate()
+        .flat_map(|(doc_idx, d)| d.chunks.iter().map(move |c| (doc_idx, c.text.clone())))
+        .collect::<Vec<_>>();
+    let mut done = 0;
+    for (batch_index, batch) in queue.chunks(batch_size).enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            job.status = "cancelled".to_string();
+            job.finished_at = Some(now_ms().to_string());
+            break;
+        }
+        stats.batches += 1;
+        let texts = batch.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>();
+        let embeddings = client
+            .embeddings(&model, &texts)
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|v| {
+                if v.len() == texts.len() {
+                    Ok(v)
+                } else {
+                    Err("embedding_count_mismatch".to_string())
+                }
+            });
+        match embeddings {
+            Ok(vectors) => {
+                for ((doc_idx, _), vector) in batch.iter().zip(vectors) {
+                    pending[*doc_idx].vectors.push(vector);
+                }
+            }
+            Err(message) => {
+                let mut doc_ids = Vec::<String>::new();
+                for (doc_idx, _) in batch {
+                    let doc = &mut pending[*doc_idx];
+                    if !doc.failed {
+                        doc.failed = true;
+                        doc_ids.push(doc.doc.id.clone());
+                    }
+                }
+                stats.failures.push(serde_json::json!({
+                    "batch": batch_index,
+                    "stage": "embedding",
+                    "documentIds": doc_ids,
+                    "message": message,
+                }));
+            }
+        }
+        let ready = batch.last().map_or(done, |(doc_idx, _)| {
+            if pending[*doc_idx].vectors.len() == pending[*doc_idx].chunks.len() {
+                doc_idx + 1
+            } else {
+                *doc_idx
+            }
+        });
+        write_documents(
+            state,
+            &req.collection,
+            &model,
+            &job.id,
+            &pending[done..ready],
+            &mut stats,
+        )
+        .await;
+        done = ready;
+        job.summary = Some(ingest_summary(&stats, req.prune_missing));
+        job.stats = serde_json::to_value(&stats).unwrap_or_default();
+        let _ = write_job(state, &job).await;
+    }
+
+    if job.status == "running" {
+        write_documents(
+            state,
+            &req.collection,
+            &model,
+            &job.id,
+            &pending[done..],
+            &mut stats,
+        )
+        .await;
+        if req.prune_missing {
+            let mut missing = stored.into_iter().collect::<Vec<_>>();
+            missing.sort_by(|a, b| a.0.cmp(&b.0));
+            for (id, doc) in missing {
+                match vector_delete_points_internal(state, &req.collection, &doc.chunk_ids).await {
+                    Ok(deleted) => {
+                        stats.pruned += 1;
+                        stats.deleted_chunks += deleted;
+                    }
+                    Err(err) => stats.failures.push(serde_json::json!({
+                        "stage": "prune",
+                        "documentIds": [id],
+                        "message": err.to_string(),
+                    })),
+                }
+            }
+        }
+    }
+    stats.failed += pending.iter().filter(|d| d.failed).count() as u64;
+
+    if job.status == "running" {
+        job.finished_at = Some(now_ms().to_string());
+        job.status = if stats.failures.is_empty() {
+            "succeeded"
+        } else if stats.ingested > 0 || stats.skipped > 0 || stats.pruned > 0 {
+            "partial"
+        } else {
+            "failed"
+        }
+        .to_string();
+    }
+    job.summary = Some(ingest_summary(&stats, req.prune_missing));
+    job.error = stats
+        .failures
+        .first()
+        .and_then(|f| f.get("message"))
+        .and_then(|m| m.as_str())
+        .map(|m| m.to_string());
+    job.stats = serde_json::to_value(&stats).unwrap_or_default();
+    let _ = write_job(state, &job).await;
+}
@@ -0,0 +1,746 @@
+use std::{collections::HashMap, time::Instant};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::{
+    vector_filter::{point_id, PayloadSchema, VectorFilter},
+    vector_index::{Distance, Quantization, DEFAULT_EF_CONSTRUCTION, DEFAULT_M},
+    vector_store::{
+        vector_collection_distance, vector_collections, vector_create_collection,
+        vector_create_payload_index, vector_delete_payload_index, vector_delete_points,
+        vector_drop_collection, vector_get_points, vector_scroll_points, vector_search,
+        vector_stats, vector_upsert_points, CreateVectorCollectionRequest,
+        DeleteVectorPointsRequest, GetVectorPointsRequest, PayloadIndexRequest,
+        ScrollVectorPointsRequest, SearchVectorRequest, UpsertVectorPointsRequest,
+        VectorCollectionStats, VectorError, VectorPoint, VectorPointRecord,
+    },
+    AppState,
+};
+
+const QDRANT_MAX_SCROLL_LIMIT: usize = 999;
+
+type QdrantResult = Result<Value, (StatusCode, String)>;
+
+fn reply(started: Instant, result: QdrantResult) -> axum::response::Response {
+    let time = started.elapsed().as_secs_f64();
+    match result {
+        Ok(result) => (
+            StatusCode::OK,
+            Json(json!({ "result": result, "status": "ok", "time": time })),
+        )
+            .into_response(),
+        Err((status, message)) => (
+            status,
+            Json(json!({ "status": { "error": message }, "time": time })),
+        )
+            .into_response(),
+    }
+}
+
+fn bad_request(message: impl Into<String>) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, message.into())
+}
+
+fn store_error(err: VectorError) -> (StatusCode, String) {
+    (err.status, err.to_string())
+}
+
+fn parse<T: DeserializeOwned>(value: Value) -> Result<T, (StatusCode, String)> {
+    serde_json::from_value(value)
+        .map_err(|err| bad_request(format!("Format error in JSON body: {err}")))
+}
+
+fn parse_filter(filter: Option<Value>) -> Result<Option<VectorFilter>, (StatusCode, String)> {
+    filter.filter(|f| !f.is_null()).map(parse).transpose()
+}
+
+fn qdrant_id(id: &str) -> Value {
+    match id.parse::<u64>() {
+        Ok(n) if n.to_string() == id => Value::from(n),
+        _ => Value::String(id.to_string()),
+    }
+}
+
+fn qdrant_distance(distance: Distance) -> &'static str {
+    match distance {
+        Distance::Cosine => "Cosine",
+        Distance::Dot => "Dot",
+        Distance::Euclidean => "Euclid",
+    }
+}
+
+fn qdrant_data_type(schema: PayloadSchema) -> &'static str {
+    match schema {
+        PayloadSchema::Keyword => "keyword",
+        PayloadSchema::Number => "float",
+        PayloadSchema::Datetime => "datetime",
+        PayloadSchema::Text => "text",
+    }
+}
+
+fn point_ids(ids: &[Value]) -> Result<Vec<String>, (StatusCode, String)> {
+    ids.iter()
+        .map(|id| point_id(id).ok_or_else(|| bad_request(format!("Invalid point id: {id}"))))
+        .collect()
+}
+
+fn wants(selector: Option<&Value>, default: bool) -> bool {
+    match selector {
+        None | Some(Value::Null) => default,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn select_payload(selector: Option<&Value>, default: bool, payload: &Value) -> Value {
+    let pick = |fields: &Vec<Value>, keep: bool| {
+        let fields = fields.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+        let map = payload
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| fields.contains(&key.as_str()) == keep)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Value::Object(map)
+    };
+    match selector {
+        None | Some(Value::Null) if default => payload.clone(),
+        Some(Value::Bool(true)) => payload.clone(),
+        Some(Value::Array(fields)) => pick(fields, true),
+        Some(Value::Object(o)) => match (o.get("include"), o.get("exclude")) {
+            (Some(Value::Array(fields)), _) => pick(fields, true),
+            (_, Some(Value::Array(fields))) => pick(fields, false),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
+
+fn unnamed_vector(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Array(_) => Some(value),
+        Value::Object(o) => match o.get("vector") {
+            Some(vector @ Value::Array(_)) => Some(vector),
+            _ if o.len() == 1 => o.get("").filter(|v| v.is_array()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn qdrant_point(
+    point: &VectorPointRecord,
+    with_payload: Option<&Value>,
+    default_payload: bool,
+    with_vector: bool,
+) -> Value {
+    json!({
+        "id": qdrant_id(&point.id),
+        "payload": select_payload(with_payload, default_payload, &point.payload),
+        "vector": if with_vector { json!(point.vector) } else { Value::Null },
+    })
+}
+
+pub(super) async fn qdrant_list_collections(
+    State(state): State<AppState>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let collections = vector_collections(&state)
+        .await
+        .into_iter()
+        .map(|c| json!({ "name": c.name }))
+        .collect::<Vec<_>>();
+    reply(started, Ok(json!({ "collections": collections })))
+}
+
+async fn collection_stats(
+    state: &AppState,
+    name: &str,
+) -> Result<VectorCollectionStats, (StatusCode, String)> {
+    vector_stats(state, name).await.map_err(store_error)
+}
+
+pub(super) async fn qdrant_get_collection(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = collection_stats(&state, &name).await.map(|stats| {
+        let meta = &stats.collection;
+        let payload_schema = stats
+            .payload_schema
+            .iter()
+            .filter_map(|(key, field)| {
+                let data_type = qdrant_data_type(field.index?);
+                Some((
+                    key.clone(),
+                    json!({ "data_type": data_type, "points": field.points }),
+                ))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let quantization = match meta.quantization {
+            Quantization::Int8 => json!({ "scalar": { "type": "int8", "always_ram": true } }),
+            Quantization::Binary => json!({ "binary": { "always_ram": true } }),
+            Quantization::None => Value::Null,
+        };
+        json!({
+            "status": "green",
+            "optimizer_status": "ok",
+            "vectors_count": stats.points,
+            "indexed_vectors_count": stats.points,
+            "points_count": stats.points,
+            "segments_count": 1,
+            "config": {
+                "params": {
+                    "vectors": {
+                        "size": meta.dimension,
+                        "distance": qdrant_distance(meta.distance),
+                    },
+                    "shard_number": 1,
+                    "replication_factor": 1,
+                    "write_consistency_factor": 1,
+                    "on_disk_payload": true,
+                },
+                "hnsw_config": {
+                    "m": DEFAULT_M,
+                    "ef_construct": DEFAULT_EF_CONSTRUCTION,
+                    "full_scan_threshold": 5_000,
+                    "max_indexing_threads": 0,
+                    "on_disk": false,
+                },
+                "optimizer_config": {
+                    "deleted_threshold": 0.5,
+                    "vacuum_min_vector_number": 256,
+                    "default_segment_number": 1,
+                    "max_segment_size": null,
+                    "memmap_threshold": null,
+                    "indexing_threshold": 0,
+                    "flush_interval_sec": 0,
+                    "max_optimization_threads": null,
+                },
+                "wal_config": { "wal_capacity_mb": 0, "wal_segments_ahead": 0 },
+                "quantization_config": quantization,
+            },
+            "payload_schema": payload_schema,
+        })
+    });
+    reply(started, result)
+}
+
+pub(super) async fn qdrant_collection_exists(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = match collection_stats(&state, &name).await {
+        Ok(_) => Ok(json!({ "exists": true })),
+        Err((StatusCode::NOT_FOUND, _)) => Ok(json!({ "exists": false })),
+        Err(err) => Err(err),
+    };
+    reply(started, result)
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantCreateCollectionRequest {
+    vectors: Value,
+    quantization_config: Option<Value>,
+}
+
+pub(super) async fn qdrant_create_collection(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantCreateCollectionRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    reply(started, create_collection(&state, &name, req).await)
+}
+
+async fn create_collection(
+    state: &AppState,
+    name: &str,
+    req: QdrantCreateCollectionRequest,
+) -> QdrantResult {
+    let Some(size) = req.vectors.get("size").and_then(Value::as_u64) else {
+        return Err(bad_request("Named vectors are not supported"));
+    };
+    let distance = match req.vectors["distance"].as_str().unwrap_or("Cosine") {
+        "Cosine" => "cosine",
+        "Dot" => "dot",
+        "Euclid" => "euclidean",
+        other => return Err(bad_request(format!("Unsupported distance: {other}"))),
+    };
+    let quantization = match &req.quantization_config {
+        None | Some(Value::Null) => "none",
+        Some(config) if config.get("scalar").is_some() => "int8",
+        Some(config) if config.get("binary").is_some() => "binary",
+        Some(_) => return Err(bad_request("Unsupported quantization_config")),
+    };
+    match collection_stats(state, name).await {
+        Ok(_) => {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Wrong input: Collection `{name}` already exists!"),
+            ))
+        }
+        Err((StatusCode::NOT_FOUND, _)) => {}
+        Err(err) => return Err(err),
+    }
+    vector_create_collection(
+        state,
+        CreateVectorCollectionRequest {
+            name: name.to_string(),
+            dimension: u32::try_from(size).unwrap_or(u32::MAX),
+            distance: distance.to_string(),
+            quantization: Some(quantization.to_string()),
+            embedding_model: None,
+        },
+    )
+    .await
+    .map_err(store_error)?;
+    Ok(Value::Bool(true))
+}
+
+pub(super) async fn qdrant_delete_collection(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = match vector_drop_collection(&state, &name).await {
+        Ok(()) => Ok(Value::Bool(true)),
+        Err(err) if err.status == StatusCode::NOT_FOUND => Ok(Value::Bool(false)),
+        Err(err) => Err(store_error(err)),
+    };
+    reply(started, result)
+}
+
+fn completed() -> Value {
+    json!({ "operation_id": 0, "status": "completed" })
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantCreateIndexRequest {
+    field_name: String,
+    field_schema: Value,
+}
+
+pub(super) async fn qdrant_create_index(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantCreateIndexRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let schema = match &req.field_schema {
+        Value::Object(o) => o.get("type").cloned().unwrap_or(Value::Null),
+        other => other.clone(),
+    };
+    let result = vector_create_payload_index(
+        &state,
+        PayloadIndexRequest {
+            collection: name,
+            field: req.field_name,
+            schema: schema.as_str().map(str::to_string),
+        },
+    )
+    .await
+    .map(|_| completed())
+    .map_err(store_error);
+    reply(started, result)
+}
+
+pub(super) async fn qdrant_delete_index(
+    State(state): State<AppState>,
+    Path((name, field)): Path<(String, String)>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = vector_delete_payload_index(
+        &state,
+        PayloadIndexRequest {
+            collection: name,
+            field,
+            schema: None,
+        },
+    )
+    .await
+    .map(|_| completed())
+    .map_err(store_error);
+    reply(started, result)
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantUpsertRequest {
+    points: Option<Vec<Value>>,
+    batch: Option<QdrantBatch>,
+}
+
+#[derive(Deserialize)]
+struct QdrantBatch {
+    ids: Vec<Value>,
+    vectors: Vec<Value>,
+    payloads: Option<Vec<Value>>,
+}
+
+pub(super) async fn qdrant_upsert_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantUpsertRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    reply(started, upsert_points(&state, &name, req).await)
+}
+
+async fn upsert_points(state: &AppState, name: &str, req: QdrantUpsertRequest) -> QdrantResult {
+    let records = match (req.points, req.batch) {
+        (Some(points), None) => points
+            .into_iter()
+            .map(|p| (p["id"].clone(), p["vector"].clone(), p["payload"].clone()))
+            .collect::<Vec<_>>(),
+        (None, Some(batch)) => {
+            let payloads = batch.payloads.unwrap_or_default();
+            if batch.ids.len() != batch.vectors.len()
+                || (!payloads.is_empty() && payloads.len() != batch.ids.len())
+            {
+                return Err(bad_request(
+                    "Batch ids, vectors and payloads must have the same length",
+                ));
+            }
+            batch
+                .ids
+                .into_iter()
+                .zip(batch.vectors)
+                .enumerate()
+                .map(|(i, (id, vector))| {
+                    (id, vector, payloads.get(i).cloned().unwrap_or(Value::Null))
+                })
+                .collect()
+        }
+        _ => return Err(bad_request("Expected either `points` or `batch`")),
+    };
+    let mut points = Vec::with_capacity(records.len());
+    for (id, vector, payload) in records {
+        let id = point_id(&id).ok_or_else(|| bad_request(format!("Invalid point id: {id}")))?;
+        let vector = unnamed_vector(&vector)
+            .cloned()
+            .ok_or_else(|| bad_request("Named vectors are not supported"))?;
+        points.push(VectorPoint {
+            id,
+            vector: parse(vector)?,
+            payload,
+            batch_id: None,
+        });
+    }
+    vector_upsert_points(
+        state,
+        UpsertVectorPointsRequest {
+            collection: name.to_string(),
+            points,
+            batch_id: None,
+            embedding_model: None,
+        },
+    )
+    .await
+    .map_err(store_error)?;
+    Ok(completed())
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantSearchRequest {
+    #[serde(alias = "query")]
+    vector: Option<Value>,
+    filter: Option<Value>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    with_payload: Option<Value>,
+    with_vector: Option<Value>,
+    score_threshold: Option<f32>,
+    params: Option<QdrantSearchParams>,
+}
+
+#[derive(Deserialize)]
+struct QdrantSearchParams {
+    hnsw_ef: Option<u32>,
+    #[serde(default)]
+    exact: bool,
+}
+
+pub(super) async fn qdrant_search_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantSearchRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    reply(started, search_points(&state, &name, req).await)
+}
+
+pub(super) async fn qdrant_query_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantSearchRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = search_points(&state, &name, req)
+        .await
+        .map(|points| json!({ "points": points }));
+    reply(started, result)
+}
+
+async fn search_points(state: &AppState, name: &str, req: QdrantSearchRequest) -> QdrantResult {
+    let query = req.vector.as_ref().map(|v| v.get("nearest").unwrap_or(v));
+    let Some(vector) = query.and_then(unnamed_vector) else {
+        return Err(bad_request("A dense query vector is required"));
+    };
+    let limit = req.limit.unwrap_or(10);
+    let offset = req.offset.unwrap_or(0);
+    let params = req.params.as_ref();
+    let found = vector_search(
+        state,
+        SearchVectorRequest {
+            collection: name.to_string(),
+            vector: parse(vector.clone())?,
+            top_k: Some(u32::try_from(limit + offset).unwrap_or(u32::MAX)),
+            filter: parse_filter(req.filter.clone())?,
+            exact: params.is_some_and(|p| p.exact),
+            ef: params.and_then(|p| p.hnsw_ef),
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(store_error)?;
+    let lower_is_better = match req.score_threshold {
+        Some(_) => vector_collection_distance(state, name).await == Some(Distance::Euclidean),
+        None => false,
+    };
+    let hits = found
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .filter(|hit| {
+            req.score_threshold.is_none_or(|t| {
+                if lower_is_better {
+                    hit.score <= t
+                } else {
+                    hit.score >= t
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut vectors = HashMap::<String, Vec<f32>>::new();
+    let with_vector = wants(req.with_vector.as_ref(), false);
+    if with_vector && !hits.is_empty() {
+        let got = vector_get_points(
+            state,
+            GetVectorPointsRequest {
+                collection: name.to_string(),
+                ids: hits.iter().map(|hit| hit.id.clone()).collect(),
+                with_vector: true,
+            },
+        )
+        .await
+        .map_err(store_error)?;
+        for point in got {
+            if let Some(vector) = point.vector {
+                vectors.insert(point.id, vector);
+            }
+        }
+    }
+    Ok(Value::Array(
+        hits.into_iter()
+            .map(|hit| {
+                json!({
+                    "id": qdrant_id(&hit.id),
+                    "version": 0,
+                    "score": hit.score,
+                    "payload": select_payload(req.with_payload.as_ref(), false, &hit.payload),
+                    "vector": vectors.remove(&hit.id),
+                })
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantScrollRequest {
+    filter: Option<Value>,
+    limit: Option<usize>,
+    offset: Option<Value>,
+    with_payload: Option<Value>,
+    with_vector: Option<Value>,
+}
+
+pub(super) async fn qdrant_scroll_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantScrollRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    reply(started, scroll_points(&state, &name, req).await)
+}
+
+async fn scroll_points(state: &AppState, name: &str, req: QdrantScrollRequest) -> QdrantResult {
+    let limit = req.limit.unwrap_or(10).clamp(1, QDRANT_MAX_SCROLL_LIMIT);
+    let with_vector = wants(req.with_vector.as_ref(), false);
+    let offset = match req.offset.as_ref().filter(|v| !v.is_null()) {
+        Some(offset) => Some(
+            point_id(offset).ok_or_else(|| bad_request(format!("Invalid point id: {offset}")))?,
+        ),
+        None => None,
+    };
+
+    let mut points = Vec::new();
+    if let Some(offset) = &offset {
+        let mut must = vec![json!({ "hasId": [offset] })];
+        must.extend(req.filter.clone().filter(|f| !f.is_null()));
+        let first = vector_scroll_points(
+            state,
+            ScrollVectorPointsRequest {
+                collection: name.to_string(),
+                filter: Some(parse(json!({ "must": must }))?),
+                limit: Some(1),
+                cursor: None,
+                with_vector,
+            },
+        )
+        .await
+        .map_err(store_error)?;
+        points.extend(first.points);
+    }
+    let rest = vector_scroll_points(
+        state,
+        ScrollVectorPointsRequest {
+            collection: name.to_string(),
+            filter: parse_filter(req.filter)?,
+            limit: Some((limit + 1 - points.len()) as u32),
+            cursor: offset,
+            with_vector,
+        },
+    )
+    .await
+    .map_err(store_error)?;
+    points.extend(rest.points);
+
+    let next_page_offset = points.get(limit).map(|p| qdrant_id(&p.id));
+    points.truncate(limit);
+    let points = points
+        .iter()
+        .map(|p| qdrant_point(p, req.with_payload.as_ref(), true, with_vector))
+        .collect::<Vec<_>>();
+    Ok(json!({ "points": points, "next_page_offset": next_page_offset }))
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantRetrieveRequest {
+    ids: Vec<Value>,
+    with_payload: Option<Value>,
+    with_vector: Option<Value>,
+}
+
+pub(super) async fn qdrant_retrieve_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantRetrieveRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = retrieve_points(
+        &state,
+        &name,
+        &req.ids,
+        req.with_payload.as_ref(),
+        req.with_vector.as_ref(),
+    )
+    .await
+    .map(Value::Array);
+    reply(started, result)
+}
+
+async fn retrieve_points(
+    state: &AppState,
+    name: &str,
+    ids: &[Value],
+    with_payload: Option<&Value>,
+    with_vector: Option<&Value>,
+) -> Result<Vec<Value>, (StatusCode, String)> {
+    let with_vector = wants(with_vector, false);
+    let got = vector_get_points(
+        state,
+        GetVectorPointsRequest {
+            collection: name.to_string(),
+            ids: point_ids(ids)?,
+            with_vector,
+        },
+    )
+    .await
+    .map_err(store_error)?;
+    Ok(got
+        .iter()
+        .map(|p| qdrant_point(p, with_payload, true, with_vector))
+        .collect())
+}
+
+pub(super) async fn qdrant_get_point(
+    State(state): State<AppState>,
+    Path((name, id)): Path<(String, String)>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let result = retrieve_points(
+        &state,
+        &name,
+        &[Value::String(id.clone())],
+        None,
+        Some(&Value::Bool(true)),
+    )
+    .await
+    .and_then(|mut points| {
+        points.pop().ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Not found: No point with id {id} found"),
+            )
+        })
+    });
+    reply(started, result)
+}
+
+#[derive(Deserialize)]
+pub(super) struct QdrantDeleteRequest {
+    points: Option<Vec<Value>>,
+    filter: Option<Value>,
+}
+
+pub(super) async fn qdrant_delete_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<QdrantDeleteRequest>,
+) -> axum::response::Response {
+    let started = Instant::now();
+    let filter = match (&req.points, req.filter) {
+        (Some(ids), None) => {
+            point_ids(ids).and_then(|ids| parse(json!({ "must": [{ "hasId": ids }] })))
+        }
+        (None, Some(filter)) => parse(filter),
+        _ => Err(bad_request("Expected either `points` or `filter`")),
+    };
+    let result = match filter {
+        Ok(filter) => vector_delete_points(
+            &state,
+            DeleteVectorPointsRequest {
+                collection: name,
+                filter: Some(filter),
+                batch_id: None,
+            },
+        )
+        .await
+        .map(|_| completed())
+        .map_err(store_error),
+        Err(err) => Err(err),
+    };
+    reply(started, result)
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt as _;
+
+use crate::{vector_index::Reader, vector_store::VectorPoint};
+
+const SEGMENT_MAGIC: &[u8; 8] = b"CEVSEG01";
+const OP_UPSERT: u8 = 1;
+const OP_DELETE: u8 = 2;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SegmentManifest {
+    pub(crate) generation: u64,
+}
+
+pub(crate) struct SegmentFiles {
+    dir: PathBuf,
+}
+
+impl SegmentFiles {
+    pub(crate) fn new(base: &Path, collection: &str) -> Self {
+        Self {
+            dir: base.join("segments").join(collection),
+        }
+    }
+
+    fn manifest(&self) -> PathBuf {
+        self.dir.join("manifest.json")
+    }
+
+    pub(crate) fn vectors(&self, generation: u64) -> PathBuf {
+        self.dir.join(format!("vectors-{generation}.bin"))
+    }
+
+    pub(crate) fn payloads(&self, generation: u64) -> PathBuf {
+        self.dir.join(format!("payloads-{generation}.bin"))
+    }
+
+    pub(crate) fn index(&self) -> PathBuf {
+        self.dir.join("index.hnsw")
+    }
+
+    pub(crate) async fn read_manifest(&self) -> Option<SegmentManifest> {
+        let text = tokio::fs::read_to_string(self.manifest()).await.ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub(crate) async fn write_generation(
+        &self,
+        generation: u64,
+        vectors: &[u8],
+        payloads: &[u8],
+    ) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.vectors(generation), vectors).await?;
+        tokio::fs::write(self.payloads(generation), payloads).await?;
+        let tmp = self.dir.join("manifest.json.tmp");
+        tokio::fs::write(
+            &tmp,
+            serde_json::to_string_pretty(&SegmentManifest { generation })?,
+        )
+        .await?;
+        tokio::fs::rename(tmp, self.manifest()).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn exists(&self) -> bool {
+        tokio::fs::metadata(&self.dir).await.is_ok()
+    }
+
+    pub(crate) async fn remove_all(&self) {
+        let _ = tokio::fs::remove_dir_all(&self.dir).await;
+    }
+
+    /// A scratch directory next to this collection's segments. Collection
+    /// names cannot contain `.`, so the sibling never collides with one.
+    pub(crate) fn sibling(&self, suffix: &str) -> Self {
+        let name = self
+            .dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            dir: self.dir.with_file_name(format!(".{name}.{suffix}")),
+        }
+    }
+
+    /// Moves `staged` into this collection's place. The current segments are
+    /// set aside rather than deleted; the caller drops them once the swap is
+    /// committed, or hands them back to `reinstate`.
+    pub(crate) async fn swap_in(&self, staged: &SegmentFiles) -> std::io::Result<SegmentFiles> {
+        let previous = self.sibling("previous");
+        previous.remove_all().await;
+        match tokio::fs::rename(&self.dir, &previous.dir).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        if let Err(err) = tokio::fs::rename(&staged.dir, &self.dir).await {
+            self.reinstate(&previous).await;
+            return Err(err);
+        }
+        Ok(previous)
+    }
+
+    pub(crate) async fn reinstate(&self, previous: &SegmentFiles) {
+        if tokio::fs::metadata(&previous.dir).await.is_ok() {
+            self.remove_all().await;
+            let _ = tokio::fs::rename(&previous.dir, &self.dir).await;
+        }
+    }
+
+    pub(crate) async fn remove_generation(&self, generation: u64) {
+        let _ = tokio::fs::remove_file(self.vectors(generation)).await;
+        let _ = tokio::fs::remove_file(self.payloads(generation)).await;
+    }
+
+    pub(crate) async fn append(&self, generation: u64, batch: &SegmentBatch) -> anyhow::Result<()> {
+        if !batch.payloads.is_empty() {
+            append_file(&self.payloads(generation), &batch.payloads).await?;
+        }
+        append_file(&self.vectors(generation), &batch.vectors).await
+    }
+
+    pub(crate) async fn truncate_vectors(&self, generation: u64, len: u64) -> anyhow::Result<()> {
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(self.vectors(generation))
+            .await?;
+        file.set_len(len).await?;
+        Ok(())
+    }
+}
+
+async fn append_file(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(bytes).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PayloadEntry {
+    #[serde(default)]
+    payload: serde_json::Value,
+    #[serde(default)]
+    batch_id: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct SegmentBatch {
+    pub(crate) vectors: Vec<u8>,
+    pub(crate) payloads: Vec<u8>,
+    pub(crate) locators: Vec<u64>,
+}
+
+pub(crate) fn empty_segment() -> Vec<u8> {
+    SEGMENT_MAGIC.to_vec()
+}
+
+pub(crate) fn encode_upserts<'a>(
+    points: impl IntoIterator<Item = &'a VectorPoint>,
+    vectors_base: u64,
+    payloads_base: u64,
+) -> anyhow::Result<SegmentBatch> {
+    let mut batch = SegmentBatch::default();
+    for p in points {
+        let payload = serde_json::to_vec(&PayloadEntry {
+            payload: p.payload.clone(),
+            batch_id: p.batch_id.clone(),
+        })?;
+        let payload_offset = payloads_base + batch.payloads.len() as u64;
+        batch.payloads.extend_from_slice(&payload);
+
+        batch
+            .locators
+            .push(vectors_base + batch.vectors.len() as u64);
+        batch.vectors.push(OP_UPSERT);
+        batch
+            .vectors
+            .extend_from_slice(&(p.id.len() as u32).to_le_bytes());
+        batch.vectors.extend_from_slice(p.id.as_bytes());
+        for x in &p.vector {
+            batch.vectors.extend_from_slice(&x.to_le_bytes());
+        }
+        batch
+            .vectors
+            .extend_from_slice(&payload_offset.to_le_bytes());
+        batch
+            .vectors
+            .extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    }
+    Ok(batch)
+}
+
+pub(crate) fn upsert_record_len(id_len: usize, dim: usize) -> usize {
+    1 + 4 + id_len + dim * 4 + 8 + 4
+}
+
+pub(crate) fn encode_deletes<'a>(ids: impl IntoIterator<Item = &'a String>) -> SegmentBatch {
+    let mut batch = SegmentBatch::default();
+    for id in ids {
+        batch.vectors.push(OP_DELETE);
+        batch
+            .vectors
+            .extend_from_slice(&(id.len() as u32).to_le_bytes());
+        batch.vectors.extend_from_slice(id.as_bytes());
+    }
+    batch
+}
+
+pub(crate) struct SegmentRecord {
+    pub(crate) offset: u64,
+    pub(crate) id: String,
+    pub(crate) upsert: Option<(Vec<f32>, u64, u32)>,
+}
+
+fn read_record(r: &mut Reader<'_>, dim: usize) -> Option<SegmentRecord> {
+    let offset = r.pos() as u64;
+    let op = r.u8()?;
+    let len = r.u32()? as usize;
+    let id = String::from_utf8(r.take(len)?.to_vec()).ok()?;
+    let upsert = match op {
+        OP_UPSERT => {
+            let mut vector = Vec::with_capacity(dim);
+            for _ in 0..dim {
+                vector.push(r.f32()?);
+            }
+            Some((vector, r.u64()?, r.u32()?))
+        }
+        OP_DELETE => None,
+        _ => return None,
+    };
+    Some(SegmentRecord { offset, id, upsert })
+}
+
+pub(crate) fn records_from(
+    vectors: &[u8],
+    start: u64,
+    dim: usize,
+) -> impl Iterator<Item = SegmentRecord> + '_ {
+    let mut r = Reader::at(vectors, (start as usize).max(SEGMENT_MAGIC.len()));
+    std::iter::from_fn(move || read_record(&mut r, dim))
+}
+
+pub(crate) fn vector_at(vectors: &[u8], offset: u64, dim: usize) -> Option<Vec<f32>> {
+    let mut r = Reader::at(vectors, offset as usize);
+    read_record(&mut r, dim)?
+        .upsert
+        .map(|(vector, _, _)| vector)
+}
+
+pub(crate) struct SegmentScan {
+    pub(crate) points: HashMap<String, VectorPoint>,
+    pub(crate) locators: HashMap<String, u64>,
+    pub(crate) records: u64,
+    pub(crate) valid_len: u64,
+}
+
+pub(crate) fn scan_segment(vectors: &[u8], payloads: &[u8], dim: usize) -> Option<SegmentScan> {
+    if vectors.get(..SEGMENT_MAGIC.len())? != SEGMENT_MAGIC {
+        return None;
+    }
+    let mut scan = SegmentScan {
+        points: HashMap::new(),
+        locators: HashMap::new(),
+        records: 0,
+        valid_len: SEGMENT_MAGIC.len() as u64,
+    };
+    let mut r = Reader::at(vectors, SEGMENT_MAGIC.len());
+    while let Some(record) = read_record(&mut r, dim) {
+        scan.records += 1;
+        scan.valid_len = r.pos() as u64;
+        match record.upsert {
+            Some((vector, payload_offset, payload_len)) => {
+                let entry = payloads
+                    .get(payload_offset as usize..(payload_offset as usize + payload_len as usize))
+                    .and_then(|bytes| serde_json::from_slice::<PayloadEntry>(bytes).ok())
+                    .unwrap_or(PayloadEntry {
+                        payload: serde_json::Value::Object(serde_json::Map::new()),
+                        batch_id: None,
+                    });
+                scan.locators.insert(record.id.clone(), record.offset);
+                scan.points.insert(
+                    record.id.clone(),
+                    VectorPoint {
+                        id: record.id,
+                        vector,
+                        payload: entry.payload,
+                        batch_id: entry.batch_id,
+                    },
+                );
+            }
+            None => {
+                scan.locators.remove(&record.id);
+                scan.points.remove(&record.id);
+            }
+        }
+    }
+    Some(scan)
+}
@@ -0,0 +1,64 @@
+use std::io::{Read as _, Write as _};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"CEVSNAP1";
+const SNAPSHOT_MAX_DECODED_BYTES: u64 = 1 << 30;
+
+pub(crate) struct VectorSnapshot {
+    pub(crate) meta: Vec<u8>,
+    pub(crate) generation: u64,
+    pub(crate) vectors: Vec<u8>,
+    pub(crate) payloads: Vec<u8>,
+    pub(crate) index: Vec<u8>,
+}
+
+impl VectorSnapshot {
+    pub(crate) fn encode(&self) -> std::io::Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(SNAPSHOT_MAGIC)?;
+        encoder.write_all(&self.generation.to_le_bytes())?;
+        for section in [&self.meta, &self.vectors, &self.payloads, &self.index] {
+            encoder.write_all(&(section.len() as u64).to_le_bytes())?;
+            encoder.write_all(section)?;
+        }
+        encoder.finish()
+    }
+
+    /// Inflates section by section, so memory use is bounded by the decoded
+    /// sections themselves (at most `SNAPSHOT_MAX_DECODED_BYTES`) rather than
+    /// a second copy of the whole archive.
+    pub(crate) fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut input = GzDecoder::new(bytes).take(SNAPSHOT_MAX_DECODED_BYTES);
+        let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+        input.read_exact(&mut magic).ok()?;
+        if &magic != SNAPSHOT_MAGIC {
+            return None;
+        }
+        let generation = read_u64(&mut input)?;
+        let mut section = || {
+            let len = read_u64(&mut input)?;
+            if len > input.limit() {
+                return None;
+            }
+            let mut out = Vec::new();
+            (&mut input).take(len).read_to_end(&mut out).ok()?;
+            (out.len() as u64 == len).then_some(out)
+        };
+        let snapshot = Self {
+            meta: section()?,
+            generation,
+            vectors: section()?,
+            payloads: section()?,
+            index: section()?,
+        };
+        let mut trailing = [0u8; 1];
+        (input.into_inner().read(&mut trailing).ok()? == 0).then_some(snapshot)
+    }
+}
+
+fn read_u64(input: &mut impl std::io::Read) -> Option<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf).ok()?;
+    Some(u64::from_le_bytes(buf))
+}
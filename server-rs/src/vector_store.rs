@@ -1,33 +1,125 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
 
-use crate::{is_safe_identifier, now_ms, AppState};
+use crate::{
+    is_safe_identifier, now_ms,
+    vector_filter::{matches_filter, PayloadIndex, PayloadIndexSpec, PayloadSchema, VectorFilter},
+    vector_hybrid::{
+        fuse, mmr, Fusion, MmrOptions, Ranked, SearchMode, DEFAULT_ALPHA, DEFAULT_MMR_LAMBDA,
+        DEFAULT_RRF_K,
+    },
+    vector_index::{Distance, HnswIndex, Quantization, DEFAULT_EF_SEARCH},
+    vector_segments::{
+        empty_segment, encode_deletes, encode_upserts, records_from, scan_segment,
+        upsert_record_len, vector_at, SegmentFiles,
+    },
+    vector_snapshot::VectorSnapshot,
+    AppState,
+};
+
+const INDEX_CHECKPOINT_BYTES: u64 = 16 << 20;
+const COMPACTION_MIN_DEAD_RECORDS: u64 = 256;
+const FILTERED_SCAN_THRESHOLD: usize = 5_000;
+pub(super) const VECTOR_SNAPSHOT_MAX_BYTES: usize = 1 << 30;
+
+pub(super) type VectorCollectionCache = Arc<Mutex<HashMap<String, Arc<RwLock<LoadedCollection>>>>>;
+
+pub(super) struct LoadedCollection {
+    meta: VectorCollection,
+    points: HashMap<String, VectorPoint>,
+    index: HnswIndex,
+    payload_index: PayloadIndex,
+    generation: u64,
+    vectors_len: u64,
+    payloads_len: u64,
+    records: u64,
+    checkpoint: u64,
+}
+
+impl LoadedCollection {
+    fn dead_records(&self) -> u64 {
+        self.records.saturating_sub(self.points.len() as u64)
+    }
+
+    fn needs_compaction(&self) -> bool {
+        let dead = self.dead_records();
+        dead >= COMPACTION_MIN_DEAD_RECORDS && dead > self.points.len() as u64
+    }
+
+    fn indexed_candidates(&self, filter: Option<&VectorFilter>) -> Option<HashSet<String>> {
+        filter.and_then(|f| self.payload_index.candidates(f))
+    }
+
+    fn matching_points<'a>(
+        &'a self,
+        filter: Option<&VectorFilter>,
+        candidates: Option<&HashSet<String>>,
+    ) -> Vec<&'a VectorPoint> {
+        match candidates {
+            Some(ids) => ids
+                .iter()
+                .filter_map(|id| self.points.get(id))
+                .filter(|p| matches_filter(&p.id, &p.payload, filter))
+                .collect(),
+            None => self
+                .points
+                .values()
+                .filter(|p| matches_filter(&p.id, &p.payload, filter))
+                .collect(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct VectorCollection {
-    name: String,
-    dimension: u32,
-    distance: String,
+pub(super) struct VectorCollection {
+    pub(super) name: String,
+    pub(super) dimension: u32,
+    pub(super) distance: Distance,
+    #[serde(default, skip_serializing_if = "Quantization::is_none")]
+    pub(super) quantization: Quantization,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    payload_indexes: Vec<PayloadIndexSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    embedding_model: Option<String>,
     created_at: String,
 }
 
+impl VectorCollection {
+    /// Once a collection has recorded an embedding model, every upsert must
+    /// name that same model; vectors from an unnamed model are rejected.
+    fn embedding_model_error(&self, model: Option<&str>) -> Option<&'static str> {
+        let model = model.map(str::trim).filter(|m| !m.is_empty());
+        match (self.embedding_model.as_deref(), model) {
+            (Some(_), None) => Some("embedding_model_required"),
+            (Some(a), Some(b)) if a != b => Some("embedding_model_mismatch"),
+            _ => None,
+        }
+    }
+}
+
 fn vector_base_dir(state: &AppState) -> PathBuf {
     state.data_dir.join("vector")
 }
 
-pub(super) async fn list_vector_collections(
-    State(state): State<AppState>,
-) -> axum::response::Response {
-    let dir = vector_base_dir(&state).join("collections");
+pub(super) async fn vector_collections(state: &AppState) -> Vec<VectorCollection> {
+    let dir = vector_base_dir(state).join("collections");
     let mut out = Vec::<VectorCollection>::new();
-    let mut rd = match tokio::fs::read_dir(&dir).await {
-        Ok(rd) => rd,
-        Err(_) => {
-            return (StatusCode::OK, Json(out)).into_response();
-        }
+    let Ok(mut rd) = tokio::fs::read_dir(&dir).await else {
+        return out;
     };
     while let Ok(Some(entry)) = rd.next_entry().await {
         let path = entry.path();
@@ -41,91 +133,217 @@ pub(super) async fn list_vector_collections(
         }
     }
     out.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-    (StatusCode::OK, Json(out)).into_response()
+    out
+}
+
+pub(super) async fn list_vector_collections(
+    State(state): State<AppState>,
+) -> axum::response::Response {
+    (StatusCode::OK, Json(vector_collections(&state).await)).into_response()
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(super) struct CreateVectorCollectionRequest {
-    name: String,
-    dimension: u32,
-    distance: String,
+    pub(super) name: String,
+    pub(super) dimension: u32,
+    pub(super) distance: String,
+    pub(super) quantization: Option<String>,
+    pub(super) embedding_model: Option<String>,
 }
 
 pub(super) async fn create_vector_collection(
     State(state): State<AppState>,
     Json(req): Json<CreateVectorCollectionRequest>,
 ) -> axum::response::Response {
+    vector_reply(vector_create_collection(&state, req).await)
+}
+
+pub(super) async fn vector_create_collection(
+    state: &AppState,
+    req: CreateVectorCollectionRequest,
+) -> Result<VectorCollection, VectorError> {
     if !is_safe_identifier(&req.name) {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "invalid_collection" })),
-        )
-            .into_response();
+        return Err(VectorError::invalid_collection());
     }
     if req.dimension == 0 || req.dimension > 4096 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(
-                serde_json::json!({ "error": "validation_failed", "message": "invalid_dimension" }),
-            ),
-        )
-            .into_response();
-    }
-    let distance = req.distance.trim().to_ascii_lowercase();
-    if distance != "cosine" {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(
-                serde_json::json!({ "error": "validation_failed", "message": "unsupported_distance" }),
-            ),
-        )
-            .into_response();
+        return Err(VectorError::validation("invalid_dimension"));
     }
+    let distance = Distance::parse(&req.distance)
+        .ok_or_else(|| VectorError::validation("unsupported_distance"))?;
+    let quantization = match Quantization::parse(req.quantization.as_deref().unwrap_or("none")) {
+        Some(q) if q.supports(distance) => q,
+        _ => return Err(VectorError::validation("unsupported_quantization")),
+    };
 
-    let base = vector_base_dir(&state);
+    let base = vector_base_dir(state);
     let col_dir = base.join("collections");
-    let points_dir = base.join("points");
-    if tokio::fs::create_dir_all(&col_dir).await.is_err()
-        || tokio::fs::create_dir_all(&points_dir).await.is_err()
-    {
-        return (
+    if tokio::fs::create_dir_all(&col_dir).await.is_err() {
+        return Err(VectorError::new(
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": "write_failed" })),
-        )
-            .into_response();
+            "write_failed",
+        ));
     }
 
     let meta = VectorCollection {
         name: req.name.clone(),
         dimension: req.dimension,
-        distance: distance.to_string(),
+        distance,
+        quantization,
+        payload_indexes: Vec::new(),
+        embedding_model: req
+            .embedding_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string),
         created_at: now_ms().to_string(),
     };
-    let path = col_dir.join(format!("{}.json", req.name));
-    let text = match serde_json::to_string_pretty(&meta) {
-        Ok(t) => t,
-        Err(err) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": "write_failed", "message": err.to_string() })),
-            )
-                .into_response();
-        }
+    let (path, legacy_points, _) = collection_paths(state, &req.name);
+    let mut cache = state.vector_collections.lock().await;
+    let exists = tokio::fs::metadata(&path).await.is_ok()
+        || tokio::fs::metadata(&legacy_points).await.is_ok()
+        || SegmentFiles::new(&base, &req.name).exists().await;
+    if exists {
+        return Err(VectorError::collection(
+            &req.name,
+            (StatusCode::CONFLICT, "already_exists"),
+        ));
+    }
+    let text = serde_json::to_string_pretty(&meta).map_err(VectorError::write_failed)?;
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(VectorError::write_failed)?;
+    cache.remove(&req.name);
+    Ok(meta)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct PayloadIndexRequest {
+    pub(super) collection: String,
+    pub(super) field: String,
+    pub(super) schema: Option<String>,
+}
+
+pub(super) async fn create_payload_index(
+    State(state): State<AppState>,
+    Json(req): Json<PayloadIndexRequest>,
+) -> axum::response::Response {
+    vector_reply(vector_create_payload_index(&state, req).await)
+}
+
+pub(super) async fn vector_create_payload_index(
+    state: &AppState,
+    req: PayloadIndexRequest,
+) -> Result<VectorCollection, VectorError> {
+    let Some(schema) = req.schema.as_deref().and_then(PayloadSchema::parse) else {
+        return Err(VectorError::validation("unsupported_schema"));
     };
-    if let Err(err) = tokio::fs::write(&path, text).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": "write_failed", "message": err.to_string() })),
-        )
-            .into_response();
+    update_payload_indexes(state, &req, |specs| {
+        specs.retain(|spec| spec.field != req.field);
+        specs.push(PayloadIndexSpec {
+            field: req.field.clone(),
+            schema,
+        });
+    })
+    .await
+}
+
+pub(super) async fn delete_payload_index(
+    State(state): State<AppState>,
+    Json(req): Json<PayloadIndexRequest>,
+) -> axum::response::Response {
+    vector_reply(vector_delete_payload_index(&state, req).await)
+}
+
+pub(super) async fn vector_delete_payload_index(
+    state: &AppState,
+    req: PayloadIndexRequest,
+) -> Result<VectorCollection, VectorError> {
+    update_payload_indexes(state, &req, |specs| {
+        specs.retain(|spec| spec.field != req.field);
+    })
+    .await
+}
+
+async fn update_payload_indexes(
+    state: &AppState,
+    req: &PayloadIndexRequest,
+    update: impl FnOnce(&mut Vec<PayloadIndexSpec>),
+) -> Result<VectorCollection, VectorError> {
+    if !is_safe_identifier(&req.collection) {
+        return Err(VectorError::invalid_collection());
+    }
+    if req.field.trim().is_empty() || req.field.split('.').any(|s| s.trim().is_empty()) {
+        return Err(VectorError::validation("invalid_field"));
+    }
+    let loaded = open_collection(state, &req.collection)
+        .await
+        .map_err(|err| VectorError::collection(&req.collection, err))?;
+    let mut loaded = loaded.write().await;
+    let mut meta = loaded.meta.clone();
+    update(&mut meta.payload_indexes);
+    meta.payload_indexes.sort_by(|a, b| a.field.cmp(&b.field));
+
+    write_collection_meta(state, &meta)
+        .await
+        .map_err(VectorError::write_failed)?;
+    loaded.payload_index = PayloadIndex::build(
+        &meta.payload_indexes,
+        loaded.points.values().map(|p| (p.id.as_str(), &p.payload)),
+    );
+    loaded.meta = meta;
+    Ok(loaded.meta.clone())
+}
+
+async fn write_collection_meta(state: &AppState, meta: &VectorCollection) -> anyhow::Result<()> {
+    let (col_path, _, _) = collection_paths(state, &meta.name);
+    let text = serde_json::to_string_pretty(meta)?;
+    tokio::fs::write(&col_path, text).await?;
+    Ok(())
+}
+
+async fn record_embedding_model(
+    state: &AppState,
+    loaded: &mut LoadedCollection,
+    model: Option<&str>,
+) -> anyhow::Result<()> {
+    let Some(model) = model.map(str::trim).filter(|m| !m.is_empty()) else {
+        return Ok(());
+    };
+    if loaded.meta.embedding_model.is_some() {
+        return Ok(());
     }
-    let points_path = points_dir.join(format!("{}.jsonl", req.name));
-    if tokio::fs::metadata(&points_path).await.is_err() {
-        let _ = tokio::fs::write(&points_path, b"").await;
+    let mut meta = loaded.meta.clone();
+    meta.embedding_model = Some(model.to_string());
+    write_collection_meta(state, &meta).await?;
+    loaded.meta = meta;
+    Ok(())
+}
+
+pub(super) async fn vector_collection_embedding_model(
+    state: &AppState,
+    collection: &str,
+) -> Option<String> {
+    if !is_safe_identifier(collection) {
+        return None;
     }
+    let loaded = open_collection(state, collection).await.ok()?;
+    let model = loaded.read().await.meta.embedding_model.clone();
+    model
+}
 
-    (StatusCode::OK, Json(meta)).into_response()
+pub(super) async fn vector_collection_distance(
+    state: &AppState,
+    collection: &str,
+) -> Option<Distance> {
+    if !is_safe_identifier(collection) {
+        return None;
+    }
+    let loaded = open_collection(state, collection).await.ok()?;
+    let distance = loaded.read().await.meta.distance;
+    Some(distance)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,322 +359,1323 @@ pub(super) struct VectorPoint {
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(super) struct UpsertVectorPointsRequest {
-    collection: String,
-    points: Vec<VectorPoint>,
-    batch_id: Option<String>,
+    pub(super) collection: String,
+    pub(super) points: Vec<VectorPoint>,
+    pub(super) batch_id: Option<String>,
+    pub(super) embedding_model: Option<String>,
 }
 
-pub(super) async fn upsert_vector_points(
-    State(state): State<AppState>,
-    Json(req): Json<UpsertVectorPointsRequest>,
-) -> axum::response::Response {
-    if !is_safe_identifier(&req.collection) {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "invalid_collection" })),
-        )
-            .into_response();
+fn collection_paths(state: &AppState, collection: &str) -> (PathBuf, PathBuf, PathBuf) {
+    let base = vector_base_dir(state);
+    (
+        base.join("collections").join(format!("{collection}.json")),
+        base.join("points").join(format!("{collection}.jsonl")),
+        base.join("points").join(format!("{collection}.hnsw")),
+    )
+}
+
+/// A failed vector store operation. The native routes render it as
+/// `{ "error", "message", ... }`; the Qdrant routes fold it into `status.error`.
+#[derive(Debug)]
+pub(super) struct VectorError {
+    pub(super) status: StatusCode,
+    error: &'static str,
+    message: Option<String>,
+    details: serde_json::Map<String, serde_json::Value>,
+}
+
+impl VectorError {
+    fn new(status: StatusCode, error: &'static str) -> Self {
+        Self {
+            status,
+            error,
+            message: None,
+            details: serde_json::Map::new(),
+        }
     }
-    if req.points.is_empty() || req.points.len() > 10_000 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "limit_exceeded", "message": "too_many_points" })),
-        )
-            .into_response();
+
+    fn invalid_collection() -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "invalid_collection")
     }
 
-    let base = vector_base_dir(&state);
-    let col_path = base
-        .join("collections")
-        .join(format!("{}.json", req.collection));
-    let meta_text = match tokio::fs::read_to_string(&col_path).await {
-        Ok(t) => t,
-        Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({ "error": "not_found", "collection": req.collection })),
-            )
-                .into_response();
+    fn validation(message: &str) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "validation_failed").message(message)
+    }
+
+    fn collection(collection: &str, (status, error): (StatusCode, &'static str)) -> Self {
+        Self::new(status, error).detail("collection", collection)
+    }
+
+    fn write_failed(err: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "write_failed").message(err)
+    }
+
+    fn read_failed(err: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "read_failed").message(err)
+    }
+
+    fn message(mut self, message: impl std::fmt::Display) -> Self {
+        self.message = Some(message.to_string());
+        self
+    }
+
+    fn detail(mut self, key: &str, value: impl Serialize) -> Self {
+        self.details.insert(
+            key.to_string(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+        self
+    }
+}
+
+impl std::fmt::Display for VectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {message}", self.error),
+            None => f.write_str(self.error),
         }
-    };
-    let meta: VectorCollection = match serde_json::from_str(&meta_text) {
-        Ok(m) => m,
-        Err(_) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": "parse_failed" })),
-            )
-                .into_response();
+    }
+}
+
+impl IntoResponse for VectorError {
+    fn into_response(self) -> axum::response::Response {
+        let mut body = serde_json::Map::new();
+        body.insert("error".to_string(), self.error.into());
+        if let Some(message) = self.message {
+            body.insert("message".to_string(), message.into());
         }
-    };
+        body.extend(self.details);
+        (self.status, Json(serde_json::Value::Object(body))).into_response()
+    }
+}
 
-    if req
-        .points
-        .iter()
-        .any(|p| p.id.trim().is_empty() || p.vector.len() != meta.dimension as usize)
-    {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "validation_failed", "message": "dimension_mismatch" })),
-        )
-            .into_response();
+fn vector_reply<T: Serialize>(result: Result<T, VectorError>) -> axum::response::Response {
+    match result {
+        Ok(value) => (StatusCode::OK, Json(value)).into_response(),
+        Err(err) => err.into_response(),
     }
+}
+
+fn collection_error(collection: &str, err: (StatusCode, &'static str)) -> axum::response::Response {
+    VectorError::collection(collection, err).into_response()
+}
 
-    let points_path = base
-        .join("points")
-        .join(format!("{}.jsonl", req.collection));
-    let existing = tokio::fs::read_to_string(&points_path)
+async fn migrate_legacy_points(
+    state: &AppState,
+    collection: &str,
+    files: &SegmentFiles,
+    dim: usize,
+) -> anyhow::Result<u64> {
+    let (_, legacy_points, legacy_index) = collection_paths(state, collection);
+    let text = tokio::fs::read_to_string(&legacy_points)
         .await
         .unwrap_or_default();
-    let mut map = HashMap::<String, VectorPoint>::new();
-    for line in existing.lines() {
+    let mut points = HashMap::<String, VectorPoint>::new();
+    for line in text.lines() {
         if line.trim().is_empty() {
             continue;
         }
         if let Ok(p) = serde_json::from_str::<VectorPoint>(line) {
-            map.insert(p.id.clone(), p);
+            if p.vector.len() == dim {
+                points.insert(p.id.clone(), p);
+            }
         }
     }
-    let batch_id = req.batch_id.clone();
-    for mut p in req.points {
-        if p.payload.is_null() {
-            p.payload = serde_json::Value::Object(serde_json::Map::new());
-        }
-        if p.batch_id.is_none() {
-            p.batch_id = batch_id.clone();
-        }
-        map.insert(p.id.clone(), p);
+    let mut ids = points.keys().collect::<Vec<_>>();
+    ids.sort();
+    let mut vectors = empty_segment();
+    let batch = encode_upserts(
+        ids.into_iter().map(|id| &points[id]),
+        vectors.len() as u64,
+        0,
+    )?;
+    vectors.extend_from_slice(&batch.vectors);
+    files.write_generation(1, &vectors, &batch.payloads).await?;
+
+    if tokio::fs::metadata(&legacy_points).await.is_ok() {
+        tokio::fs::rename(
+            &legacy_points,
+            legacy_points.with_extension("jsonl.migrated"),
+        )
+        .await?;
+    }
+    let _ = tokio::fs::remove_file(&legacy_index).await;
+    Ok(1)
+}
+
+async fn open_collection(
+    state: &AppState,
+    collection: &str,
+) -> Result<Arc<RwLock<LoadedCollection>>, (StatusCode, &'static str)> {
+    let mut cache = state.vector_collections.lock().await;
+    if let Some(loaded) = cache.get(collection) {
+        return Ok(loaded.clone());
     }
 
-    let mut out = String::new();
-    for p in map.values() {
-        if let Ok(line) = serde_json::to_string(p) {
-            out.push_str(&line);
-            out.push('\n');
+    let (col_path, _, _) = collection_paths(state, collection);
+    let meta_text = tokio::fs::read_to_string(&col_path)
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "not_found"))?;
+    let meta: VectorCollection = serde_json::from_str(&meta_text)
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "parse_failed"))?;
+    let dim = meta.dimension as usize;
+    let (distance, quantization) = (meta.distance, meta.quantization);
+
+    let files = SegmentFiles::new(&vector_base_dir(state), collection);
+    let generation = match files.read_manifest().await {
+        Some(manifest) => manifest.generation,
+        None => migrate_legacy_points(state, collection, &files, dim)
+            .await
+            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "write_failed"))?,
+    };
+    let vectors = tokio::fs::read(files.vectors(generation))
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "read_failed"))?;
+    let payloads = tokio::fs::read(files.payloads(generation))
+        .await
+        .unwrap_or_default();
+    let index_bytes = tokio::fs::read(files.index()).await.unwrap_or_default();
+    let file_len = vectors.len() as u64;
+
+    let (scan, index, checkpoint) = tokio::task::spawn_blocking(move || {
+        let mut scan = scan_segment(&vectors, &payloads, dim)?;
+        let restored = HnswIndex::from_bytes(&index_bytes, dim, distance, quantization, |offset| {
+            vector_at(&vectors, offset, dim)
+        })
+        .filter(|(_, g, checkpoint)| *g == generation && *checkpoint <= scan.valid_len)
+        .map(|(mut index, _, checkpoint)| {
+            for record in records_from(&vectors, checkpoint, dim) {
+                match record.upsert {
+                    Some((vector, _, _)) => index.upsert(&record.id, &vector, record.offset),
+                    None => {
+                        index.remove(&record.id);
+                    }
+                }
+            }
+            (index, checkpoint)
+        })
+        .filter(|(index, _)| index.len() == scan.points.len());
+        let (index, checkpoint) = restored.unwrap_or_else(|| {
+            let index = HnswIndex::build(
+                dim,
+                distance,
+                quantization,
+                scan.points
+                    .values()
+                    .map(|p| (p.id.as_str(), p.vector.as_slice(), scan.locators[&p.id])),
+            );
+            (index, 0)
+        });
+        if !quantization.is_none() {
+            for p in scan.points.values_mut() {
+                p.vector = Vec::new();
+            }
         }
+        Some((scan, index, checkpoint))
+    })
+    .await
+    .ok()
+    .flatten()
+    .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "segment_corrupt"))?;
+
+    if scan.valid_len < file_len {
+        files
+            .truncate_vectors(generation, scan.valid_len)
+            .await
+            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "write_failed"))?;
     }
-    if let Err(err) = tokio::fs::write(&points_path, out).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": "write_failed", "message": err.to_string() })),
-        )
-            .into_response();
+    let payload_index = PayloadIndex::build(
+        &meta.payload_indexes,
+        scan.points.values().map(|p| (p.id.as_str(), &p.payload)),
+    );
+    let mut loaded = LoadedCollection {
+        meta,
+        points: scan.points,
+        index,
+        payload_index,
+        generation,
+        vectors_len: scan.valid_len,
+        payloads_len: tokio::fs::metadata(files.payloads(generation))
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0),
+        records: scan.records,
+        checkpoint,
+    };
+    if checkpoint == 0 {
+        let _ = persist_index(&files, &mut loaded).await;
     }
+    let loaded = Arc::new(RwLock::new(loaded));
+    cache.insert(collection.to_string(), loaded.clone());
+    Ok(loaded)
+}
 
-    (
-        StatusCode::OK,
-        Json(serde_json::json!({ "upserted": map.len() })),
+async fn persist_index(files: &SegmentFiles, loaded: &mut LoadedCollection) -> anyhow::Result<()> {
+    let path = files.index();
+    let tmp = path.with_extension("hnsw.tmp");
+    tokio::fs::write(
+        &tmp,
+        loaded.index.to_bytes(loaded.generation, loaded.vectors_len),
     )
-        .into_response()
+    .await?;
+    tokio::fs::rename(tmp, path).await?;
+    loaded.checkpoint = loaded.vectors_len;
+    Ok(())
 }
 
-pub(super) async fn vector_upsert_points_internal(
+async fn append_upserts(
     state: &AppState,
     collection: &str,
+    loaded: &mut LoadedCollection,
     points: Vec<VectorPoint>,
-) -> anyhow::Result<u64> {
-    if !is_safe_identifier(collection) {
-        anyhow::bail!("invalid_collection");
-    }
-    let base = vector_base_dir(state);
-    let col_path = base.join("collections").join(format!("{collection}.json"));
-    let meta_text = tokio::fs::read_to_string(&col_path).await?;
-    let meta: VectorCollection = serde_json::from_str(&meta_text)?;
-    if points
-        .iter()
-        .any(|p| p.id.trim().is_empty() || p.vector.len() != meta.dimension as usize)
-    {
-        anyhow::bail!("dimension_mismatch");
-    }
-    let points_path = base.join("points").join(format!("{collection}.jsonl"));
-    let existing = tokio::fs::read_to_string(&points_path)
-        .await
-        .unwrap_or_default();
-    let mut map = HashMap::<String, VectorPoint>::new();
-    for line in existing.lines() {
-        if line.trim().is_empty() {
-            continue;
+) -> anyhow::Result<()> {
+    let files = SegmentFiles::new(&vector_base_dir(state), collection);
+    let batch = encode_upserts(&points, loaded.vectors_len, loaded.payloads_len)?;
+    files.append(loaded.generation, &batch).await?;
+    loaded.vectors_len += batch.vectors.len() as u64;
+    loaded.payloads_len += batch.payloads.len() as u64;
+    loaded.records += points.len() as u64;
+    let quantized = !loaded.meta.quantization.is_none();
+    for (mut p, locator) in points.into_iter().zip(batch.locators) {
+        loaded.index.upsert(&p.id, &p.vector, locator);
+        if quantized {
+            p.vector = Vec::new();
         }
-        if let Ok(p) = serde_json::from_str::<VectorPoint>(line) {
-            map.insert(p.id.clone(), p);
+        if let Some(old) = loaded.points.get(&p.id) {
+            loaded.payload_index.remove(&p.id, &old.payload);
         }
+        loaded.payload_index.insert(&p.id, &p.payload);
+        loaded.points.insert(p.id.clone(), p);
     }
-    for p in points {
-        map.insert(p.id.clone(), p);
-    }
-    let mut out = String::new();
-    for p in map.values() {
-        out.push_str(&serde_json::to_string(p)?);
-        out.push('\n');
+    if loaded.vectors_len - loaded.checkpoint >= INDEX_CHECKPOINT_BYTES {
+        persist_index(&files, loaded).await?;
     }
-    tokio::fs::write(&points_path, out).await?;
-    Ok(map.len() as u64)
+    Ok(())
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct VectorFilter {
-    must: Option<Vec<VectorFilterCondition>>,
+async fn append_deletes(
+    state: &AppState,
+    collection: &str,
+    loaded: &mut LoadedCollection,
+    ids: &[String],
+) -> anyhow::Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let files = SegmentFiles::new(&vector_base_dir(state), collection);
+    let batch = encode_deletes(ids);
+    files.append(loaded.generation, &batch).await?;
+    loaded.vectors_len += batch.vectors.len() as u64;
+    loaded.records += ids.len() as u64;
+    for id in ids {
+        if let Some(old) = loaded.points.remove(id) {
+            loaded.payload_index.remove(id, &old.payload);
+        }
+        loaded.index.remove(id);
+    }
+    Ok(())
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct VectorFilterCondition {
-    key: String,
-    r#match: VectorMatch,
+async fn compact_collection(
+    state: &AppState,
+    collection: &str,
+    loaded: &mut LoadedCollection,
+) -> anyhow::Result<()> {
+    let files = SegmentFiles::new(&vector_base_dir(state), collection);
+    let previous = loaded.generation;
+    let generation = previous + 1;
+    let mut ids = loaded.points.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+    let mut raw = if loaded.meta.quantization.is_none() {
+        HashMap::new()
+    } else {
+        raw_vectors(state, collection, loaded, ids.clone()).await?
+    };
+    let live = ids
+        .iter()
+        .map(|id| {
+            let mut p = loaded.points[id].clone();
+            if let Some(vector) = raw.remove(id) {
+                p.vector = vector;
+            }
+            p
+        })
+        .collect::<Vec<_>>();
+    let mut vectors = empty_segment();
+    let batch = encode_upserts(&live, vectors.len() as u64, 0)?;
+    vectors.extend_from_slice(&batch.vectors);
+    files
+        .write_generation(generation, &vectors, &batch.payloads)
+        .await?;
+
+    let locators = ids
+        .iter()
+        .zip(batch.locators.iter().copied())
+        .collect::<HashMap<_, _>>();
+    if loaded.index.needs_compaction() {
+        loaded.index = HnswIndex::build(
+            loaded.meta.dimension as usize,
+            loaded.meta.distance,
+            loaded.meta.quantization,
+            live.iter()
+                .map(|p| (p.id.as_str(), p.vector.as_slice(), locators[&p.id])),
+        );
+    } else {
+        loaded
+            .index
+            .relocate(|id| locators.get(&id.to_string()).copied());
+    }
+    loaded.generation = generation;
+    loaded.vectors_len = vectors.len() as u64;
+    loaded.payloads_len = batch.payloads.len() as u64;
+    loaded.records = ids.len() as u64;
+    persist_index(&files, loaded).await?;
+    files.remove_generation(previous).await;
+    Ok(())
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct VectorMatch {
-    value: serde_json::Value,
+async fn raw_vectors(
+    state: &AppState,
+    collection: &str,
+    loaded: &LoadedCollection,
+    ids: Vec<String>,
+) -> anyhow::Result<HashMap<String, Vec<f32>>> {
+    if loaded.meta.quantization.is_none() {
+        return Ok(ids
+            .into_iter()
+            .filter_map(|id| {
+                let vector = loaded.points.get(&id)?.vector.clone();
+                Some((id, vector))
+            })
+            .collect());
+    }
+    let path = SegmentFiles::new(&vector_base_dir(state), collection).vectors(loaded.generation);
+    let dim = loaded.meta.dimension as usize;
+    let wanted = ids
+        .into_iter()
+        .filter_map(|id| {
+            let locator = loaded.index.locator(&id)?;
+            Some((id, locator))
+        })
+        .collect::<Vec<_>>();
+    tokio::task::spawn_blocking(move || {
+        use std::io::{Read as _, Seek as _, SeekFrom};
+        let mut file = std::fs::File::open(path)?;
+        let mut out = HashMap::with_capacity(wanted.len());
+        for (id, locator) in wanted {
+            let mut record = vec![0u8; upsert_record_len(id.len(), dim)];
+            file.seek(SeekFrom::Start(locator))?;
+            file.read_exact(&mut record)?;
+            if let Some(vector) = vector_at(&record, 0, dim) {
+                out.insert(id, vector);
+            }
+        }
+        Ok(out)
+    })
+    .await?
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
+fn schedule_compaction(state: &AppState, collection: &str, loaded: &Arc<RwLock<LoadedCollection>>) {
+    let state = state.clone();
+    let collection = collection.to_string();
+    let loaded = loaded.clone();
+    tokio::spawn(async move {
+        let mut guard = loaded.write().await;
+        if !guard.needs_compaction() {
+            return;
+        }
+        if let Err(err) = compact_collection(&state, &collection, &mut guard).await {
+            tracing::warn!(collection = %collection, error = %err, "vector compaction failed");
+            drop(guard);
+            state.vector_collections.lock().await.remove(&collection);
+        }
+    });
+}
+
+pub(super) async fn upsert_vector_points(
+    State(state): State<AppState>,
+    Json(req): Json<UpsertVectorPointsRequest>,
+) -> axum::response::Response {
+    vector_reply(
+        vector_upsert_points(&state, req)
+            .await
+            .map(|upserted| serde_json::json!({ "upserted": upserted })),
+    )
+}
+
+pub(super) async fn vector_upsert_points(
+    state: &AppState,
+    req: UpsertVectorPointsRequest,
+) -> Result<usize, VectorError> {
+    if !is_safe_identifier(&req.collection) {
+        return Err(VectorError::invalid_collection());
+    }
+    if req.points.is_empty() || req.points.len() > 10_000 {
+        return Err(
+            VectorError::new(StatusCode::BAD_REQUEST, "limit_exceeded").message("too_many_points")
+        );
+    }
+
+    let loaded = open_collection(state, &req.collection)
+        .await
+        .map_err(|err| VectorError::collection(&req.collection, err))?;
+    let handle = loaded;
+    let mut loaded = handle.write().await;
+
+    if req
+        .points
+        .iter()
+        .any(|p| p.id.trim().is_empty() || p.vector.len() != loaded.meta.dimension as usize)
+    {
+        return Err(VectorError::validation("dimension_mismatch"));
+    }
+    if let Some(message) = loaded
+        .meta
+        .embedding_model_error(req.embedding_model.as_deref())
+    {
+        return Err(VectorError::validation(message)
+            .detail("collection", &req.collection)
+            .detail("embeddingModel", &loaded.meta.embedding_model));
+    }
+    record_embedding_model(state, &mut loaded, req.embedding_model.as_deref())
+        .await
+        .map_err(VectorError::write_failed)?;
+
+    let batch_id = req.batch_id.clone();
+    let points = req
+        .points
+        .into_iter()
+        .map(|mut p| {
+            if p.payload.is_null() {
+                p.payload = serde_json::Value::Object(serde_json::Map::new());
+            }
+            if p.batch_id.is_none() {
+                p.batch_id = batch_id.clone();
+            }
+            p
+        })
+        .collect::<Vec<_>>();
+
+    if let Err(err) = append_upserts(state, &req.collection, &mut loaded, points).await {
+        drop(loaded);
+        state
+            .vector_collections
+            .lock()
+            .await
+            .remove(&req.collection);
+        return Err(VectorError::write_failed(err));
+    }
+    if loaded.needs_compaction() {
+        schedule_compaction(state, &req.collection, &handle);
+    }
+    Ok(loaded.points.len())
+}
+
+pub(super) async fn vector_upsert_points_internal(
+    state: &AppState,
+    collection: &str,
+    points: Vec<VectorPoint>,
+    embedding_model: Option<&str>,
+) -> anyhow::Result<u64> {
+    if !is_safe_identifier(collection) {
+        anyhow::bail!("invalid_collection");
+    }
+    let loaded = open_collection(state, collection)
+        .await
+        .map_err(|(_, error)| anyhow::anyhow!(error))?;
+    let handle = loaded;
+    let mut loaded = handle.write().await;
+    if points
+        .iter()
+        .any(|p| p.id.trim().is_empty() || p.vector.len() != loaded.meta.dimension as usize)
+    {
+        anyhow::bail!("dimension_mismatch");
+    }
+    if let Some(message) = loaded.meta.embedding_model_error(embedding_model) {
+        anyhow::bail!(message);
+    }
+    record_embedding_model(state, &mut loaded, embedding_model).await?;
+    if let Err(err) = append_upserts(state, collection, &mut loaded, points).await {
+        drop(loaded);
+        state.vector_collections.lock().await.remove(collection);
+        return Err(err);
+    }
+    if loaded.needs_compaction() {
+        schedule_compaction(state, collection, &handle);
+    }
+    Ok(loaded.points.len() as u64)
+}
+
+pub(super) async fn vector_delete_points_internal(
+    state: &AppState,
+    collection: &str,
+    ids: &[String],
+) -> anyhow::Result<u64> {
+    if !is_safe_identifier(collection) {
+        anyhow::bail!("invalid_collection");
+    }
+    let loaded = open_collection(state, collection)
+        .await
+        .map_err(|(_, error)| anyhow::anyhow!(error))?;
+    let handle = loaded;
+    let mut loaded = handle.write().await;
+    let doomed = ids
+        .iter()
+        .filter(|id| loaded.points.contains_key(id.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    if let Err(err) = append_deletes(state, collection, &mut loaded, &doomed).await {
+        drop(loaded);
+        state.vector_collections.lock().await.remove(collection);
+        return Err(err);
+    }
+    if loaded.needs_compaction() {
+        schedule_compaction(state, collection, &handle);
+    }
+    Ok(doomed.len() as u64)
+}
+
+#[derive(Default)]
+pub(super) struct StoredDocument {
+    pub(super) content_hash: Option<String>,
+    pub(super) chunk_ids: Vec<String>,
+}
+
+pub(super) async fn vector_stored_documents(
+    state: &AppState,
+    collection: &str,
+) -> Result<HashMap<String, StoredDocument>, (StatusCode, &'static str)> {
+    if !is_safe_identifier(collection) {
+        return Err((StatusCode::BAD_REQUEST, "invalid_collection"));
+    }
+    let loaded = open_collection(state, collection).await?;
+    let loaded = loaded.read().await;
+    let mut out = HashMap::<String, StoredDocument>::new();
+    for p in loaded.points.values() {
+        let Some(doc_id) = p.payload.get("_documentId").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let hash = p.payload.get("_contentHash").and_then(|v| v.as_str());
+        let doc = out.entry(doc_id.to_string()).or_default();
+        doc.content_hash = if doc.chunk_ids.is_empty() || doc.content_hash.as_deref() == hash {
+            hash.map(str::to_string)
+        } else {
+            None
+        };
+        doc.chunk_ids.push(p.id.clone());
+    }
+    Ok(out)
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub(super) struct SearchVectorRequest {
-    collection: String,
-    vector: Vec<f32>,
-    top_k: Option<u32>,
-    filter: Option<VectorFilter>,
+    pub(super) collection: String,
+    #[serde(default)]
+    pub(super) vector: Vec<f32>,
+    pub(super) top_k: Option<u32>,
+    pub(super) filter: Option<VectorFilter>,
+    #[serde(default)]
+    pub(super) exact: bool,
+    pub(super) ef: Option<u32>,
+    pub(super) distance: Option<String>,
+    pub(super) rescore: Option<bool>,
+    pub(super) oversampling: Option<f32>,
+    pub(super) query: Option<String>,
+    pub(super) mode: Option<SearchMode>,
+    pub(super) text_field: Option<String>,
+    pub(super) fusion: Option<Fusion>,
+    pub(super) alpha: Option<f32>,
+    pub(super) rrf_k: Option<u32>,
+    pub(super) mmr: Option<MmrOptions>,
+    pub(super) provider_id: Option<String>,
+    pub(super) embedding_model: Option<String>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct VectorSearchHit {
-    id: String,
-    score: f32,
-    payload: serde_json::Value,
+pub(super) struct VectorSearchHit {
+    pub(super) id: String,
+    pub(super) score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vector_score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyword_score: Option<f32>,
+    pub(super) payload: serde_json::Value,
+}
+
+fn search_validation_error(message: &str) -> axum::response::Response {
+    VectorError::validation(message).into_response()
+}
+
+async fn embed_query(
+    state: &AppState,
+    provider_id: &str,
+    model: &str,
+    query: &str,
+) -> Result<Vec<f32>, VectorError> {
+    let provider_error = |message: String| {
+        VectorError::new(StatusCode::BAD_REQUEST, "provider_failed").message(message)
+    };
+    let embedding_error = |message: String| {
+        VectorError::new(StatusCode::BAD_REQUEST, "embedding_failed").message(message)
+    };
+    let provider = crate::load_provider(state, provider_id)
+        .await
+        .map_err(|_| provider_error("not_found".to_string()))?;
+    let api_key = crate::decrypt_provider_api_key(state, provider_id)
+        .await
+        .map_err(|err| provider_error(err.to_string()))?;
+    let embeddings = crate::ProviderClient::new(&provider, api_key)
+        .embeddings(model, &[query.to_string()])
+        .await
+        .map_err(|err| embedding_error(err.to_string()))?;
+    match <[Vec<f32>; 1]>::try_from(embeddings) {
+        Ok([vector]) => Ok(vector),
+        Err(_) => Err(embedding_error("count_mismatch".to_string())),
+    }
 }
 
 pub(super) async fn search_vector(
     State(state): State<AppState>,
     Json(req): Json<SearchVectorRequest>,
 ) -> axum::response::Response {
+    vector_reply(
+        vector_search(&state, req)
+            .await
+            .map(|hits| serde_json::json!({ "hits": hits })),
+    )
+}
+
+pub(super) async fn vector_search(
+    state: &AppState,
+    mut req: SearchVectorRequest,
+) -> Result<Vec<VectorSearchHit>, VectorError> {
     if !is_safe_identifier(&req.collection) {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "invalid_collection" })),
-        )
-            .into_response();
+        return Err(VectorError::invalid_collection());
     }
     let top_k = req.top_k.unwrap_or(10).min(100) as usize;
+    if let Some(Err(message)) = req.filter.as_ref().map(VectorFilter::validate) {
+        return Err(VectorError::validation(message));
+    }
+    let query = req.query.clone().unwrap_or_default();
+    let query = query.trim();
+    let provider_id = req
+        .provider_id
+        .clone()
+        .filter(|id| !id.trim().is_empty() && req.vector.is_empty());
+    let mode = req
+        .mode
+        .unwrap_or(match (req.vector.is_empty(), query.is_empty()) {
+            (_, true) => SearchMode::Vector,
+            (true, false) if provider_id.is_some() => SearchMode::Vector,
+            (true, false) => SearchMode::Keyword,
+            (false, false) => SearchMode::Hybrid,
+        });
+    if (mode != SearchMode::Vector || provider_id.is_some()) && query.is_empty() {
+        return Err(VectorError::validation("query_required"));
+    }
+    let alpha = req.alpha.unwrap_or(DEFAULT_ALPHA);
+    if !(0.0..=1.0).contains(&alpha) {
+        return Err(VectorError::validation("invalid_alpha"));
+    }
+    let lambda = req
+        .mmr
+        .as_ref()
+        .and_then(|m| m.lambda)
+        .unwrap_or(DEFAULT_MMR_LAMBDA);
+    if !(0.0..=1.0).contains(&lambda) {
+        return Err(VectorError::validation("invalid_mmr_lambda"));
+    }
+    let depth = if mode == SearchMode::Vector && req.mmr.is_none() {
+        top_k
+    } else {
+        req.mmr
+            .as_ref()
+            .and_then(|m| m.fetch_k)
+            .map_or(top_k * 4, |n| n as usize)
+            .clamp(top_k, 1_000)
+    };
 
-    let base = vector_base_dir(&state);
-    let col_path = base
-        .join("collections")
-        .join(format!("{}.json", req.collection));
-    let meta_text = match tokio::fs::read_to_string(&col_path).await {
-        Ok(t) => t,
-        Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({ "error": "not_found", "collection": req.collection })),
-            )
-                .into_response();
-        }
+    let loaded = open_collection(state, &req.collection)
+        .await
+        .map_err(|err| VectorError::collection(&req.collection, err))?;
+    let (embedding_model, dimension) = {
+        let loaded = loaded.read().await;
+        (loaded.meta.embedding_model.clone(), loaded.meta.dimension)
     };
-    let meta: VectorCollection = match serde_json::from_str(&meta_text) {
-        Ok(m) => m,
-        Err(_) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": "parse_failed" })),
-            )
-                .into_response();
+    if req.embedding_model.is_some() && req.embedding_model != embedding_model {
+        return Err(VectorError::validation("embedding_model_mismatch")
+            .detail("collection", &req.collection)
+            .detail("embeddingModel", &embedding_model));
+    }
+    if let Some(provider_id) = provider_id
+        .as_deref()
+        .filter(|_| mode != SearchMode::Keyword)
+    {
+        let Some(model) = embedding_model.as_deref() else {
+            return Err(VectorError::validation("embedding_model_missing"));
+        };
+        let vector = embed_query(state, provider_id, model, query).await?;
+        if vector.len() != dimension as usize {
+            return Err(VectorError::validation("dimension_mismatch")
+                .detail("collection", &req.collection)
+                .detail("dimension", dimension)
+                .detail("embeddingDimension", vector.len()));
         }
+        req.vector = vector;
+    }
+    let loaded = loaded.read().await;
+    let distance = loaded.meta.distance;
+    if req
+        .distance
+        .as_deref()
+        .is_some_and(|d| Distance::parse(d) != Some(distance))
+    {
+        return Err(VectorError::validation("distance_mismatch")
+            .detail("collection", &req.collection)
+            .detail("distance", distance));
+    }
+    let indexed = loaded.indexed_candidates(req.filter.as_ref());
+
+    let vector_hits = if mode == SearchMode::Keyword {
+        Vec::new()
+    } else {
+        vector_hits(state, &req, &loaded, indexed.as_ref(), depth).await?
     };
-    if req.vector.len() != meta.dimension as usize {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": "validation_failed", "message": "dimension_mismatch" })),
-        )
-            .into_response();
+    let keyword_hits = if mode == SearchMode::Vector {
+        Vec::new()
+    } else {
+        let searched =
+            loaded
+                .payload_index
+                .text_search(req.text_field.as_deref(), query, depth, |id| {
+                    indexed.as_ref().is_none_or(|ids| ids.contains(id))
+                        && loaded
+                            .points
+                            .get(id)
+                            .is_some_and(|p| matches_filter(&p.id, &p.payload, req.filter.as_ref()))
+                });
+        searched.map_err(VectorError::validation)?
+    };
+
+    let mut ranked = match mode {
+        SearchMode::Vector => vector_hits
+            .into_iter()
+            .map(|(id, sim)| Ranked {
+                id,
+                relevance: sim,
+                vector_score: None,
+                keyword_score: None,
+            })
+            .collect(),
+        SearchMode::Keyword => keyword_hits
+            .into_iter()
+            .map(|(id, score)| Ranked {
+                id,
+                relevance: score,
+                vector_score: None,
+                keyword_score: Some(score),
+            })
+            .collect(),
+        SearchMode::Hybrid => fuse(
+            &vector_hits,
+            &keyword_hits,
+            req.fusion.unwrap_or_default(),
+            alpha,
+            req.rrf_k.map_or(DEFAULT_RRF_K, |k| k as f32),
+        ),
+    };
+    if req.mmr.is_some() {
+        let ids = ranked.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+        let vectors = raw_vectors(state, &req.collection, &loaded, ids)
+            .await
+            .map_err(VectorError::read_failed)?;
+        ranked = mmr(ranked, &vectors, lambda, top_k);
     }
+    ranked.truncate(top_k);
+    let hits = ranked
+        .into_iter()
+        .filter_map(|r| {
+            let p = loaded.points.get(&r.id)?;
+            let score = match mode {
+                SearchMode::Vector => distance.score(r.relevance),
+                SearchMode::Keyword | SearchMode::Hybrid => r.relevance,
+            };
+            Some(VectorSearchHit {
+                id: r.id,
+                score,
+                vector_score: r.vector_score.map(|sim| distance.score(sim)),
+                keyword_score: r.keyword_score,
+                payload: p.payload.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(hits)
+}
 
-    let points_path = base
-        .join("points")
-        .join(format!("{}.jsonl", req.collection));
-    let text = tokio::fs::read_to_string(&points_path)
-        .await
-        .unwrap_or_default();
-    let mut hits = Vec::<VectorSearchHit>::new();
-    for line in text.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        let Ok(p) = serde_json::from_str::<VectorPoint>(line) else {
-            continue;
+async fn vector_hits(
+    state: &AppState,
+    req: &SearchVectorRequest,
+    loaded: &LoadedCollection,
+    indexed: Option<&HashSet<String>>,
+    depth: usize,
+) -> Result<Vec<(String, f32)>, VectorError> {
+    if req.vector.len() != loaded.meta.dimension as usize {
+        return Err(VectorError::validation("dimension_mismatch"));
+    }
+    let distance = loaded.meta.distance;
+    let quantization = loaded.meta.quantization;
+    let oversampling = req
+        .oversampling
+        .unwrap_or_else(|| quantization.default_oversampling());
+    if !(1.0..=16.0).contains(&oversampling) {
+        return Err(VectorError::validation("invalid_oversampling"));
+    }
+    let scan = req.exact || indexed.is_some_and(|ids| ids.len() <= FILTERED_SCAN_THRESHOLD);
+    let rescore = !quantization.is_none() && (scan || req.rescore.unwrap_or(true));
+
+    let candidates = if scan {
+        loaded
+            .matching_points(req.filter.as_ref(), indexed)
+            .into_iter()
+            .map(|p| {
+                let sim = if quantization.is_none() {
+                    distance.similarity(&req.vector, &p.vector)
+                } else {
+                    0.0
+                };
+                (p.id.clone(), sim)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        let ef = req
+            .ef
+            .map(|ef| ef.clamp(1, 4096) as usize)
+            .unwrap_or(DEFAULT_EF_SEARCH);
+        let fetch = if rescore {
+            (depth as f32 * oversampling).ceil() as usize
+        } else {
+            depth
         };
-        if !vector_point_matches_filter(&p, req.filter.as_ref()) {
-            continue;
+        loaded.index.search(&req.vector, fetch, ef, |id| {
+            indexed.is_none_or(|ids| ids.contains(id))
+                && loaded
+                    .points
+                    .get(id)
+                    .is_some_and(|p| matches_filter(&p.id, &p.payload, req.filter.as_ref()))
+        })
+    };
+    let mut hits = if rescore {
+        let ids = candidates.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+        let raw = raw_vectors(state, &req.collection, loaded, ids)
+            .await
+            .map_err(VectorError::read_failed)?;
+        let mut hits = raw
+            .into_iter()
+            .map(|(id, vector)| {
+                let sim = distance.similarity(&req.vector, &vector);
+                (id, sim)
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hits
+    } else {
+        let mut hits = candidates;
+        if scan {
+            hits.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
         }
-        let score = cosine_similarity(&req.vector, &p.vector);
-        hits.push(VectorSearchHit {
-            id: p.id,
-            score,
-            payload: p.payload,
-        });
+        hits
+    };
+    hits.truncate(depth);
+    Ok(hits)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct DeleteVectorPointsRequest {
+    pub(super) collection: String,
+    pub(super) filter: Option<VectorFilter>,
+    pub(super) batch_id: Option<String>,
+}
+
+pub(super) async fn delete_vector_points(
+    State(state): State<AppState>,
+    Json(req): Json<DeleteVectorPointsRequest>,
+) -> axum::response::Response {
+    vector_reply(
+        vector_delete_points(&state, req)
+            .await
+            .map(|deleted| serde_json::json!({ "deleted": deleted })),
+    )
+}
+
+pub(super) async fn vector_delete_points(
+    state: &AppState,
+    req: DeleteVectorPointsRequest,
+) -> Result<u64, VectorError> {
+    if !is_safe_identifier(&req.collection) {
+        return Err(VectorError::invalid_collection());
     }
-    hits.sort_by(|a, b| {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-    hits.truncate(top_k);
-    (StatusCode::OK, Json(serde_json::json!({ "hits": hits }))).into_response()
+    if let Some(Err(message)) = req.filter.as_ref().map(VectorFilter::validate) {
+        return Err(VectorError::validation(message));
+    }
+    let loaded = open_collection(state, &req.collection)
+        .await
+        .map_err(|err| VectorError::collection(&req.collection, err))?;
+    let handle = loaded;
+    let mut loaded = handle.write().await;
+    let indexed = loaded.indexed_candidates(req.filter.as_ref());
+    let doomed = loaded
+        .matching_points(req.filter.as_ref(), indexed.as_ref())
+        .into_iter()
+        .filter(|p| {
+            req.batch_id
+                .as_deref()
+                .is_none_or(|bid| p.batch_id.as_deref() == Some(bid))
+        })
+        .map(|p| p.id.clone())
+        .collect::<Vec<_>>();
+    if let Err(err) = append_deletes(state, &req.collection, &mut loaded, &doomed).await {
+        drop(loaded);
+        state
+            .vector_collections
+            .lock()
+            .await
+            .remove(&req.collection);
+        return Err(VectorError::write_failed(err));
+    }
+    if loaded.needs_compaction() {
+        schedule_compaction(state, &req.collection, &handle);
+    }
+    Ok(doomed.len() as u64)
 }
 
-fn vector_point_matches_filter(p: &VectorPoint, filter: Option<&VectorFilter>) -> bool {
-    let Some(filter) = filter else {
-        return true;
-    };
-    let Some(must) = &filter.must else {
-        return true;
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct VectorCollectionRequest {
+    collection: String,
+}
+
+async fn remove_collection_files(state: &AppState, collection: &str) -> std::io::Result<()> {
+    let (col_path, legacy_points, legacy_index) = collection_paths(state, collection);
+    match tokio::fs::remove_file(&col_path).await {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    SegmentFiles::new(&vector_base_dir(state), collection)
+        .remove_all()
+        .await;
+    let _ = tokio::fs::remove_file(legacy_points.with_extension("jsonl.migrated")).await;
+    let _ = tokio::fs::remove_file(&legacy_points).await;
+    let _ = tokio::fs::remove_file(&legacy_index).await;
+    Ok(())
+}
+
+pub(super) async fn drop_vector_collection(
+    State(state): State<AppState>,
+    Json(req): Json<VectorCollectionRequest>,
+) -> axum::response::Response {
+    vector_reply(
+        vector_drop_collection(&state, &req.collection)
+            .await
+            .map(|()| serde_json::json!({ "collection": req.collection, "dropped": true })),
+    )
+}
+
+pub(super) async fn vector_drop_collection(
+    state: &AppState,
+    collection: &str,
+) -> Result<(), VectorError> {
+    if !is_safe_identifier(collection) {
+        return Err(VectorError::invalid_collection());
+    }
+    let (col_path, _, _) = collection_paths(state, collection);
+    let mut cache = state.vector_collections.lock().await;
+    if tokio::fs::metadata(&col_path).await.is_err() {
+        return Err(VectorError::collection(
+            collection,
+            (StatusCode::NOT_FOUND, "not_found"),
+        ));
+    }
+    let cached = cache.remove(collection);
+    let _guard = match &cached {
+        Some(loaded) => Some(loaded.write().await),
+        None => None,
     };
-    for cond in must {
-        if cond.key.trim().is_empty() {
-            return false;
+    remove_collection_files(state, collection)
+        .await
+        .map_err(VectorError::write_failed)
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct PayloadFieldStats {
+    pub(super) points: u64,
+    types: BTreeMap<&'static str, u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) index: Option<PayloadSchema>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct VectorCollectionStats {
+    pub(super) collection: VectorCollection,
+    pub(super) points: u64,
+    records: u64,
+    dead_records: u64,
+    generation: u64,
+    bytes: VectorCollectionBytes,
+    pub(super) payload_schema: BTreeMap<String, PayloadFieldStats>,
+}
+
+#[derive(Serialize)]
+struct VectorCollectionBytes {
+    vectors: u64,
+    payloads: u64,
+    index: u64,
+}
+
+fn json_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+fn collect_payload_shape(
+    path: String,
+    value: &serde_json::Value,
+    depth: usize,
+    out: &mut BTreeMap<String, BTreeSet<&'static str>>,
+) {
+    out.entry(path.clone())
+        .or_default()
+        .insert(json_type(value));
+    if depth >= 4 {
+        return;
+    }
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                collect_payload_shape(format!("{path}.{key}"), value, depth + 1, out);
+            }
         }
-        let Some(obj) = p.payload.as_object() else {
-            return false;
-        };
-        let Some(v) = obj.get(&cond.key) else {
-            return false;
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_payload_shape(format!("{path}[]"), item, depth + 1, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(super) async fn vector_collection_stats(
+    State(state): State<AppState>,
+    Json(req): Json<VectorCollectionRequest>,
+) -> axum::response::Response {
+    vector_reply(vector_stats(&state, &req.collection).await)
+}
+
+pub(super) async fn vector_stats(
+    state: &AppState,
+    collection: &str,
+) -> Result<VectorCollectionStats, VectorError> {
+    if !is_safe_identifier(collection) {
+        return Err(VectorError::invalid_collection());
+    }
+    let loaded = open_collection(state, collection)
+        .await
+        .map_err(|err| VectorError::collection(collection, err))?;
+    let loaded = loaded.read().await;
+
+    let mut fields = BTreeMap::<String, PayloadFieldStats>::new();
+    for p in loaded.points.values() {
+        let Some(payload) = p.payload.as_object() else {
+            continue;
         };
-        if v != &cond.r#match.value {
-            return false;
+        let mut shape = BTreeMap::new();
+        for (key, value) in payload {
+            collect_payload_shape(key.clone(), value, 0, &mut shape);
         }
+        for (path, types) in shape {
+            let stats = fields.entry(path).or_default();
+            stats.points += 1;
+            for t in types {
+                *stats.types.entry(t).or_default() += 1;
+            }
+        }
+    }
+    for spec in &loaded.meta.payload_indexes {
+        fields.entry(spec.field.clone()).or_default().index = Some(spec.schema);
     }
-    true
+
+    let files = SegmentFiles::new(&vector_base_dir(state), collection);
+    let index_bytes = tokio::fs::metadata(files.index())
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    Ok(VectorCollectionStats {
+        collection: loaded.meta.clone(),
+        points: loaded.points.len() as u64,
+        records: loaded.records,
+        dead_records: loaded.dead_records(),
+        generation: loaded.generation,
+        bytes: VectorCollectionBytes {
+            vectors: loaded.vectors_len,
+            payloads: loaded.payloads_len,
+            index: index_bytes,
+        },
+        payload_schema: fields,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct VectorPointRecord {
+    pub(super) id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) vector: Option<Vec<f32>>,
+    pub(super) payload: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    batch_id: Option<String>,
+}
+
+async fn point_records(
+    state: &AppState,
+    collection: &str,
+    loaded: &LoadedCollection,
+    ids: Vec<String>,
+    with_vector: bool,
+) -> Result<Vec<VectorPointRecord>, VectorError> {
+    let mut vectors = if with_vector {
+        raw_vectors(state, collection, loaded, ids.clone())
+            .await
+            .map_err(VectorError::read_failed)?
+    } else {
+        HashMap::new()
+    };
+    Ok(ids
+        .into_iter()
+        .filter_map(|id| {
+            let p = loaded.points.get(&id)?;
+            Some(VectorPointRecord {
+                vector: vectors.remove(&id),
+                payload: p.payload.clone(),
+                batch_id: p.batch_id.clone(),
+                id,
+            })
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ScrollVectorPointsRequest {
+    pub(super) collection: String,
+    pub(super) filter: Option<VectorFilter>,
+    pub(super) limit: Option<u32>,
+    pub(super) cursor: Option<String>,
+    #[serde(default)]
+    pub(super) with_vector: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct VectorPointPage {
+    pub(super) points: Vec<VectorPointRecord>,
+    pub(super) next_cursor: Option<String>,
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    let mut dot = 0.0f32;
-    let mut na = 0.0f32;
-    let mut nb = 0.0f32;
-    for (x, y) in a.iter().zip(b.iter()) {
-        dot += x * y;
-        na += x * x;
-        nb += y * y;
+pub(super) async fn scroll_vector_points(
+    State(state): State<AppState>,
+    Json(req): Json<ScrollVectorPointsRequest>,
+) -> axum::response::Response {
+    vector_reply(vector_scroll_points(&state, req).await)
+}
+
+pub(super) async fn vector_scroll_points(
+    state: &AppState,
+    req: ScrollVectorPointsRequest,
+) -> Result<VectorPointPage, VectorError> {
+    if !is_safe_identifier(&req.collection) {
+        return Err(VectorError::invalid_collection());
     }
-    if na == 0.0 || nb == 0.0 {
-        return 0.0;
+    if let Some(Err(message)) = req.filter.as_ref().map(VectorFilter::validate) {
+        return Err(VectorError::validation(message));
     }
-    dot / (na.sqrt() * nb.sqrt())
+    let limit = req.limit.unwrap_or(100).clamp(1, 1_000) as usize;
+    let loaded = open_collection(state, &req.collection)
+        .await
+        .map_err(|err| VectorError::collection(&req.collection, err))?;
+    let loaded = loaded.read().await;
+    let indexed = loaded.indexed_candidates(req.filter.as_ref());
+    let mut ids = loaded
+        .matching_points(req.filter.as_ref(), indexed.as_ref())
+        .into_iter()
+        .map(|p| p.id.as_str())
+        .filter(|id| req.cursor.as_deref().is_none_or(|cursor| *id > cursor))
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    let next_cursor = (ids.len() > limit).then(|| ids[limit - 1].to_string());
+    let ids = ids
+        .into_iter()
+        .take(limit)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let points = point_records(state, &req.collection, &loaded, ids, req.with_vector).await?;
+    Ok(VectorPointPage {
+        points,
+        next_cursor,
+    })
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(super) struct DeleteVectorPointsRequest {
-    collection: String,
-    filter: Option<VectorFilter>,
-    batch_id: Option<String>,
+pub(super) struct GetVectorPointsRequest {
+    pub(super) collection: String,
+    pub(super) ids: Vec<String>,
+    #[serde(default)]
+    pub(super) with_vector: bool,
 }
 
-pub(super) async fn delete_vector_points(
+pub(super) async fn get_vector_points(
     State(state): State<AppState>,
-    Json(req): Json<DeleteVectorPointsRequest>,
+    Json(req): Json<GetVectorPointsRequest>,
+) -> axum::response::Response {
+    vector_reply(
+        vector_get_points(&state, req)
+            .await
+            .map(|points| serde_json::json!({ "points": points })),
+    )
+}
+
+pub(super) async fn vector_get_points(
+    state: &AppState,
+    req: GetVectorPointsRequest,
+) -> Result<Vec<VectorPointRecord>, VectorError> {
+    if !is_safe_identifier(&req.collection) {
+        return Err(VectorError::invalid_collection());
+    }
+    if req.ids.len() > 1_000 {
+        return Err(
+            VectorError::new(StatusCode::BAD_REQUEST, "limit_exceeded").message("too_many_ids")
+        );
+    }
+    let loaded = open_collection(state, &req.collection)
+        .await
+        .map_err(|err| VectorError::collection(&req.collection, err))?;
+    let loaded = loaded.read().await;
+    point_records(state, &req.collection, &loaded, req.ids, req.with_vector).await
+}
+
+pub(super) async fn snapshot_vector_collection(
+    State(state): State<AppState>,
+    Json(req): Json<VectorCollectionRequest>,
 ) -> axum::response::Response {
     if !is_safe_identifier(&req.collection) {
         return (
@@ -465,55 +1684,162 @@ pub(super) async fn delete_vector_points(
         )
             .into_response();
     }
-    let base = vector_base_dir(&state);
-    let points_path = base
-        .join("points")
-        .join(format!("{}.jsonl", req.collection));
-    let text = tokio::fs::read_to_string(&points_path)
-        .await
-        .unwrap_or_default();
-    let mut kept = Vec::<VectorPoint>::new();
-    let mut deleted: u64 = 0;
-    for line in text.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-        let Ok(p) = serde_json::from_str::<VectorPoint>(line) else {
-            continue;
-        };
-        let by_batch = req
-            .batch_id
-            .as_deref()
-            .is_some_and(|bid| p.batch_id.as_deref() == Some(bid));
-        let by_filter = vector_point_matches_filter(&p, req.filter.as_ref());
-        let should_delete = if req.batch_id.is_some() {
-            by_batch && by_filter
-        } else {
-            by_filter
+    let loaded = match open_collection(&state, &req.collection).await {
+        Ok(loaded) => loaded,
+        Err(err) => return collection_error(&req.collection, err),
+    };
+    let loaded = loaded.read().await;
+    let files = SegmentFiles::new(&vector_base_dir(&state), &req.collection);
+    let read = async {
+        let mut vectors = tokio::fs::read(files.vectors(loaded.generation)).await?;
+        vectors.truncate(loaded.vectors_len as usize);
+        let mut payloads = tokio::fs::read(files.payloads(loaded.generation))
+            .await
+            .unwrap_or_default();
+        payloads.truncate(loaded.payloads_len as usize);
+        let snapshot = VectorSnapshot {
+            meta: serde_json::to_vec(&loaded.meta)?,
+            generation: loaded.generation,
+            vectors,
+            payloads,
+            index: tokio::fs::read(files.index()).await.unwrap_or_default(),
         };
-        if should_delete {
-            deleted += 1;
-        } else {
-            kept.push(p);
+        tokio::task::spawn_blocking(move || snapshot.encode()).await?
+    };
+    let archive = match read.await {
+        Ok(archive) => archive,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "read_failed", "message": err.to_string() })),
+            )
+                .into_response();
         }
+    };
+    drop(loaded);
+
+    let mut res = axum::response::Response::new(axum::body::Body::from(archive));
+    let headers = res.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    if let Ok(v) = HeaderValue::from_str(&format!(
+        "attachment; filename=\"{}.vsnap\"",
+        req.collection
+    )) {
+        headers.insert(header::CONTENT_DISPOSITION, v);
     }
-    let mut out = String::new();
-    for p in kept {
-        if let Ok(line) = serde_json::to_string(&p) {
-            out.push_str(&line);
-            out.push('\n');
+    res
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct RestoreVectorCollectionQuery {
+    name: Option<String>,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+pub(super) async fn restore_vector_collection(
+    State(state): State<AppState>,
+    Query(q): Query<RestoreVectorCollectionQuery>,
+    body: Bytes,
+) -> axum::response::Response {
+    let decoded = tokio::task::spawn_blocking(move || {
+        let snapshot = VectorSnapshot::decode(&body)?;
+        let meta = serde_json::from_slice::<VectorCollection>(&snapshot.meta).ok()?;
+        if meta.dimension == 0 || meta.dimension > 4096 {
+            return None;
+        }
+        scan_segment(
+            &snapshot.vectors,
+            &snapshot.payloads,
+            meta.dimension as usize,
+        )?;
+        Some((snapshot, meta))
+    })
+    .await;
+    let Ok(Some((snapshot, mut meta))) = decoded else {
+        return search_validation_error("invalid_snapshot");
+    };
+    let name = q.name.unwrap_or_else(|| meta.name.clone());
+    if !is_safe_identifier(&name) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "invalid_collection" })),
+        )
+            .into_response();
+    }
+    meta.name = name.clone();
+
+    let (col_path, legacy_points, legacy_index) = collection_paths(&state, &name);
+    let mut cache = state.vector_collections.lock().await;
+    if !q.overwrite && tokio::fs::metadata(&col_path).await.is_ok() {
+        return collection_error(&name, (StatusCode::CONFLICT, "already_exists"));
+    }
+    let cached = cache.remove(&name);
+    let guard = match &cached {
+        Some(loaded) => Some(loaded.write().await),
+        None => None,
+    };
+    // Stage the restored collection next to the live one and swap it in only
+    // once every file is written, so a failed overwrite keeps the original.
+    let files = SegmentFiles::new(&vector_base_dir(&state), &name);
+    let staged = files.sibling("restore");
+    let meta_tmp = col_path.with_extension("json.restore");
+    let written = async {
+        staged.remove_all().await;
+        staged
+            .write_generation(snapshot.generation, &snapshot.vectors, &snapshot.payloads)
+            .await?;
+        if !snapshot.index.is_empty() {
+            tokio::fs::write(staged.index(), &snapshot.index).await?;
+        }
+        if let Some(dir) = col_path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&meta_tmp, serde_json::to_string_pretty(&meta)?).await?;
+        let previous = files.swap_in(&staged).await?;
+        if let Err(err) = tokio::fs::rename(&meta_tmp, &col_path).await {
+            files.reinstate(&previous).await;
+            return Err(err.into());
         }
+        previous.remove_all().await;
+        let _ = tokio::fs::remove_file(legacy_points.with_extension("jsonl.migrated")).await;
+        let _ = tokio::fs::remove_file(&legacy_points).await;
+        let _ = tokio::fs::remove_file(&legacy_index).await;
+        Ok(())
+    };
+    let written: anyhow::Result<()> = written.await;
+    if written.is_err() {
+        staged.remove_all().await;
+        let _ = tokio::fs::remove_file(&meta_tmp).await;
     }
-    if let Err(err) = tokio::fs::write(&points_path, out).await {
+    drop(guard);
+    drop(cache);
+    if let Err(err) = written {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": "write_failed", "message": err.to_string() })),
         )
             .into_response();
     }
+
+    let loaded = match open_collection(&state, &name).await {
+        Ok(loaded) => loaded,
+        Err(_) => {
+            let _ = remove_collection_files(&state, &name).await;
+            return search_validation_error("invalid_snapshot");
+        }
+    };
+    let loaded = loaded.read().await;
     (
         StatusCode::OK,
-        Json(serde_json::json!({ "deleted": deleted })),
+        Json(serde_json::json!({
+            "collection": loaded.meta,
+            "points": loaded.points.len() as u64,
+        })),
     )
         .into_response()
 }
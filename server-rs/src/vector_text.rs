@@ -0,0 +1,141 @@
+use std::collections::{HashMap, HashSet};
+
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{ac00}'..='\u{d7af}'
+            | '\u{f900}'..='\u{faff}'
+    )
+}
+
+fn is_joiner(c: char) -> bool {
+    matches!(c, '-' | '_' | '.' | '/')
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    let trimmed = word.trim_matches(is_joiner);
+    if !trimmed.is_empty() {
+        tokens.push(trimmed.to_string());
+        if trimmed.contains(is_joiner) {
+            tokens.extend(
+                trimmed
+                    .split(is_joiner)
+                    .filter(|part| !part.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+    word.clear();
+}
+
+fn flush_cjk(run: &mut Vec<char>, tokens: &mut Vec<String>) {
+    match run.len() {
+        0 => {}
+        1 => tokens.push(run[0].to_string()),
+        _ => tokens.extend(run.windows(2).map(|pair| pair.iter().collect())),
+    }
+    run.clear();
+}
+
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk = Vec::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(c);
+        } else if c.is_alphanumeric() || is_joiner(c) {
+            flush_cjk(&mut cjk, &mut tokens);
+            word.push(c);
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+    tokens
+}
+
+#[derive(Default)]
+pub(crate) struct TextIndex {
+    postings: HashMap<String, HashMap<String, u32>>,
+    doc_lens: HashMap<String, u32>,
+    total_len: u64,
+}
+
+impl TextIndex {
+    pub(crate) fn insert(&mut self, id: &str, text: &str) {
+        let tokens = tokenize(text);
+        if tokens.is_empty() {
+            return;
+        }
+        self.doc_lens.insert(id.to_string(), tokens.len() as u32);
+        self.total_len += tokens.len() as u64;
+        for token in tokens {
+            *self
+                .postings
+                .entry(token)
+                .or_default()
+                .entry(id.to_string())
+                .or_default() += 1;
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: &str, text: &str) {
+        let Some(len) = self.doc_lens.remove(id) else {
+            return;
+        };
+        self.total_len -= len as u64;
+        for token in tokenize(text).into_iter().collect::<HashSet<_>>() {
+            if let Some(docs) = self.postings.get_mut(&token) {
+                docs.remove(id);
+                if docs.is_empty() {
+                    self.postings.remove(&token);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn search(
+        &self,
+        query: &str,
+        k: usize,
+        accept: impl Fn(&str) -> bool,
+    ) -> Vec<(String, f32)> {
+        let n = self.doc_lens.len() as f32;
+        if n == 0.0 || k == 0 {
+            return Vec::new();
+        }
+        let avg_len = self.total_len as f32 / n;
+        let mut scores = HashMap::<&str, f32>::new();
+        for token in tokenize(query).into_iter().collect::<HashSet<_>>() {
+            let Some(docs) = self.postings.get(&token) else {
+                continue;
+            };
+            let df = docs.len() as f32;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            for (id, &tf) in docs {
+                let tf = tf as f32;
+                let len = self.doc_lens.get(id).copied().unwrap_or(0) as f32;
+                let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len);
+                *scores.entry(id.as_str()).or_default() += idf * tf * (BM25_K1 + 1.0) / (tf + norm);
+            }
+        }
+        let mut ranked = scores.into_iter().collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .filter(|(id, _)| accept(id))
+            .take(k)
+            .map(|(id, score)| (id.to_string(), score))
+            .collect()
+    }
+}
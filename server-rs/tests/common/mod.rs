@@ -0,0 +1,169 @@
+//! Helpers shared by the integration tests. Each test binary compiles this
+//! module separately and uses only part of it.
+#![allow(dead_code)]
+
+use std::io::{Read as _, Write as _};
+use std::net::TcpListener;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt as _;
+use tempfile::tempdir;
+use tower::ServiceExt as _;
+
+pub const DATA_KEY: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+pub fn setup() -> (tempfile::TempDir, axum::Router) {
+    std::env::set_var("DATA_KEY", DATA_KEY);
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    (dir, app)
+}
+
+pub async fn send(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    let body = match body {
+        Some(v) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(v.to_string())
+        }
+        None => Body::empty(),
+    };
+    let response = app
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+pub async fn wait_for_job(app: &axum::Router, job_id: &str) -> serde_json::Value {
+    for _ in 0..500 {
+        let (status, job) = send(app, "GET", &format!("/api/jobs/{job_id}"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        if job["status"] != "running" {
+            return job;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    panic!("job {job_id} did not finish");
+}
+
+/// Starts an experiment job, waits for it and returns the finished record.
+pub async fn run_experiment(
+    app: &axum::Router,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let (status, json) = send(app, "POST", "/api/experiments", Some(body)).await;
+    if status != StatusCode::ACCEPTED {
+        return (status, json);
+    }
+    assert_eq!(json["job"]["jobType"], "experiment");
+    let job = wait_for_job(app, json["job"]["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    let exp_id = json["experimentId"].as_str().unwrap();
+    assert_eq!(job["stats"]["experimentId"], exp_id);
+    send(app, "GET", &format!("/api/experiments/{exp_id}"), None).await
+}
+
+/// One request received by a mock server. `head` is the lower-cased request
+/// line and headers; `body` is `Null` when it is not JSON.
+pub struct MockRequest {
+    pub path: String,
+    pub head: String,
+    pub body: serde_json::Value,
+}
+
+/// Serves OpenAI-style JSON replies from `handler` on a local port for the
+/// rest of the test and returns its base URL (`http://127.0.0.1:<port>`).
+pub fn spawn_mock_openai(
+    handler: impl Fn(&MockRequest) -> serde_json::Value + Send + 'static,
+) -> String {
+    spawn_mock_openai_with_status(move |req| (StatusCode::OK, handler(req)))
+}
+
+/// Like [`spawn_mock_openai`], but `handler` also picks the response status.
+pub fn spawn_mock_openai_with_status(
+    handler: impl Fn(&MockRequest) -> (StatusCode, serde_json::Value) + Send + 'static,
+) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                break;
+            };
+            let mut buf = Vec::new();
+            let mut tmp = [0u8; 4096];
+            let body_start = loop {
+                let n = stream.read(&mut tmp).unwrap_or(0);
+                if n == 0 {
+                    break None;
+                }
+                buf.extend_from_slice(&tmp[..n]);
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break Some(pos + 4);
+                }
+            };
+            let Some(body_start) = body_start else {
+                continue;
+            };
+            let head = String::from_utf8_lossy(&buf[..body_start]).to_ascii_lowercase();
+            let len = head
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            while buf.len() < body_start + len {
+                let n = stream.read(&mut tmp).unwrap_or(0);
+                if n == 0 {
+                    break;
+                }
+                buf.extend_from_slice(&tmp[..n]);
+            }
+            let request = MockRequest {
+                path: head
+                    .lines()
+                    .next()
+                    .and_then(|l| l.split_whitespace().nth(1))
+                    .unwrap_or("")
+                    .to_string(),
+                body: serde_json::from_slice(&buf[body_start..]).unwrap_or(serde_json::Value::Null),
+                head,
+            };
+            let (status, out) = handler(&request);
+            let out = out.to_string();
+            let resp = format!(
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{}",
+                out.len(),
+                out
+            );
+            let _ = stream.write_all(resp.as_bytes());
+        }
+    });
+    format!("http://{}", addr)
+}
+
+/// The text of the last chat message in an OpenAI request body.
+pub fn last_message(body: &serde_json::Value) -> String {
+    body["messages"]
+        .as_array()
+        .and_then(|m| m.last())
+        .and_then(|m| m["content"].as_str())
+        .unwrap_or("")
+        .to_string()
+}
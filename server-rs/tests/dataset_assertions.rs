@@ -0,0 +1,227 @@
+use axum::http::StatusCode;
+use base64::Engine as _;
+use sha2::Digest as _;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{send, spawn_mock_openai};
+
+fn serve_mock_openai(content: &'static str) -> String {
+    spawn_mock_openai(move |_| {
+        serde_json::json!({
+            "choices": [ { "message": { "role": "assistant", "content": content } } ],
+            "usage": { "prompt_tokens": 5, "completion_tokens": 30, "total_tokens": 35 }
+        })
+    })
+}
+
+fn digest(text: &str) -> String {
+    base64::engine::general_purpose::STANDARD.encode(sha2::Sha256::digest(text.as_bytes()))
+}
+
+#[tokio::test]
+async fn replay_evaluates_row_expectations() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Assert Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": "Q: {{q}}" } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "Checks",
+            "rows": [
+                { "q": "hello", "_expect": {
+                    "contains": "hello", "regex": ["^--- Input ---", "Q: \\w+"],
+                    "notContains": "bye", "maxTokens": 50 } },
+                { "q": "bye", "_expect": { "notContains": ["bye"] } },
+                { "q": "x", "_expect": { "digest": digest("--- Input ---\nQ: x") } },
+                { "q": "y", "_expect": { "regex": "(" } },
+                { "q": "z" }
+            ]
+        })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": project_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let statuses = summaries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["status"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        vec!["passed", "failed", "passed", "failed", "succeeded"]
+    );
+
+    let run_id = summaries[0]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    let assertions = run["assertions"].as_array().unwrap();
+    assert_eq!(assertions.len(), 5);
+    assert!(assertions.iter().all(|a| a["passed"] == true));
+    assert_eq!(assertions[4]["kind"], "maxTokens");
+    assert_eq!(assertions[4]["message"], "tokens=6 source=estimate");
+
+    let run_id = summaries[1]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(run["assertions"][0]["kind"], "notContains");
+    assert_eq!(run["assertions"][0]["passed"], false);
+
+    let run_id = summaries[3]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert!(run["assertions"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid_regex"));
+
+    let run_id = summaries[4]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(run["assertions"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn expectations_check_model_output_when_executed() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let base_url = serve_mock_openai("```json\n{\"answer\": 42}\n```");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": base_url,
+            "apiKey": "sk-test"
+        })),
+    )
+    .await;
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Schema Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": "Answer {{q}} as JSON" } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let schema = serde_json::json!({
+        "type": "object",
+        "required": ["answer"],
+        "properties": { "answer": { "type": "integer" } }
+    });
+    let strict_schema = serde_json::json!({
+        "type": "object",
+        "required": ["answer", "reason"]
+    });
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "Schema",
+            "rows": [
+                { "q": "6*7", "_expect": { "jsonSchema": schema, "contains": "42" } },
+                { "q": "6*7", "_expect": { "jsonSchema": strict_schema } },
+                { "q": "6*7", "_expect": { "maxTokens": 10 } },
+                { "q": "6*7", "_expect": { "digest": digest("```json\n{\"answer\": 42}\n```") } },
+                { "q": "6*7", "_expect": { "digest": digest("--- Input ---\nAnswer 6*7 as JSON") } }
+            ]
+        })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({
+            "projectId": project["id"],
+            "execute": true,
+            "providerId": provider["id"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summaries[0]["status"], "passed");
+    assert_eq!(summaries[1]["status"], "failed");
+    assert_eq!(summaries[2]["status"], "failed");
+    assert_eq!(summaries[3]["status"], "passed");
+    assert_eq!(summaries[4]["status"], "failed");
+
+    let run_id = summaries[1]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(run["assertions"][0]["kind"], "jsonSchema");
+    assert!(run["assertions"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("reason"));
+
+    let run_id = summaries[2]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(run["assertions"][0]["message"], "tokens=30 source=usage");
+
+    let run_id = summaries[4]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(run["assertions"][0]["kind"], "digest");
+    assert_eq!(
+        run["assertions"][0]["message"],
+        format!("actual={}", digest("```json\n{\"answer\": 42}\n```"))
+    );
+    assert_eq!(
+        run["outputDigest"],
+        digest("--- Input ---\nAnswer 6*7 as JSON")
+    );
+}
@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{last_message, send, spawn_mock_openai_with_status};
+
+fn serve_mock_openai() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let url = spawn_mock_openai_with_status(move |req| {
+        let last = last_message(&req.body);
+        seen2.lock().unwrap().push(req.body.clone());
+        if last.contains("boom") {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({ "error": "boom" }),
+            );
+        }
+        (
+            StatusCode::OK,
+            serde_json::json!({
+                "model": "mock-chat",
+                "choices": [ { "message": { "role": "assistant", "content": format!("echo: {last}") } } ],
+                "usage": { "prompt_tokens": 7, "completion_tokens": 3, "total_tokens": 10 }
+            }),
+        )
+    });
+    (url, seen)
+}
+
+#[tokio::test]
+async fn replay_can_execute_rendered_prompts_against_provider() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let (base_url, seen) = serve_mock_openai();
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": base_url,
+            "apiKey": "sk-test",
+            "defaultChatModel": "mock-default"
+        })),
+    )
+    .await;
+    let provider_id = provider["id"].as_str().unwrap().to_string();
+
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Exec Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "System", "type": "system_prompt", "content": "Be brief." } },
+                    { "id": "n2", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": "Say {{word}}" } }
+                ],
+                "edges": [ { "id": "e1", "source": "n1", "target": "n2" } ],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "Words",
+            "rows": [ { "word": "hello" }, { "word": "boom" } ]
+        })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": project_id, "execute": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "validation_failed");
+
+    let (status, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({
+            "projectId": project_id,
+            "execute": true,
+            "providerId": provider_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summaries[0]["status"], "succeeded");
+    assert_eq!(summaries[1]["status"], "failed");
+
+    let requests = seen.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["model"], "mock-default");
+    assert_eq!(
+        requests[0]["messages"],
+        serde_json::json!([
+            { "role": "system", "content": "Be brief." },
+            { "role": "user", "content": "Say hello" }
+        ])
+    );
+
+    let run_id = summaries[0]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    let execution = &run["execution"];
+    assert_eq!(execution["providerId"], provider_id.as_str());
+    assert_eq!(execution["model"], "mock-chat");
+    assert_eq!(execution["completion"], "echo: Say hello");
+    assert_eq!(execution["usage"]["totalTokens"], 10);
+    assert!(execution["latencyMs"].is_u64());
+    assert!(execution["error"].is_null());
+
+    let run_id = summaries[1]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert!(run["execution"]["completion"].is_null());
+    assert!(run["execution"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("status=500"));
+
+    let (_, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": project_id, "limit": 1 })),
+    )
+    .await;
+    let run_id = summaries[0]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert!(run["execution"].is_null());
+    assert_eq!(seen.lock().unwrap().len(), 2);
+}
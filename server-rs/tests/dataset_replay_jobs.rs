@@ -0,0 +1,127 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{send, wait_for_job};
+
+#[tokio::test]
+async fn replay_job_tracks_progress_cancels_and_resumes() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Replay Job Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "User", "type": "user_input", "content": "Hi {{name}}" } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+
+    let rows = (0..300)
+        .map(|i| serde_json::json!({ "name": format!("user{i}") }))
+        .collect::<Vec<_>>();
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({ "name": "Big", "rows": rows })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay-jobs"),
+        Some(serde_json::json!({ "projectId": project_id, "offset": 250 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(json["job"]["jobType"], "dataset_replay");
+    assert_eq!(json["job"]["stats"]["total"], 50);
+    let job = wait_for_job(&app, json["job"]["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["stats"]["processed"], 50);
+    assert_eq!(job["stats"]["nextRowIndex"], 300);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay-jobs"),
+        Some(serde_json::json!({ "projectId": project_id, "limit": 250 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(json["job"]["stats"]["total"], 250);
+    let job_id = json["job"]["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(&app, "POST", &format!("/api/jobs/{job_id}/cancel"), None).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(json["cancelRequested"], true);
+
+    let job = wait_for_job(&app, &job_id).await;
+    assert_eq!(job["status"], "cancelled");
+    let processed = job["stats"]["processed"].as_u64().unwrap();
+    assert!(processed < 250);
+    assert_eq!(job["stats"]["nextRowIndex"], processed);
+
+    let (status, _) = send(&app, "POST", &format!("/api/jobs/{job_id}/cancel"), None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(&app, "POST", &format!("/api/jobs/{job_id}/resume"), None).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let job = wait_for_job(&app, &job_id).await;
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["stats"]["processed"], 250);
+    assert_eq!(job["stats"]["succeeded"], 250);
+    assert_eq!(job["stats"]["nextRowIndex"], 250);
+
+    let (status, _) = send(&app, "POST", &format!("/api/jobs/{job_id}/resume"), None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (_, runs) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs"),
+        None,
+    )
+    .await;
+    let runs = runs.as_array().unwrap();
+    assert_eq!(runs.len(), 300);
+    let mut indexes = runs
+        .iter()
+        .map(|r| r["rowIndex"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    indexes.sort();
+    indexes.dedup();
+    assert_eq!(indexes.len(), 300);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay-jobs"),
+        Some(serde_json::json!({ "projectId": "proj_missing" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "project_not_found");
+}
@@ -0,0 +1,287 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{last_message, run_experiment, send, spawn_mock_openai, wait_for_job};
+
+fn serve_mock_judge() -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls2 = calls.clone();
+    let url = spawn_mock_openai(move |req| {
+        calls2.fetch_add(1, Ordering::SeqCst);
+        let content = if last_message(&req.body).contains("Please") {
+            "```json\n{\"score\": 5, \"label\": \"polite\"}\n```"
+        } else {
+            "{\"score\": 2}"
+        };
+        serde_json::json!({
+            "choices": [ { "message": { "role": "assistant", "content": content } } ]
+        })
+    });
+    (url, calls)
+}
+
+async fn create_project(app: &axum::Router, name: &str, content: &str) -> String {
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let (_, project) = send(
+        app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": name,
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": content } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    project["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn evaluators_score_runs_with_cache_and_aggregate() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let (base_url, calls) = serve_mock_judge();
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "judge",
+            "provider": "siliconflow",
+            "baseUrl": base_url,
+            "apiKey": "sk-test",
+            "defaultChatModel": "judge-model"
+        })),
+    )
+    .await;
+    let provider_id = provider["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/evaluators",
+        Some(serde_json::json!({
+            "name": "broken",
+            "prompt": "Rate it.",
+            "providerId": provider_id,
+            "scoreSchema": { "type": 12 }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "invalid_schema");
+
+    let (status, politeness) = send(
+        &app,
+        "POST",
+        "/api/evaluators",
+        Some(serde_json::json!({
+            "name": "politeness",
+            "prompt": "Rate how polite the output is.",
+            "rubric": "5 = very polite, 1 = rude",
+            "providerId": provider_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        politeness["scoreSchema"]["required"],
+        serde_json::json!(["score"])
+    );
+    let politeness_id = politeness["id"].as_str().unwrap().to_string();
+
+    let (_, strict) = send(
+        &app,
+        "POST",
+        "/api/evaluators",
+        Some(serde_json::json!({
+            "name": "strict",
+            "prompt": "Rate it with a reason.",
+            "providerId": provider_id,
+            "scoreSchema": { "type": "object", "required": ["score", "reason"] }
+        })),
+    )
+    .await;
+    let strict_id = strict["id"].as_str().unwrap().to_string();
+
+    let (_, list) = send(&app, "GET", "/api/evaluators", None).await;
+    assert_eq!(list.as_array().unwrap().len(), 2);
+
+    let baseline_id = create_project(&app, "Baseline", "Say {{w}}").await;
+    let candidate_id = create_project(&app, "Candidate", "Please say {{w}}").await;
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({ "name": "Words", "rows": [ { "w": "hi" }, { "w": "yo" } ] })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (_, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": baseline_id, "limit": 1 })),
+    )
+    .await;
+    let run_id = summaries[0]["runId"].as_str().unwrap().to_string();
+    let evaluate = serde_json::json!({ "evaluatorIds": [politeness_id] });
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/runs/{run_id}/evaluate"),
+        Some(evaluate.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let score = &json["scores"][0];
+    assert_eq!(score["evaluatorId"], politeness_id.as_str());
+    assert_eq!(score["model"], "judge-model");
+    assert_eq!(score["score"]["score"], 2);
+    assert_eq!(score["valid"], true);
+    assert_eq!(score["cached"], false);
+    let digest = score["inputDigest"].clone();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (_, json) = send(
+        &app,
+        "POST",
+        &format!("/api/runs/{run_id}/evaluate"),
+        Some(evaluate.clone()),
+    )
+    .await;
+    assert_eq!(json["scores"].as_array().unwrap().len(), 1);
+    assert_eq!(json["scores"][0]["cached"], true);
+    assert_eq!(json["scores"][0]["inputDigest"], digest);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let (_, json) = send(
+        &app,
+        "POST",
+        &format!("/api/runs/{run_id}/evaluate"),
+        Some(serde_json::json!({ "evaluatorIds": [politeness_id], "force": true })),
+    )
+    .await;
+    assert_eq!(json["scores"][0]["cached"], false);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(run["scores"][0]["score"]["score"], 2);
+
+    let (_, exp) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "projectId": baseline_id },
+            "candidate": { "projectId": candidate_id }
+        }),
+    )
+    .await;
+    let exp_id = exp["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/experiments/{exp_id}/evaluate"),
+        Some(serde_json::json!({ "evaluatorIds": [politeness_id, strict_id] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{json}");
+    assert_eq!(json["job"]["jobType"], "evaluate_experiment");
+    let job = wait_for_job(&app, json["job"]["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["stats"]["evaluated"], 4);
+    let (status, scores) = send(
+        &app,
+        "GET",
+        &format!("/api/experiments/{exp_id}/scores"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let baseline = &scores["baseline"][politeness_id.as_str()];
+    assert_eq!(baseline["count"], 2);
+    assert_eq!(baseline["fields"]["score"]["mean"], 2.0);
+    let candidate = &scores["candidate"][politeness_id.as_str()];
+    assert_eq!(candidate["fields"]["score"]["mean"], 5.0);
+    assert_eq!(candidate["fields"]["score"]["max"], 5.0);
+    let strict_candidate = &scores["candidate"][strict_id.as_str()];
+    assert_eq!(strict_candidate["count"], 2);
+    assert_eq!(strict_candidate["valid"], 0);
+    assert_eq!(calls.load(Ordering::SeqCst), 9);
+
+    let (_, again) = send(
+        &app,
+        "GET",
+        &format!("/api/experiments/{exp_id}/scores"),
+        None,
+    )
+    .await;
+    assert_eq!(again, scores);
+
+    let (status, by_dataset) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/scores?projectId={candidate_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(by_dataset["runs"], 2);
+    assert_eq!(
+        by_dataset["evaluators"][politeness_id.as_str()]["fields"]["score"]["mean"],
+        5.0
+    );
+
+    let (_, by_dataset) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/scores"),
+        None,
+    )
+    .await;
+    assert_eq!(by_dataset["runs"], 5);
+    assert_eq!(by_dataset["evaluators"][politeness_id.as_str()]["count"], 5);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/runs/{run_id}/evaluate"),
+        Some(serde_json::json!({ "evaluatorIds": ["eval_missing"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "evaluator_not_found");
+
+    let (status, _) = send(
+        &app,
+        "DELETE",
+        &format!("/api/evaluators/{strict_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
@@ -0,0 +1,236 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{last_message, run_experiment, send, spawn_mock_openai};
+
+fn serve_mock_openai() -> String {
+    spawn_mock_openai(|req| {
+        let first = req.body["messages"][0]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+        let last = last_message(&req.body);
+        let content = if first.contains("decide which one is better") {
+            let response_a = last
+                .split("Response A:")
+                .nth(1)
+                .and_then(|rest| rest.split("Prompt B:").next())
+                .unwrap_or("");
+            let winner = if response_a.contains("Please") {
+                "A"
+            } else {
+                "B"
+            };
+            format!("```json\n{{\"winner\": \"{winner}\", \"reason\": \"more polite\"}}\n```")
+        } else {
+            format!("echo: {last}")
+        };
+        let tokens = last.len();
+        serde_json::json!({
+            "choices": [ { "message": { "role": "assistant", "content": content } } ],
+            "usage": { "prompt_tokens": tokens, "completion_tokens": 0, "total_tokens": tokens }
+        })
+    })
+}
+
+async fn create_project(app: &axum::Router, name: &str, content: &str) -> String {
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let (_, project) = send(
+        app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": name,
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": content } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    project["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn experiment_compares_two_projects_row_by_row() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let base_url = serve_mock_openai();
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let baseline_id = create_project(&app, "Baseline", "Say {{w}}").await;
+    let candidate_id = create_project(&app, "Candidate", "Please say {{w}}").await;
+    let same_id = create_project(&app, "Same", "Say {{w}}").await;
+
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "Words",
+            "rows": [
+                { "w": "hi", "_expect": { "contains": "Please" } },
+                { "w": "yo", "_expect": { "notContains": "Please" } },
+                { "w": "ok" }
+            ]
+        })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, exp) = run_experiment(
+        &app,
+        serde_json::json!({
+            "name": "politeness",
+            "datasetId": dataset_id,
+            "baseline": { "projectId": baseline_id },
+            "candidate": { "label": "polite", "projectId": candidate_id }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(exp["id"].as_str().unwrap().starts_with("exp_"));
+    assert_eq!(exp["baseline"]["label"], "baseline");
+    assert_eq!(exp["status"], "succeeded");
+    let rows = exp["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().all(|r| r["digestChanged"] == true));
+    assert_ne!(rows[0]["baselineRunId"], rows[0]["candidateRunId"]);
+    assert_eq!(rows[0]["baselineStatus"], "failed");
+    assert_eq!(rows[0]["candidateStatus"], "passed");
+    assert_eq!(rows[0]["outcome"], "win");
+    assert_eq!(rows[1]["outcome"], "loss");
+    assert_eq!(rows[1]["candidateAssertions"]["failed"], 1);
+    assert_eq!(rows[2]["outcome"], "tie");
+    assert!(rows[2]["tokenDelta"].is_null());
+
+    let run_id = rows[0]["candidateRunId"].as_str().unwrap();
+    let (status, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(run["projectId"], candidate_id.as_str());
+
+    let exp_id = exp["id"].as_str().unwrap().to_string();
+    let (status, summary) = send(
+        &app,
+        "GET",
+        &format!("/api/experiments/{exp_id}/summary"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["rows"], 3);
+    assert_eq!(summary["wins"], 1);
+    assert_eq!(summary["losses"], 1);
+    assert_eq!(summary["ties"], 1);
+    assert_eq!(summary["digestChanged"], 3);
+    assert_eq!(summary["judged"], 0);
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": base_url,
+            "apiKey": "sk-test"
+        })),
+    )
+    .await;
+    let provider_id = provider["id"].as_str().unwrap().to_string();
+
+    let (status, json) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "projectId": baseline_id },
+            "candidate": { "projectId": candidate_id },
+            "judge": { "providerId": provider_id }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "validation_failed");
+
+    let (status, exp) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "projectId": baseline_id },
+            "candidate": { "projectId": candidate_id },
+            "limit": 2,
+            "execute": true,
+            "providerId": provider_id,
+            "judge": { "providerId": provider_id, "rubric": "Prefer polite answers." }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = exp["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["tokenDelta"], 7);
+    assert_eq!(rows[0]["judge"]["winner"], "candidate");
+    assert_eq!(rows[0]["judge"]["reason"], "more polite");
+    assert_eq!(rows[0]["judge"]["swapped"], false);
+    assert_eq!(rows[1]["judge"]["swapped"], true);
+    assert_eq!(rows[1]["judge"]["winner"], "candidate");
+    assert_eq!(rows[1]["outcome"], "win");
+
+    let exp_id = exp["id"].as_str().unwrap().to_string();
+    let (_, summary) = send(
+        &app,
+        "GET",
+        &format!("/api/experiments/{exp_id}/summary"),
+        None,
+    )
+    .await;
+    assert_eq!(summary["wins"], 2);
+    assert_eq!(summary["judged"], 2);
+    assert_eq!(summary["tokenDelta"], 14);
+
+    let (status, exp) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "projectId": baseline_id },
+            "candidate": { "projectId": same_id }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(exp["rows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|r| r["digestChanged"] == false && r["outcome"] == "tie"));
+
+    let (_, list) = send(&app, "GET", "/api/experiments", None).await;
+    assert_eq!(list.as_array().unwrap().len(), 3);
+
+    let (status, json) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "projectId": baseline_id },
+            "candidate": { "projectId": "proj_missing" }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "project_not_found");
+
+    let (status, _) = send(&app, "GET", "/api/experiments/exp_missing", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
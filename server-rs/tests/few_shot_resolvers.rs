@@ -0,0 +1,183 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt as _;
+use tempfile::tempdir;
+use tower::ServiceExt as _;
+
+async fn post_json(app: &axum::Router, uri: &str, body: serde_json::Value) -> serde_json::Value {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(
+        response.status().is_success(),
+        "{uri}: {}",
+        response.status()
+    );
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+async fn preview(
+    app: &axum::Router,
+    resolver: String,
+    value: serde_json::Value,
+) -> serde_json::Value {
+    let value = match value {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    };
+    post_json(
+        app,
+        "/api/preview",
+        serde_json::json!({
+            "nodes": [
+                { "id": "n1", "label": "Examples", "kind": "system", "content": "{{examples}}" }
+            ],
+            "variables": [
+                { "id": "v1", "name": "examples", "type": "dynamic", "value": value, "resolver": resolver }
+            ],
+            "outputStyle": "plain"
+        }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn dataset_and_run_resolvers_render_few_shot_blocks() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let dataset = post_json(
+        &app,
+        "/api/datasets",
+        serde_json::json!({
+            "name": "Sentiment",
+            "rows": [
+                { "text": "great", "label": "pos", "lang": "en" },
+                { "text": "awful", "label": "neg", "lang": "en" },
+                { "text": "fine", "label": "pos", "lang": "en" },
+                { "text": "bad", "label": "neg", "lang": "de" },
+                { "text": "nice", "label": "pos", "lang": "en", "_note": "hidden" }
+            ]
+        }),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let json = preview(
+        &app,
+        format!("dataset://{dataset_id}"),
+        serde_json::json!("2"),
+    )
+    .await;
+    assert_eq!(
+        json["text"],
+        "label: pos\nlang: en\ntext: great\n\nlabel: neg\nlang: en\ntext: awful"
+    );
+    assert_eq!(json["messages"][0]["code"], "variable_resolved");
+    assert_eq!(json["messages"][0]["details"]["debug"]["strategy"], "first");
+
+    let json = preview(
+        &app,
+        format!("dataset://{dataset_id}"),
+        serde_json::json!({
+            "filter": { "lang": "en" },
+            "stratifyBy": "label",
+            "n": 3,
+            "template": "{{_index}}. {{text}} => {{label}}",
+            "separator": "\n"
+        }),
+    )
+    .await;
+    assert_eq!(
+        json["text"],
+        "1. awful => neg\n2. great => pos\n3. fine => pos"
+    );
+    let debug = &json["messages"][0]["details"]["debug"];
+    assert_eq!(debug["strategy"], "stratified");
+    assert_eq!(debug["matchedRows"], 4);
+    assert_eq!(debug["rowIndexes"], serde_json::json!([1, 0, 2]));
+
+    let random_spec =
+        serde_json::json!({ "sample": "random", "seed": 7, "n": 3, "fields": ["text"] });
+    let a = preview(&app, format!("dataset://{dataset_id}"), random_spec.clone()).await;
+    let b = preview(&app, format!("dataset://{dataset_id}"), random_spec).await;
+    assert_eq!(a["text"], b["text"]);
+    assert_eq!(a["messages"][0]["details"]["debug"]["seed"], 7);
+    assert_eq!(a["text"].as_str().unwrap().lines().count(), 5);
+
+    let json = preview(
+        &app,
+        "dataset://dsset_missing".to_string(),
+        serde_json::json!(""),
+    )
+    .await;
+    assert_eq!(json["text"], "[examples]");
+    assert_eq!(json["messages"][0]["details"]["errorCode"], "not_found");
+
+    let project = post_json(
+        &app,
+        "/api/projects",
+        serde_json::json!({
+            "name": "Source Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "System", "type": "system_prompt", "content": "Classify." } },
+                    { "id": "n2", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": "Text: {{text}}" } }
+                ],
+                "edges": [ { "id": "e1", "source": "n1", "target": "n2" } ],
+                "variables": []
+            }
+        }),
+    )
+    .await;
+    let summaries = post_json(
+        &app,
+        &format!("/api/datasets/{dataset_id}/replay"),
+        serde_json::json!({ "projectId": project["id"], "limit": 1 }),
+    )
+    .await;
+    let run_id = summaries[0]["runId"].as_str().unwrap().to_string();
+
+    let json = preview(&app, format!("run://{run_id}"), serde_json::json!("n2")).await;
+    assert_eq!(json["text"], "--- Input ---\nText: great");
+
+    let json = preview(&app, format!("run://{run_id}"), serde_json::json!("Nope")).await;
+    assert_eq!(
+        json["messages"][0]["details"]["errorCode"],
+        "segment_not_found"
+    );
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/api/runs/{run_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,252 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+fn app() -> (tempfile::TempDir, axum::Router) {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    (dir, app)
+}
+
+fn node(id: &str) -> serde_json::Value {
+    serde_json::json!({ "id": id, "label": id, "kind": "text", "content": id })
+}
+
+fn segment_ids(trace: &serde_json::Value) -> Vec<String> {
+    trace["segments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["nodeId"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn message<'a>(trace: &'a serde_json::Value, code: &str) -> &'a serde_json::Value {
+    trace["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["code"] == code)
+        .unwrap_or_else(|| panic!("missing {code} in {trace}"))
+}
+
+#[tokio::test]
+async fn execute_and_preview_share_edge_ordering() {
+    let (_dir, app) = app();
+
+    let body = serde_json::json!({
+        "nodes": [ node("c"), node("b"), node("a"), node("loose") ],
+        "edges": [
+            { "source": "a", "target": "b" },
+            { "source": "b", "target": "c" }
+        ],
+        "variables": [],
+        "outputStyle": "plain"
+    });
+    let (status, executed) = send(&app, "POST", "/api/execute", Some(body.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(segment_ids(&executed), vec!["a", "b", "c", "loose"]);
+    let disconnected = message(&executed, "node_disconnected");
+    assert_eq!(disconnected["severity"], "warn");
+    assert_eq!(
+        disconnected["details"]["nodeIds"],
+        serde_json::json!(["loose"])
+    );
+
+    let (status, previewed) = send(&app, "POST", "/api/preview", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(segment_ids(&previewed), segment_ids(&executed));
+    assert_eq!(previewed["text"], executed["text"]);
+
+    let (_, unlinked) = send(
+        &app,
+        "POST",
+        "/api/execute",
+        Some(serde_json::json!({
+            "nodes": [ node("c"), node("b"), node("a") ],
+            "variables": [],
+            "outputStyle": "plain"
+        })),
+    )
+    .await;
+    assert_eq!(segment_ids(&unlinked), vec!["c", "b", "a"]);
+    assert!(unlinked["messages"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn execute_reports_cycles_disabled_and_isolated_policy() {
+    let (_dir, app) = app();
+
+    let mut disabled = node("off");
+    disabled["disabled"] = serde_json::json!(true);
+    let (status, trace) = send(
+        &app,
+        "POST",
+        "/api/execute",
+        Some(serde_json::json!({
+            "nodes": [ node("root"), node("x"), node("y"), node("after"), disabled, node("tail") ],
+            "edges": [
+                { "source": "root", "target": "off" },
+                { "source": "off", "target": "tail" },
+                { "source": "x", "target": "y" },
+                { "source": "y", "target": "x" },
+                { "source": "y", "target": "after" },
+                { "source": "tail", "target": "ghost" }
+            ],
+            "variables": [],
+            "outputStyle": "plain"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(segment_ids(&trace), vec!["root", "tail"]);
+    let cycle = message(&trace, "cycle_detected");
+    assert_eq!(cycle["severity"], "error");
+    assert_eq!(cycle["details"]["nodeIds"], serde_json::json!(["x", "y"]));
+    assert_eq!(
+        message(&trace, "node_blocked")["details"]["nodeIds"],
+        serde_json::json!(["after"])
+    );
+    assert_eq!(
+        message(&trace, "node_disabled")["details"]["nodeIds"],
+        serde_json::json!(["off"])
+    );
+    assert_eq!(
+        message(&trace, "edge_dangling")["details"]["edges"],
+        serde_json::json!(["tail->ghost"])
+    );
+
+    for (policy, severity, ids) in [
+        ("skip", "info", vec!["a", "b"]),
+        ("error", "error", vec!["a", "b"]),
+        ("include", "warn", vec!["a", "b", "loose"]),
+    ] {
+        let (_, trace) = send(
+            &app,
+            "POST",
+            "/api/execute",
+            Some(serde_json::json!({
+                "nodes": [ node("a"), node("b"), node("loose") ],
+                "edges": [ { "source": "a", "target": "b" } ],
+                "isolatedNodePolicy": policy,
+                "variables": [],
+                "outputStyle": "plain"
+            })),
+        )
+        .await;
+        assert_eq!(segment_ids(&trace), ids);
+        let disconnected = message(&trace, "node_disconnected");
+        assert_eq!(disconnected["severity"], severity);
+        assert_eq!(disconnected["details"]["policy"], policy);
+    }
+}
+
+#[tokio::test]
+async fn replay_orders_by_edges_and_fails_on_cycles() {
+    let (_dir, app) = app();
+
+    let flow_node = |id: &str, content: &str| {
+        serde_json::json!({
+            "id": id, "type": "contextNode", "position": { "x": 0, "y": 0 },
+            "data": { "label": id, "type": "user_input", "content": content }
+        })
+    };
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Ordered",
+            "state": {
+                "nodes": [ flow_node("second", "then {{w}}"), flow_node("first", "first"), flow_node("stray", "stray") ],
+                "edges": [ { "source": "first", "target": "second" } ],
+                "variables": [],
+                "isolatedNodePolicy": "skip"
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({ "name": "Words", "rows": [ { "w": "hi" } ] })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": project_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summaries[0]["status"], "succeeded");
+    let run_id = summaries[0]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(segment_ids(&run["trace"]), vec!["first", "second"]);
+    assert_eq!(
+        run["trace"]["text"],
+        "--- first ---\nfirst\n\n--- second ---\nthen hi"
+    );
+    assert_eq!(
+        message(&run["trace"], "node_disconnected")["severity"],
+        "info"
+    );
+
+    let (_, snapshot) = send(
+        &app,
+        "POST",
+        &format!("/api/projects/{project_id}/snapshots"),
+        None,
+    )
+    .await;
+    assert_eq!(snapshot["isolatedNodePolicy"], "skip");
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        &format!("/api/projects/{project_id}"),
+        Some(serde_json::json!({
+            "name": "Ordered",
+            "state": {
+                "nodes": [ flow_node("second", "then {{w}}"), flow_node("first", "first") ],
+                "edges": [
+                    { "source": "first", "target": "second" },
+                    { "source": "second", "target": "first" }
+                ],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let (_, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": project_id })),
+    )
+    .await;
+    assert_eq!(summaries[0]["status"], "failed");
+    let run_id = summaries[0]["runId"].as_str().unwrap();
+    let (_, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert!(run["trace"]["segments"].as_array().unwrap().is_empty());
+    assert_eq!(
+        message(&run["trace"], "cycle_detected")["details"]["nodeIds"],
+        serde_json::json!(["second", "first"])
+    );
+}
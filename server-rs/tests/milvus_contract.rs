@@ -78,3 +78,185 @@ async fn milvus_client_hits_expected_endpoints_and_headers() {
     let req = seen.lock().unwrap().clone();
     assert!(req.starts_with("POST /v2/vectordb/entities/query HTTP/1.1\r\n"));
 }
+
+#[tokio::test]
+async fn milvus_client_hits_collection_management_endpoints() {
+    let cases: [(&str, &str); 7] = [
+        ("create_collection", "/v2/vectordb/collections/create"),
+        ("drop_collection", "/v2/vectordb/collections/drop"),
+        ("describe_collection", "/v2/vectordb/collections/describe"),
+        ("load_collection", "/v2/vectordb/collections/load"),
+        ("release_collection", "/v2/vectordb/collections/release"),
+        ("create_index", "/v2/vectordb/indexes/create"),
+        ("delete_entities", "/v2/vectordb/entities/delete"),
+    ];
+    for (op, path) in cases {
+        let (base_url, seen) = serve_one(r#"{"code":0,"data":{}}"#);
+        let client = MilvusRestClient::new(base_url, Some("t".to_string()));
+        let body = serde_json::json!({ "collectionName": "c", "filter": "id in [1]" });
+        let res = match op {
+            "create_collection" => client.create_collection(body).await,
+            "drop_collection" => client.drop_collection(body).await,
+            "describe_collection" => client.describe_collection(body).await,
+            "load_collection" => client.load_collection(body).await,
+            "release_collection" => client.release_collection(body).await,
+            "create_index" => client.create_index(body).await,
+            _ => client.delete_entities(body).await,
+        };
+        assert_eq!(res.unwrap()["code"], 0, "{op}");
+        let req = seen.lock().unwrap().clone();
+        assert!(
+            req.starts_with(&format!("POST {path} HTTP/1.1\r\n")),
+            "{op}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn milvus_management_routes_respect_permissions() {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt as _;
+    use tower::ServiceExt as _;
+
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let send = |uri: String, body: serde_json::Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(uri)
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            (status, json)
+        }
+    };
+
+    let (base_url, seen) = serve_one(r#"{"code":0,"data":{"deleteCount":2}}"#);
+    let (status, json) = send(
+        "/api/datasources".to_string(),
+        serde_json::json!({
+            "name": "milvus-admin",
+            "driver": "milvus",
+            "url": base_url,
+            "allowSchema": false,
+            "allowDelete": true
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = json["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        format!("/api/datasources/{id}/milvus/collections/create"),
+        serde_json::json!({ "collectionName": "docs", "dimension": 4 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(json["error"], "forbidden");
+
+    let (status, json) = send(
+        format!("/api/datasources/{id}/milvus/delete"),
+        serde_json::json!({ "collectionName": "docs" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "invalid_request");
+
+    let (status, json) = send(
+        format!("/api/datasources/{id}/milvus/delete"),
+        serde_json::json!({ "collectionName": "docs", "filter": "id in [1, 2]" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["deleteCount"], 2);
+    let req = seen.lock().unwrap().clone();
+    assert!(req.starts_with("POST /v2/vectordb/entities/delete HTTP/1.1\r\n"));
+
+    let (status, json) = send(
+        "/api/datasources".to_string(),
+        serde_json::json!({
+            "name": "milvus-schema",
+            "driver": "milvus",
+            "url": "http://127.0.0.1:9"
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = json["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        format!("/api/datasources/{id}/milvus/delete"),
+        serde_json::json!({ "collectionName": "docs", "filter": "id > 0" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(json["error"], "forbidden");
+
+    for path in [
+        "collections/create",
+        "collections/drop",
+        "collections/load",
+        "collections/release",
+        "indexes/create",
+    ] {
+        let (status, json) = send(
+            format!("/api/datasources/{id}/milvus/{path}"),
+            serde_json::json!({ "collectionName": "docs", "dimension": 4 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{path}");
+        assert_eq!(json["error"], "forbidden");
+    }
+
+    let (status, json) = send(
+        "/api/datasources".to_string(),
+        serde_json::json!({
+            "name": "milvus-writer",
+            "driver": "milvus",
+            "url": "http://127.0.0.1:9",
+            "allowWrite": true
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = json["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        format!("/api/datasources/{id}/milvus/collections/drop"),
+        serde_json::json!({ "collectionName": "docs" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(json["error"], "forbidden");
+
+    let (status, json) = send(
+        format!("/api/datasources/{id}/milvus/collections/create"),
+        serde_json::json!({
+            "collectionName": "docs",
+            "schema": { "fields": [ { "fieldName": "id" } ] }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "invalid_request");
+}
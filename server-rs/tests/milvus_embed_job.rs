@@ -0,0 +1,161 @@
+#![cfg(feature = "milvus")]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt as _;
+use tempfile::tempdir;
+use tower::ServiceExt as _;
+
+mod common;
+
+type Handler = Box<dyn Fn(usize, &serde_json::Value) -> serde_json::Value + Send>;
+
+fn serve(handler: Handler) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let calls = AtomicUsize::new(0);
+    let url = common::spawn_mock_openai(move |req| {
+        let out = handler(calls.fetch_add(1, Ordering::SeqCst), &req.body);
+        seen2.lock().unwrap().push(req.body.clone());
+        out
+    });
+    (url, seen)
+}
+
+async fn post_json(
+    app: &axum::Router,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn embed_to_milvus_job_batches_maps_fields_and_reports_partial_failures() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let (embed_url, _) = serve(Box::new(|_, body| {
+        let n = body["input"].as_array().map(|a| a.len()).unwrap_or(0);
+        let data = (0..n)
+            .map(|i| serde_json::json!({ "index": i, "embedding": [i as f64, 1.0] }))
+            .collect::<Vec<_>>();
+        serde_json::json!({ "data": data })
+    }));
+    let (milvus_url, milvus_seen) = serve(Box::new(|i, body| {
+        let n = body["data"].as_array().map(|a| a.len()).unwrap_or(0);
+        if i == 1 {
+            serde_json::json!({ "code": 1100, "message": "field id missing" })
+        } else {
+            serde_json::json!({ "code": 0, "data": { "insertCount": n } })
+        }
+    }));
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, provider) = post_json(
+        &app,
+        "/api/providers",
+        serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": embed_url,
+            "apiKey": "sk-test",
+            "defaultEmbeddingModel": "mock-embed"
+        }),
+    )
+    .await;
+    let (status, ds) = post_json(
+        &app,
+        "/api/datasources",
+        serde_json::json!({
+            "name": "milvus",
+            "driver": "milvus",
+            "url": milvus_url,
+            "allowWrite": true
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, dataset) = post_json(
+        &app,
+        "/api/datasets",
+        serde_json::json!({
+            "name": "docs",
+            "rows": [
+                { "docId": 1, "body": "alpha", "tag": "a" },
+                { "docId": 2, "body": "beta", "tag": "b" },
+                { "docId": 3, "body": "" },
+                { "docId": 4, "body": "gamma", "tag": "c" },
+                { "docId": 5, "body": "delta", "tag": "d" },
+                { "docId": 6, "body": "epsilon", "tag": "e" }
+            ]
+        }),
+    )
+    .await;
+
+    let (status, json) = post_json(
+        &app,
+        "/api/jobs/embed-to-milvus",
+        serde_json::json!({
+            "datasetId": dataset["id"],
+            "providerId": provider["id"],
+            "dataSourceId": ds["id"],
+            "collection": "docs",
+            "textField": "body",
+            "vectorField": "embedding",
+            "fieldMap": { "id": "docId", "text": "body", "category": "tag" },
+            "batchSize": 2
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{json}");
+    assert_eq!(json["job"]["jobType"], "embed_to_milvus");
+    let job = &common::wait_for_job(&app, json["job"]["id"].as_str().unwrap()).await;
+    assert_eq!(job["status"], "partial");
+    assert_eq!(job["stats"]["batches"], 3);
+    assert_eq!(job["stats"]["failedBatches"], 1);
+    assert_eq!(job["stats"]["skipped"], 1);
+    assert_eq!(job["stats"]["inserted"], 3);
+    assert_eq!(job["stats"]["failed"], 2);
+    assert_eq!(job["stats"]["failures"][0]["stage"], "insert");
+    assert_eq!(
+        job["stats"]["failures"][0]["rowIndexes"],
+        serde_json::json!([3, 4])
+    );
+    assert!(job["error"].as_str().unwrap().contains("1100"));
+
+    let seen = milvus_seen.lock().unwrap().clone();
+    assert_eq!(seen.len(), 3);
+    assert_eq!(seen[0]["collectionName"], "docs");
+    let first = &seen[0]["data"][0];
+    assert_eq!(first["id"], 1);
+    assert_eq!(first["text"], "alpha");
+    assert_eq!(first["category"], "a");
+    assert_eq!(first["embedding"], serde_json::json!([0.0, 1.0]));
+}
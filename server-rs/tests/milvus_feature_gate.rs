@@ -1,3 +1,5 @@
+#![cfg(not(feature = "milvus"))]
+
 use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -129,6 +131,59 @@ async fn milvus_datasource_is_feature_gated_but_api_contract_is_stable() {
     let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
     assert_eq!(json["error"], "feature_not_enabled");
 
+    for path in [
+        "collections/create",
+        "collections/drop",
+        "collections/describe",
+        "collections/load",
+        "collections/release",
+        "indexes/create",
+        "delete",
+    ] {
+        let body = serde_json::json!({ "collectionName": "c" }).to_string();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/datasources/{id}/milvus/{path}"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED, "{path}");
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["error"], "feature_not_enabled");
+    }
+
+    let body = serde_json::json!({
+        "datasetId": "ds_x",
+        "providerId": "prov_x",
+        "dataSourceId": id,
+        "collection": "c",
+        "textField": "text"
+    })
+    .to_string();
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/jobs/embed-to-milvus")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["error"], "feature_not_enabled");
+
     let body = serde_json::json!({
         "nodes": [
             { "id": "n1", "label": "System", "kind": "system", "content": "Milvus: {{v}}" }
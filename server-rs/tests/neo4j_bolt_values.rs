@@ -0,0 +1,106 @@
+#![cfg(feature = "neo4j")]
+
+use neo4rs::{BoltNode, BoltPath, BoltType, BoltUnboundedRelation};
+use server_rs::connectors::neo4j::{
+    format_rows, graph_value_from_bolt, GraphRow, GraphValue, Neo4jOutputFormat,
+};
+
+fn person(id: i64, name: &str) -> BoltNode {
+    BoltNode::new(
+        id.into(),
+        vec!["Person".into()].into(),
+        vec![("name".into(), name.into())].into_iter().collect(),
+    )
+}
+
+fn rel(id: i64, typ: &str) -> BoltUnboundedRelation {
+    BoltUnboundedRelation::new(id.into(), typ.into(), Default::default())
+}
+
+fn path(nodes: Vec<BoltNode>, rels: Vec<BoltUnboundedRelation>, indices: &[i64]) -> BoltType {
+    BoltType::Path(BoltPath {
+        nodes: nodes
+            .into_iter()
+            .map(BoltType::Node)
+            .collect::<Vec<_>>()
+            .into(),
+        rels: rels
+            .into_iter()
+            .map(BoltType::UnboundedRelation)
+            .collect::<Vec<_>>()
+            .into(),
+        indices: indices
+            .iter()
+            .map(|i| BoltType::Integer((*i).into()))
+            .collect::<Vec<_>>()
+            .into(),
+    })
+}
+
+fn row(key: &str, value: GraphValue) -> GraphRow {
+    let mut row = GraphRow::new();
+    row.insert(key.to_string(), value);
+    row
+}
+
+#[test]
+fn bolt_paths_rebuild_relationship_direction_from_indices() {
+    let value = graph_value_from_bolt(&path(
+        vec![person(1, "Alice"), person(2, "Bob"), person(3, "Carol")],
+        vec![rel(10, "KNOWS"), rel(11, "MANAGES")],
+        &[1, 1, -2, 2],
+    ));
+    let GraphValue::Path {
+        nodes,
+        relationships,
+    } = &value
+    else {
+        panic!("expected path, got {value:?}");
+    };
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0].labels, vec!["Person".to_string()]);
+    assert_eq!(
+        relationships
+            .iter()
+            .map(|r| (r.id, r.r#type.as_str(), r.start, r.end))
+            .collect::<Vec<_>>(),
+        vec![(10, "KNOWS", 1, 2), (11, "MANAGES", 3, 2)]
+    );
+    assert_eq!(
+        format_rows(&[row("p", value)], Neo4jOutputFormat::Triples),
+        "(Alice)-[KNOWS]->(Bob)\n(Carol)-[MANAGES]->(Bob)"
+    );
+}
+
+#[test]
+fn bolt_paths_with_invalid_indices_do_not_panic() {
+    for indices in [&[0, 1][..], &[3, 1], &[1, -1], &[1]] {
+        let value = graph_value_from_bolt(&path(
+            vec![person(1, "Alice"), person(2, "Bob")],
+            vec![rel(10, "KNOWS")],
+            indices,
+        ));
+        let GraphValue::Path { relationships, .. } = value else {
+            panic!("expected path");
+        };
+        assert!(relationships.len() <= 1, "{indices:?}");
+    }
+}
+
+#[test]
+fn bolt_nodes_and_scalars_convert_to_graph_values() {
+    let node = graph_value_from_bolt(&BoltType::Node(person(7, "Dana")));
+    assert_eq!(
+        node.to_json(),
+        serde_json::json!({ "id": 7, "labels": ["Person"], "properties": { "name": "Dana" } })
+    );
+    assert_eq!(
+        format_rows(&[row("n", node)], Neo4jOutputFormat::Triples),
+        "(Dana)"
+    );
+
+    let list = graph_value_from_bolt(&BoltType::List(
+        vec![BoltType::Integer(3.into()), BoltType::String("x".into())].into(),
+    ));
+    assert_eq!(list.to_json(), serde_json::json!([3, "x"]));
+}
@@ -1,3 +1,5 @@
+#![cfg(not(feature = "neo4j"))]
+
 use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -0,0 +1,93 @@
+use server_rs::connectors::neo4j::{format_rows, GraphRow, GraphValue, Neo4jOutputFormat};
+
+fn load_fixture(name: &str) -> Vec<GraphRow> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    let text = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+#[test]
+fn neo4j_rows_render_as_triples_json_and_table() {
+    let rows = load_fixture("neo4j_kg_rows.json");
+
+    let triples = format_rows(&rows, Neo4jOutputFormat::Triples);
+    assert_eq!(
+        triples,
+        "(Alice)-[WORKS_AT]->(Acme)\n(Bob)-[WORKS_AT]->(Acme)"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&format_rows(&rows, Neo4jOutputFormat::Json)).unwrap();
+    let arr = json.as_array().unwrap();
+    assert_eq!(arr.len(), 3);
+    assert_eq!(arr[0]["a"]["labels"], serde_json::json!(["Person"]));
+    assert_eq!(arr[0]["a"]["properties"]["age"], 33);
+    assert_eq!(arr[0]["r"]["type"], "WORKS_AT");
+    assert_eq!(arr[0]["r"]["start"], 1);
+    assert_eq!(arr[0]["r"]["properties"]["since"], 2020);
+
+    let table = format_rows(&rows[..1], Neo4jOutputFormat::Table);
+    let mut lines = table.lines();
+    assert_eq!(lines.next().unwrap(), "a | b | r");
+    assert!(lines.next().unwrap().contains("\"Acme\""));
+}
+
+#[test]
+fn neo4j_paths_expand_into_ordered_triples() {
+    let rows = load_fixture("neo4j_path_rows.json");
+
+    let triples = format_rows(&rows, Neo4jOutputFormat::Triples);
+    assert_eq!(
+        triples,
+        "(Alice)-[KNOWS]->(Bob)\n(Bob)-[LIVES_IN]->(City#4)"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&format_rows(&rows, Neo4jOutputFormat::Json)).unwrap();
+    assert_eq!(json[0]["hops"], 2);
+    assert_eq!(json[0]["p"]["nodes"].as_array().unwrap().len(), 3);
+    assert_eq!(json[0]["p"]["relationships"][1]["type"], "LIVES_IN");
+}
+
+#[test]
+fn neo4j_value_format_keeps_scalar_contract() {
+    let mut row = GraphRow::new();
+    row.insert(
+        "value".to_string(),
+        GraphValue::Scalar {
+            value: serde_json::json!(42),
+        },
+    );
+    assert_eq!(format_rows(&[row], Neo4jOutputFormat::Value), "42");
+    assert_eq!(format_rows(&[], Neo4jOutputFormat::Value), "");
+
+    let rows = load_fixture("neo4j_kg_rows.json");
+    let fallback: serde_json::Value =
+        serde_json::from_str(&format_rows(&rows, Neo4jOutputFormat::Value)).unwrap();
+    assert_eq!(fallback.as_array().unwrap().len(), 3);
+
+    assert_eq!(
+        Neo4jOutputFormat::parse("Triples"),
+        Some(Neo4jOutputFormat::Triples)
+    );
+    assert_eq!(Neo4jOutputFormat::parse("csv"), None);
+}
+
+#[test]
+fn neo4j_triples_render_unlinked_nodes() {
+    let rows = load_fixture("neo4j_kg_rows.json");
+    let nodes_only = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .filter(|(_, v)| matches!(v, GraphValue::Node(_)))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<GraphRow>()
+        })
+        .collect::<Vec<_>>();
+    let triples = format_rows(&nodes_only, Neo4jOutputFormat::Triples);
+    assert_eq!(triples, "(Alice)\n(Acme)\n(Bob)");
+}
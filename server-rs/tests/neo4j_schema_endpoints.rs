@@ -0,0 +1,136 @@
+use axum::http::StatusCode;
+
+mod common;
+
+use common::{send, setup};
+
+async fn create_neo4j_datasource(app: &axum::Router) -> String {
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/datasources",
+        Some(serde_json::json!({
+            "name": "neo4j-demo",
+            "driver": "neo4j",
+            "url": "bolt://localhost:7687",
+            "username": "neo4j",
+            "password": "pass"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    json["id"].as_str().unwrap().to_string()
+}
+
+#[cfg(not(feature = "neo4j"))]
+#[tokio::test]
+async fn neo4j_schema_endpoints_are_feature_gated() {
+    let (_dir, app) = setup();
+    let id = create_neo4j_datasource(&app).await;
+
+    let paths = [
+        "relationship-types",
+        "indexes",
+        "constraints",
+        "labels/Person/properties",
+        "labels/Person/preview?limit=5",
+    ];
+    for path in paths {
+        let (status, json) = send(
+            &app,
+            "GET",
+            &format!("/api/datasources/{id}/neo4j/{path}"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED, "{path}");
+        assert_eq!(json["error"], "feature_not_enabled");
+        assert_eq!(json["feature"], "neo4j");
+    }
+}
+
+#[tokio::test]
+async fn neo4j_schema_endpoints_check_access_and_advertise_capabilities() {
+    let (_dir, app) = setup();
+    let id = create_neo4j_datasource(&app).await;
+
+    let (status, json) = send(
+        &app,
+        "GET",
+        &format!("/api/datasources/{id}/capabilities"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["supportsNeo4jSchema"], cfg!(feature = "neo4j"));
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/datasources",
+        Some(serde_json::json!({
+            "name": "neo4j-locked",
+            "driver": "neo4j",
+            "url": "bolt://localhost:7687",
+            "username": "neo4j",
+            "password": "pass",
+            "allowSchema": false
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let locked_id = json["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "GET",
+        &format!("/api/datasources/{locked_id}/neo4j/indexes"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(json["error"], "forbidden");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/datasources",
+        Some(serde_json::json!({
+            "name": "sqlite-demo",
+            "driver": "sqlite",
+            "url": "sqlite::memory:"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sqlite_id = json["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "GET",
+        &format!("/api/datasources/{sqlite_id}/neo4j/relationship-types"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "unsupported_driver");
+
+    let (status, json) = send(
+        &app,
+        "GET",
+        &format!("/api/datasources/{sqlite_id}/capabilities"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["supportsNeo4jSchema"], false);
+
+    let (status, _) = send(
+        &app,
+        "GET",
+        "/api/datasources/ds_missing/neo4j/constraints",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
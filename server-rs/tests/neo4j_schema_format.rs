@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use server_rs::connectors::neo4j::{
+    label_properties_from_rows, preview_nodes_from_rows, relationship_types_from_rows,
+    rows_to_json, GraphRow,
+};
+
+fn load_fixture() -> BTreeMap<String, Vec<GraphRow>> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("neo4j_schema_rows.json");
+    let text = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+#[test]
+fn neo4j_schema_rows_build_endpoint_payloads() {
+    let fixture = load_fixture();
+
+    assert_eq!(
+        relationship_types_from_rows(&fixture["relationshipTypes"]),
+        vec!["WORKS_AT".to_string(), "KNOWS".to_string()]
+    );
+
+    let properties = label_properties_from_rows(&fixture["properties"]);
+    assert_eq!(
+        serde_json::Value::Array(properties),
+        serde_json::json!([
+            { "name": "age", "types": ["Long"], "mandatory": false },
+            { "name": "name", "types": ["String"], "mandatory": true },
+            { "name": "nick", "types": [], "mandatory": false }
+        ])
+    );
+
+    let indexes = rows_to_json(&fixture["indexes"]);
+    assert_eq!(
+        indexes,
+        vec![serde_json::json!({
+            "name": "person_name",
+            "type": "RANGE",
+            "entityType": "NODE",
+            "labelsOrTypes": ["Person"],
+            "properties": ["name"],
+            "state": "ONLINE"
+        })]
+    );
+
+    let nodes = preview_nodes_from_rows(&fixture["preview"], "n");
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0]["id"], 1);
+    assert_eq!(nodes[0]["labels"], serde_json::json!(["Person"]));
+    assert_eq!(nodes[1]["properties"]["name"], "Bob");
+}
@@ -78,6 +78,76 @@ async fn nightly_neo4j_resolver_works_end_to_end() {
     assert_eq!(json["text"], "Neo4j: ok");
 }
 
+#[cfg(feature = "neo4j")]
+#[tokio::test]
+#[ignore]
+async fn nightly_neo4j_schema_endpoints_work_end_to_end() {
+    let uri = std::env::var("NEO4J_URI").unwrap_or_default();
+    let user = std::env::var("NEO4J_USER").unwrap_or_default();
+    let pass = std::env::var("NEO4J_PASS").unwrap_or_default();
+    if uri.is_empty() || user.is_empty() || pass.is_empty() {
+        return;
+    }
+
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let body = serde_json::json!({
+        "name": "neo4j-nightly",
+        "driver": "neo4j",
+        "url": uri,
+        "username": user,
+        "password": pass
+    })
+    .to_string();
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/datasources")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let id = json["id"].as_str().unwrap().to_string();
+
+    for (path, key) in [
+        ("relationship-types", "relationshipTypes"),
+        ("indexes", "indexes"),
+        ("constraints", "constraints"),
+        ("labels/Person/properties", "properties"),
+        ("labels/Person/preview?limit=5", "nodes"),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(format!("/api/datasources/{id}/neo4j/{path}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{path}");
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(json[key].is_array(), "{path}: {json}");
+    }
+}
+
 #[cfg(feature = "milvus")]
 #[tokio::test]
 #[ignore]
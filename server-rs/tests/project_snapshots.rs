@@ -0,0 +1,175 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{run_experiment, send};
+
+fn project_body(name: &str, content: &str) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "state": {
+            "nodes": [
+                { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                  "data": { "label": "Input", "type": "user_input", "content": content } }
+            ],
+            "edges": [],
+            "variables": []
+        }
+    })
+}
+
+async fn replay(
+    app: &axum::Router,
+    dataset_id: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    send(
+        app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(body),
+    )
+    .await
+}
+
+async fn run_snapshot_id(app: &axum::Router, summaries: &serde_json::Value) -> String {
+    let run_id = summaries[0]["runId"].as_str().unwrap();
+    let (status, run) = send(app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    run["snapshotId"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn replay_pins_runs_to_content_addressed_snapshots() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(project_body("Greeter", "Say {{w}}")),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({ "name": "Words", "rows": [ { "w": "hi" } ] })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, first) = replay(
+        &app,
+        &dataset_id,
+        serde_json::json!({ "projectId": project_id }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let snap_v1 = run_snapshot_id(&app, &first).await;
+    assert!(snap_v1.starts_with("snap_"));
+
+    let (_, again) = replay(
+        &app,
+        &dataset_id,
+        serde_json::json!({ "projectId": project_id }),
+    )
+    .await;
+    assert_eq!(run_snapshot_id(&app, &again).await, snap_v1);
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        &format!("/api/projects/{project_id}"),
+        Some(project_body("Greeter", "Please say {{w}}")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, edited) = replay(
+        &app,
+        &dataset_id,
+        serde_json::json!({ "projectId": project_id }),
+    )
+    .await;
+    let snap_v2 = run_snapshot_id(&app, &edited).await;
+    assert_ne!(snap_v2, snap_v1);
+    assert_ne!(edited[0]["outputDigest"], first[0]["outputDigest"]);
+
+    let (status, pinned) = replay(
+        &app,
+        &dataset_id,
+        serde_json::json!({ "snapshotId": snap_v1 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(pinned[0]["outputDigest"], first[0]["outputDigest"]);
+    assert_eq!(run_snapshot_id(&app, &pinned).await, snap_v1);
+
+    let (status, snapshot) = send(&app, "GET", &format!("/api/snapshots/{snap_v1}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(snapshot["projectId"], project_id.as_str());
+    assert_eq!(snapshot["nodes"][0]["data"]["content"], "Say {{w}}");
+
+    let (status, created) = send(
+        &app,
+        "POST",
+        &format!("/api/projects/{project_id}/snapshots"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["id"], snap_v2.as_str());
+
+    let (status, list) = send(
+        &app,
+        "GET",
+        &format!("/api/projects/{project_id}/snapshots"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 2);
+
+    let (status, json) = replay(
+        &app,
+        &dataset_id,
+        serde_json::json!({ "snapshotId": "snap_missing" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "snapshot_not_found");
+
+    let (status, json) = replay(
+        &app,
+        &dataset_id,
+        serde_json::json!({ "projectId": "p_other", "snapshotId": snap_v1 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "snapshot_project_mismatch");
+
+    let (status, exp) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "snapshotId": snap_v1 },
+            "candidate": { "snapshotId": snap_v2 }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(exp["baseline"]["snapshotId"], snap_v1.as_str());
+    assert_eq!(exp["candidate"]["projectId"], project_id.as_str());
+    assert_eq!(exp["rows"][0]["digestChanged"], true);
+}
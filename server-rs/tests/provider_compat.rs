@@ -0,0 +1,273 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+
+mod common;
+
+use common::{send, setup, spawn_mock_openai};
+
+#[derive(Debug, Clone)]
+struct Seen {
+    path: String,
+    head: String,
+    body: serde_json::Value,
+}
+
+fn reply(path: &str, body: &serde_json::Value) -> serde_json::Value {
+    if path.ends_with("/embeddings") {
+        let inputs = body["input"].as_array().cloned().unwrap_or_default();
+        let data = inputs
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, _)| serde_json::json!({ "index": i, "embedding": [i as f64, 1.0] }))
+            .collect::<Vec<_>>();
+        return serde_json::json!({ "object": "list", "data": data });
+    }
+    let message = match body["model"].as_str().unwrap_or("") {
+        "deepseek-reasoner" => {
+            serde_json::json!({ "role": "assistant", "content": "42", "reasoning_content": "thinking hard" })
+        }
+        "gpt-oss" => {
+            serde_json::json!({ "role": "assistant", "content": "ok", "reasoning": "brief thought" })
+        }
+        "deepseek-r1" => {
+            serde_json::json!({ "role": "assistant", "content": "<think>\nweigh options\n</think>\n\nfinal answer" })
+        }
+        model => serde_json::json!({ "role": "assistant", "content": format!("echo {model}") }),
+    };
+    serde_json::json!({ "choices": [{ "index": 0, "message": message }] })
+}
+
+fn serve() -> (String, Arc<Mutex<Vec<Seen>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let url = spawn_mock_openai(move |req| {
+        let out = reply(&req.path, &req.body);
+        seen2.lock().unwrap().push(Seen {
+            path: req.path.clone(),
+            head: req.head.clone(),
+            body: req.body.clone(),
+        });
+        out
+    });
+    (format!("{url}/v1"), seen)
+}
+
+async fn chat(app: &axum::Router, id: &str, model: Option<&str>) -> serde_json::Value {
+    let (status, json) = send(
+        app,
+        "POST",
+        &format!("/api/providers/{id}/chat/completions"),
+        Some(serde_json::json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": "hi", "createdAt": "0" }]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    json
+}
+
+#[tokio::test]
+async fn openai_compatible_providers_apply_auth_and_model_quirks() {
+    let (base_url, seen) = serve();
+    let (_dir, app) = setup();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({ "name": "x", "provider": "anthropic", "apiKey": "k" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "unsupported_provider");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({ "name": "x", "provider": "openai", "baseUrl": base_url })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "validation_failed");
+
+    let (status, ollama) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({ "name": "local", "provider": "ollama", "baseUrl": base_url })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{ollama}");
+    assert_eq!(ollama["provider"], "ollama");
+    let ollama_id = ollama["id"].as_str().unwrap();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/providers/{ollama_id}/embeddings"),
+        Some(serde_json::json!({ "input": ["a", "b", "c"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(
+        json["embeddings"],
+        serde_json::json!([[0.0, 1.0], [1.0, 1.0], [2.0, 1.0]])
+    );
+    {
+        let seen = seen.lock().unwrap();
+        let last = seen.last().unwrap();
+        assert_eq!(last.path, "/v1/embeddings");
+        assert_eq!(last.body["model"], "nomic-embed-text");
+        assert!(!last.head.contains("authorization:"));
+    }
+
+    let json = chat(&app, ollama_id, Some("deepseek-r1")).await;
+    assert_eq!(json["content"], "final answer");
+    assert_eq!(json["reasoningContent"], "weigh options");
+    let json = chat(&app, ollama_id, Some("gpt-oss")).await;
+    assert_eq!(json["content"], "ok");
+    assert_eq!(json["reasoningContent"], "brief thought");
+    let json = chat(&app, ollama_id, None).await;
+    assert_eq!(json["content"], "echo llama3.1");
+
+    let (status, gateway) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "gateway",
+            "provider": "openai_compatible",
+            "baseUrl": format!("{base_url}/"),
+            "apiKey": "gw-secret",
+            "authHeader": "api-key"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{gateway}");
+    assert_eq!(gateway["authHeader"], "api-key");
+    let gateway_id = gateway["id"].as_str().unwrap();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        &format!("/api/providers/{gateway_id}/chat/completions"),
+        Some(serde_json::json!({
+            "messages": [{ "role": "user", "content": "hi", "createdAt": "0" }]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{json}");
+    assert_eq!(json["message"], "model_required");
+
+    let json = chat(&app, gateway_id, Some("qwen2.5")).await;
+    assert_eq!(json["content"], "echo qwen2.5");
+    {
+        let seen = seen.lock().unwrap();
+        let last = seen.last().unwrap();
+        assert_eq!(last.path, "/v1/chat/completions");
+        assert!(last.head.contains("api-key: gw-secret"));
+        assert!(!last.head.contains("authorization:"));
+    }
+
+    let (status, deepseek) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "ds",
+            "provider": "deepseek",
+            "baseUrl": base_url,
+            "apiKey": "sk-ds"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{deepseek}");
+    let deepseek_id = deepseek["id"].as_str().unwrap();
+    let json = chat(&app, deepseek_id, None).await;
+    assert_eq!(json["content"], "echo deepseek-chat");
+    let json = chat(&app, deepseek_id, Some("deepseek-reasoner")).await;
+    assert_eq!(json["content"], "42");
+    assert_eq!(json["reasoningContent"], "thinking hard");
+    let json = chat(&app, deepseek_id, Some("deepseek-r1")).await;
+    assert!(json["content"].as_str().unwrap().starts_with("<think>"));
+    assert!(json["reasoningContent"].is_null());
+    assert!(seen
+        .lock()
+        .unwrap()
+        .last()
+        .unwrap()
+        .head
+        .contains("authorization: bearer sk-ds"));
+}
+
+#[tokio::test]
+async fn openai_compatible_provider_drives_vector_embedding_jobs() {
+    let (base_url, seen) = serve();
+    let (_dir, app) = setup();
+
+    let (status, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "vllm",
+            "provider": "vllm",
+            "baseUrl": base_url,
+            "defaultEmbeddingModel": "bge-m3"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{provider}");
+    let (status, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "rows",
+            "rows": [{ "id": "r1", "text": "alpha" }, { "id": "r2", "text": "beta" }]
+        })),
+    )
+    .await;
+    assert!(status.is_success(), "{dataset}");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": "rows", "dimension": 2, "distance": "dot" })),
+    )
+    .await;
+    assert!(status.is_success(), "{json}");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/embed-to-vector",
+        Some(serde_json::json!({
+            "datasetId": dataset["id"],
+            "providerId": provider["id"],
+            "collection": "rows",
+            "idField": "id",
+            "textField": "text"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["job"]["stats"]["inserted"], 2);
+    let last = seen.lock().unwrap().last().cloned().unwrap();
+    assert_eq!(last.body["model"], "bge-m3");
+    assert!(!last.head.contains("authorization:"));
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/get",
+        Some(serde_json::json!({ "collection": "rows", "ids": ["r1", "r2"], "withVector": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["points"][0]["vector"], serde_json::json!([0.0, 1.0]));
+    assert_eq!(json["points"][1]["vector"], serde_json::json!([1.0, 1.0]));
+}
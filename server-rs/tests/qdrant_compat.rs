@@ -0,0 +1,356 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+fn app_in(dir: &std::path::Path) -> axum::Router {
+    let static_dir = dir.join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    server_rs::build_app_with_data_dir(static_dir, dir.join("data"))
+}
+
+fn ids(points: &serde_json::Value) -> Vec<serde_json::Value> {
+    points
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn qdrant_collection_lifecycle() {
+    let dir = tempdir().unwrap();
+    let app = app_in(dir.path());
+
+    let (status, json) = send(&app, "GET", "/api/qdrant/collections/docs/exists", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["result"]["exists"], false);
+    assert_eq!(json["status"], "ok");
+
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/docs",
+        Some(serde_json::json!({
+            "vectors": { "size": 4, "distance": "Dot" },
+            "quantization_config": { "scalar": { "type": "int8" } }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["result"], true);
+
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/docs",
+        Some(serde_json::json!({ "vectors": { "size": 4, "distance": "Dot" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(json["status"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("already exists"));
+
+    for body in [
+        serde_json::json!({ "vectors": { "size": 4, "distance": "Manhattan" } }),
+        serde_json::json!({ "vectors": { "text": { "size": 4, "distance": "Cosine" } } }),
+    ] {
+        let (status, json) = send(&app, "PUT", "/api/qdrant/collections/other", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{json}");
+        assert!(json["status"]["error"].is_string());
+    }
+
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/docs/index",
+        Some(serde_json::json!({ "field_name": "lang", "field_schema": "keyword" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["result"]["status"], "completed");
+
+    let (status, json) = send(&app, "GET", "/api/qdrant/collections", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        json["result"]["collections"],
+        serde_json::json!([{ "name": "docs" }])
+    );
+
+    let (status, json) = send(&app, "GET", "/api/qdrant/collections/docs", None).await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let info = &json["result"];
+    assert_eq!(info["status"], "green");
+    assert_eq!(info["points_count"], 0);
+    assert_eq!(info["config"]["params"]["vectors"]["size"], 4);
+    assert_eq!(info["config"]["params"]["vectors"]["distance"], "Dot");
+    assert_eq!(
+        info["config"]["quantization_config"]["scalar"]["type"],
+        "int8"
+    );
+    assert_eq!(info["payload_schema"]["lang"]["data_type"], "keyword");
+
+    let (status, json) = send(
+        &app,
+        "DELETE",
+        "/api/qdrant/collections/docs/index/lang",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (_, json) = send(&app, "GET", "/api/qdrant/collections/docs", None).await;
+    assert_eq!(json["result"]["payload_schema"], serde_json::json!({}));
+
+    let (status, json) = send(&app, "DELETE", "/api/qdrant/collections/docs", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["result"], true);
+    let (status, json) = send(&app, "DELETE", "/api/qdrant/collections/docs", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["result"], false);
+    let (status, json) = send(&app, "GET", "/api/qdrant/collections/docs", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["status"]["error"].is_string());
+}
+
+#[tokio::test]
+async fn qdrant_points_search_scroll_and_delete() {
+    let dir = tempdir().unwrap();
+    let app = app_in(dir.path());
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/kb",
+        Some(serde_json::json!({ "vectors": { "size": 3, "distance": "Cosine" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/kb/points?wait=true",
+        Some(serde_json::json!({
+            "points": [
+                { "id": 1, "vector": [1.0, 0.0, 0.0], "payload": { "page_content": "alpha", "metadata": { "source": "a.md", "page": 1 } } },
+                { "id": 2, "vector": [0.9, 0.1, 0.0], "payload": { "page_content": "beta", "metadata": { "source": "b.md", "page": 2 } } },
+                { "id": "5c56c793-69f3-4fbf-87e6-c4bf54c28c26", "vector": { "": [0.0, 1.0, 0.0] }, "payload": { "page_content": "gamma", "metadata": { "source": "a.md", "page": null } } }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["result"]["status"], "completed");
+
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/kb/points",
+        Some(serde_json::json!({
+            "batch": {
+                "ids": [10, 11],
+                "vectors": [[0.0, 0.0, 1.0], [0.1, 0.0, 0.9]],
+                "payloads": [{ "page_content": "delta" }, { "page_content": "epsilon" }]
+            }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/kb/points",
+        Some(serde_json::json!({ "points": [{ "id": -1, "vector": [1.0, 0.0, 0.0] }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["status"]["error"].is_string());
+    let (status, json) = send(
+        &app,
+        "PUT",
+        "/api/qdrant/collections/kb/points",
+        Some(serde_json::json!({ "points": [{ "id": 9, "vector": [1.0, 0.0] }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        json["status"]["error"],
+        "validation_failed: dimension_mismatch"
+    );
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/search",
+        Some(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0],
+            "limit": 2,
+            "with_payload": true,
+            "with_vector": true
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let hits = &json["result"];
+    assert_eq!(ids(hits), vec![serde_json::json!(1), serde_json::json!(2)]);
+    assert!((hits[0]["score"].as_f64().unwrap() - 1.0).abs() < 1e-5);
+    assert_eq!(hits[0]["payload"]["metadata"]["source"], "a.md");
+    assert_eq!(hits[0]["vector"], serde_json::json!([1.0, 0.0, 0.0]));
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/search",
+        Some(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0],
+            "limit": 5,
+            "filter": {
+                "must": [{ "key": "metadata.source", "match": { "value": "a.md" } }],
+                "must_not": [{ "is_null": { "key": "metadata.page" } }]
+            }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(ids(&json["result"]), vec![serde_json::json!(1)]);
+    assert!(json["result"][0]["payload"].is_null());
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/query",
+        Some(serde_json::json!({
+            "query": [0.0, 0.0, 1.0],
+            "limit": 3,
+            "score_threshold": 0.5,
+            "with_payload": ["page_content"],
+            "filter": { "should": [{ "has_id": [10, 11, 1] }] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let points = &json["result"]["points"];
+    assert_eq!(
+        ids(points),
+        vec![serde_json::json!(10), serde_json::json!(11)]
+    );
+    assert_eq!(
+        points[0]["payload"],
+        serde_json::json!({ "page_content": "delta" })
+    );
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/search",
+        Some(serde_json::json!({
+            "vector": [1.0, 0.0, 0.0],
+            "filter": { "must": [{ "nested": { "key": "metadata", "filter": {} } }] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{json}");
+
+    let mut offset = serde_json::Value::Null;
+    let mut seen = Vec::new();
+    loop {
+        let (status, json) = send(
+            &app,
+            "POST",
+            "/api/qdrant/collections/kb/points/scroll",
+            Some(serde_json::json!({ "limit": 2, "offset": offset })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{json}");
+        let page = &json["result"];
+        assert!(page["points"].as_array().unwrap().len() <= 2);
+        assert!(page["points"][0]["payload"]["page_content"].is_string());
+        seen.extend(ids(&page["points"]));
+        offset = page["next_page_offset"].clone();
+        if offset.is_null() {
+            break;
+        }
+    }
+    assert_eq!(seen.len(), 5);
+    assert_eq!(seen[0], serde_json::json!(1));
+    assert_eq!(seen[1], serde_json::json!(10));
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/scroll",
+        Some(serde_json::json!({ "limit": 1, "offset": 10, "with_payload": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids(&json["result"]["points"]), vec![serde_json::json!(10)]);
+    assert_eq!(json["result"]["next_page_offset"], 11);
+    assert!(json["result"]["points"][0]["payload"].is_null());
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points",
+        Some(serde_json::json!({
+            "ids": ["5c56c793-69f3-4fbf-87e6-c4bf54c28c26", 99, 2],
+            "with_vector": true
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(
+        ids(&json["result"]),
+        vec![
+            serde_json::json!("5c56c793-69f3-4fbf-87e6-c4bf54c28c26"),
+            serde_json::json!(2)
+        ]
+    );
+    assert_eq!(
+        json["result"][0]["vector"],
+        serde_json::json!([0.0, 1.0, 0.0])
+    );
+
+    let (status, json) = send(&app, "GET", "/api/qdrant/collections/kb/points/2", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["result"]["payload"]["page_content"], "beta");
+    let (status, _) = send(&app, "GET", "/api/qdrant/collections/kb/points/404", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/delete",
+        Some(serde_json::json!({ "points": [1, 2] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/kb/points/delete",
+        Some(serde_json::json!({
+            "filter": { "must": [{ "key": "page_content", "match": { "any": ["delta"] } }] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (_, json) = send(&app, "GET", "/api/qdrant/collections/kb", None).await;
+    assert_eq!(json["result"]["points_count"], 2);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/qdrant/collections/missing/points/search",
+        Some(serde_json::json!({ "vector": [1.0, 0.0, 0.0] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["status"]["error"], "not_found");
+}
@@ -0,0 +1,174 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt as _;
+use tempfile::tempdir;
+use tower::ServiceExt as _;
+
+mod common;
+
+use common::{last_message, send, spawn_mock_openai};
+
+fn serve_mock_openai() -> String {
+    spawn_mock_openai(|req| {
+        let last = last_message(&req.body);
+        serde_json::json!({
+            "choices": [ { "message": { "role": "assistant", "content": format!("echo: {last}") } } ]
+        })
+    })
+}
+
+async fn send_raw(app: &axum::Router, uri: &str) -> (StatusCode, String, String) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn dataset_runs_export_as_jsonl_and_csv() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let base_url = serve_mock_openai();
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": base_url,
+            "apiKey": "sk-test"
+        })),
+    )
+    .await;
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Export Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "Input", "type": "user_input", "content": "Say {{w}}" } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "Words",
+            "rows": [ { "w": "a, \"quoted\"\nline" }, { "x": "missing" }, { "w": "c" } ]
+        })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({
+            "projectId": project_id,
+            "execute": true,
+            "providerId": provider["id"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, content_type, text) =
+        send_raw(&app, &format!("/api/datasets/{dataset_id}/runs/export")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/x-ndjson");
+    let records = text
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), 3);
+    assert!(records.iter().all(|r| r["trace"]["segments"].is_array()));
+    assert!(records
+        .iter()
+        .all(|r| r["execution"]["completion"].is_string()));
+
+    let (_, _, text) = send_raw(
+        &app,
+        &format!("/api/datasets/{dataset_id}/runs/export?hasMissingVariables=true"),
+    )
+    .await;
+    assert_eq!(text.lines().count(), 1);
+    assert!(text.contains("\"rowIndex\":1"));
+
+    let (status, content_type, text) = send_raw(
+        &app,
+        &format!("/api/datasets/{dataset_id}/runs/export?format=csv&includeCompletion=true"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/csv"));
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    assert_eq!(
+        reader.headers().unwrap().iter().collect::<Vec<_>>(),
+        vec![
+            "runId",
+            "createdAt",
+            "projectId",
+            "rowIndex",
+            "status",
+            "outputDigest",
+            "missingVariablesCount",
+            "text",
+            "completion"
+        ]
+    );
+    let rows = reader.records().map(|r| r.unwrap()).collect::<Vec<_>>();
+    assert_eq!(rows.len(), 3);
+    let first = rows.iter().find(|r| &r[3] == "0").unwrap();
+    assert_eq!(&first[2], project_id.as_str());
+    assert_eq!(&first[7], "--- Input ---\nSay a, \"quoted\"\nline");
+    assert_eq!(&first[8], "echo: Say a, \"quoted\"\nline");
+
+    let (_, _, text) = send_raw(
+        &app,
+        &format!("/api/datasets/{dataset_id}/runs/export?format=csv&rowIndex=2"),
+    )
+    .await;
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    assert_eq!(reader.headers().unwrap().len(), 8);
+    let rows = reader.records().map(|r| r.unwrap()).collect::<Vec<_>>();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(&rows[0][3], "2");
+
+    let (status, _, text) = send_raw(&app, "/api/datasets/ds_missing/runs/export?format=csv").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(text.lines().count(), 1);
+}
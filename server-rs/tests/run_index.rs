@@ -0,0 +1,204 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt as _;
+use tempfile::tempdir;
+use tower::ServiceExt as _;
+
+mod common;
+
+use common::send;
+
+async fn send_with_headers(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, Option<String>, serde_json::Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    let body = match body {
+        Some(v) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(v.to_string())
+        }
+        None => Body::empty(),
+    };
+    let response = app
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let cursor = response
+        .headers()
+        .get("x-next-cursor")
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, cursor, json)
+}
+
+#[tokio::test]
+async fn dataset_runs_are_listed_from_index_with_filters_and_cursor() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Index Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "User", "type": "user_input", "content": "Hi {{name}}" } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "Names",
+            "rows": [
+                { "name": "a" }, { "other": "b" }, { "name": "c" },
+                { "other": "d" }, { "name": "e" }
+            ]
+        })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    let (status, summaries) = send(
+        &app,
+        "POST",
+        &format!("/api/datasets/{dataset_id}/replay"),
+        Some(serde_json::json!({ "projectId": project_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summaries.as_array().unwrap().len(), 5);
+    assert!(data_dir.join("run_index.db").exists());
+
+    let (status, cursor, all) = send_with_headers(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(cursor.is_none());
+    assert_eq!(all.as_array().unwrap().len(), 5);
+
+    let (_, missing) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs?hasMissingVariables=true"),
+        None,
+    )
+    .await;
+    let mut missing_rows = missing
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["rowIndex"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    missing_rows.sort();
+    assert_eq!(missing_rows, vec![1, 3]);
+
+    let status_filter = all[0]["status"].as_str().unwrap().to_string();
+    let (_, by_status) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs?status={status_filter}&rowIndex=2"),
+        None,
+    )
+    .await;
+    assert_eq!(by_status.as_array().unwrap().len(), 1);
+    assert_eq!(by_status[0]["rowIndex"], 2);
+
+    let (_, none) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs?status=nope"),
+        None,
+    )
+    .await;
+    assert_eq!(none, serde_json::json!([]));
+
+    let mut seen = Vec::<String>::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let uri = match &cursor {
+            Some(c) => format!("/api/datasets/{dataset_id}/runs?limit=2&cursor={c}"),
+            None => format!("/api/datasets/{dataset_id}/runs?limit=2"),
+        };
+        let (status, next, page) = send_with_headers(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        pages += 1;
+        seen.extend(
+            page.as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["runId"].as_str().unwrap().to_string()),
+        );
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 3);
+    let expected = all
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["runId"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(seen, expected);
+
+    let (status, json) = send(
+        &app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs?limit=2&cursor=%%%"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "invalid_cursor");
+
+    let run_id = all[0]["runId"].as_str().unwrap();
+    let (status, run) = send(&app, "GET", &format!("/api/runs/{run_id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(run["trace"]["segments"].is_array());
+
+    drop(app);
+    std::fs::remove_file(data_dir.join("run_index.db")).unwrap();
+    let _ = std::fs::remove_file(data_dir.join("run_index.db-wal"));
+    let _ = std::fs::remove_file(data_dir.join("run_index.db-shm"));
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    let (_, rebuilt) = send(
+        &restarted,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs"),
+        None,
+    )
+    .await;
+    assert_eq!(rebuilt, all);
+}
@@ -0,0 +1,217 @@
+use std::io::Read as _;
+
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{run_experiment, send};
+
+async fn run_ids(app: &axum::Router, dataset_id: &str) -> Vec<String> {
+    let (_, runs) = send(
+        app,
+        "GET",
+        &format!("/api/datasets/{dataset_id}/runs"),
+        None,
+    )
+    .await;
+    runs.as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["runId"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn prune_runs_respects_policies_pins_and_archives() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir.clone());
+
+    let (_, project) = send(
+        &app,
+        "POST",
+        "/api/projects",
+        Some(serde_json::json!({
+            "name": "Retention Project",
+            "state": {
+                "nodes": [
+                    { "id": "n1", "type": "contextNode", "position": { "x": 0, "y": 0 },
+                      "data": { "label": "User", "type": "user_input", "content": "Hi {{name}}" } }
+                ],
+                "edges": [],
+                "variables": []
+            }
+        })),
+    )
+    .await;
+    let project_id = project["id"].as_str().unwrap().to_string();
+
+    let (_, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({ "name": "Names", "rows": [ { "name": "a" }, { "name": "b" } ] })),
+    )
+    .await;
+    let dataset_id = dataset["id"].as_str().unwrap().to_string();
+
+    for _ in 0..3 {
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        let (status, _) = send(
+            &app,
+            "POST",
+            &format!("/api/datasets/{dataset_id}/replay"),
+            Some(serde_json::json!({ "projectId": project_id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let (_, exp) = run_experiment(
+        &app,
+        serde_json::json!({
+            "datasetId": dataset_id,
+            "baseline": { "projectId": project_id },
+            "candidate": { "projectId": project_id },
+            "limit": 1
+        }),
+    )
+    .await;
+    let pinned = [
+        exp["rows"][0]["baselineRunId"]
+            .as_str()
+            .unwrap()
+            .to_string(),
+        exp["rows"][0]["candidateRunId"]
+            .as_str()
+            .unwrap()
+            .to_string(),
+    ];
+    assert_eq!(run_ids(&app, &dataset_id).await.len(), 8);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/prune-runs",
+        Some(serde_json::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "validation_failed");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/prune-runs",
+        Some(serde_json::json!({ "keepLastPerRow": 1, "dryRun": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let stats = &json["job"]["stats"];
+    assert_eq!(json["job"]["jobType"], "prune_runs");
+    assert_eq!(stats["scanned"], 8);
+    assert_eq!(stats["pinned"], 1);
+    assert_eq!(stats["candidates"], 5);
+    assert_eq!(stats["pruned"], 0);
+    assert!(stats["archive"].is_null());
+    assert_eq!(run_ids(&app, &dataset_id).await.len(), 8);
+    let candidates = stats["runIds"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert!(pinned.iter().all(|id| !candidates.contains(id)));
+    assert_eq!(stats["runIdsTruncated"], false);
+
+    // A run file that cannot be removed, or cannot be archived, is reported
+    // and stays indexed.
+    let stuck = candidates[0].clone();
+    let stuck_path = data_dir.join("runs").join(format!("{stuck}.json"));
+    std::fs::remove_file(&stuck_path).unwrap();
+    std::fs::create_dir_all(stuck_path.join("keep")).unwrap();
+    let corrupt = candidates[1].clone();
+    let corrupt_path = data_dir.join("runs").join(format!("{corrupt}.json"));
+    std::fs::write(&corrupt_path, "{not json").unwrap();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/prune-runs",
+        Some(serde_json::json!({ "keepLastPerRow": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let stats = &json["job"]["stats"];
+    assert_eq!(json["job"]["status"], "partial");
+    assert_eq!(json["job"]["summary"], "pruned=3 failed=2");
+    assert_eq!(stats["pruned"], 3);
+    assert_eq!(stats["failed"], 2);
+    assert_eq!(stats["failures"][0]["runId"], corrupt.as_str());
+    assert!(stats["failures"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid_run"));
+    assert_eq!(stats["failures"][1]["runId"], stuck.as_str());
+    assert!(stats["archiveBytes"].as_u64().unwrap() > 0);
+    assert!(corrupt_path.exists());
+
+    let remaining = run_ids(&app, &dataset_id).await;
+    assert_eq!(remaining.len(), 5);
+    assert!(pinned.iter().all(|id| remaining.contains(id)));
+    assert!(remaining.contains(&stuck));
+    assert!(remaining.contains(&corrupt));
+    for id in &candidates[2..] {
+        assert!(!data_dir.join("runs").join(format!("{id}.json")).exists());
+        let (status, _) = send(&app, "GET", &format!("/api/runs/{id}"), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    let archive = data_dir.join(stats["archive"].as_str().unwrap());
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(archive).unwrap())
+        .read_to_string(&mut text)
+        .unwrap();
+    let mut archived = text
+        .lines()
+        .map(|l| {
+            let v: serde_json::Value = serde_json::from_str(l).unwrap();
+            assert!(v["trace"]["segments"].is_array());
+            v["runId"].as_str().unwrap().to_string()
+        })
+        .collect::<Vec<_>>();
+    archived.sort();
+    let mut expected = candidates[2..].to_vec();
+    expected.sort();
+    assert_eq!(archived, expected);
+
+    let job_id = json["job"]["id"].as_str().unwrap();
+    let (_, job) = send(&app, "GET", &format!("/api/jobs/{job_id}"), None).await;
+    assert_eq!(job["stats"]["pruned"], 3);
+
+    std::fs::remove_dir_all(&stuck_path).unwrap();
+    std::fs::remove_file(&corrupt_path).unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let (_, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/prune-runs",
+        Some(serde_json::json!({ "datasetId": dataset_id, "maxAgeSeconds": 0 })),
+    )
+    .await;
+    assert_eq!(json["job"]["status"], "succeeded");
+    assert_eq!(json["job"]["stats"]["pruned"], 3);
+    assert_eq!(json["job"]["stats"]["failed"], 0);
+    assert_eq!(json["job"]["stats"]["pinned"], 2);
+    let mut remaining = run_ids(&app, &dataset_id).await;
+    remaining.sort();
+    let mut pinned = pinned.to_vec();
+    pinned.sort();
+    assert_eq!(remaining, pinned);
+}
@@ -31,7 +31,7 @@ async fn sql_query_returns_first_cell_value_for_sqlite() {
         .unwrap();
     conn.close().await.unwrap();
 
-    let app = server_rs::build_app(dir.path().to_path_buf());
+    let app = server_rs::build_app_with_data_dir(dir.path().to_path_buf(), dir.path().join("data"));
 
     let body = serde_json::json!({
         "url": url,
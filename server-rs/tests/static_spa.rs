@@ -15,7 +15,7 @@ async fn serves_existing_static_file() {
     fs::write(dir.path().join("index.html"), "INDEX").unwrap();
     fs::write(dir.path().join("assets/app.js"), "APP").unwrap();
 
-    let app = server_rs::build_app(dir.path().to_path_buf());
+    let app = server_rs::build_app_with_data_dir(dir.path().to_path_buf(), dir.path().join("data"));
 
     let response = app
         .oneshot(
@@ -42,7 +42,7 @@ async fn falls_back_to_index_for_unknown_routes() {
     let dir = tempdir().unwrap();
     fs::write(dir.path().join("index.html"), "INDEX").unwrap();
 
-    let app = server_rs::build_app(dir.path().to_path_buf());
+    let app = server_rs::build_app_with_data_dir(dir.path().to_path_buf(), dir.path().join("data"));
 
     for uri in ["/", "/some/route", "/deep/nested/route"] {
         let response = app
@@ -65,7 +65,7 @@ async fn serves_wasm_with_correct_content_type() {
     fs::write(dir.path().join("index.html"), "INDEX").unwrap();
     fs::write(dir.path().join("assets/module.wasm"), b"\0asm").unwrap();
 
-    let app = server_rs::build_app(dir.path().to_path_buf());
+    let app = server_rs::build_app_with_data_dir(dir.path().to_path_buf(), dir.path().join("data"));
 
     let response = app
         .oneshot(
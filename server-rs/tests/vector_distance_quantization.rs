@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 40) as f32 / (1u64 << 24) as f32) * 2.0 - 1.0
+    }
+
+    fn vector(&mut self, dim: usize) -> Vec<f32> {
+        (0..dim).map(|_| self.next()).collect()
+    }
+}
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf, std::path::PathBuf) {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    (dir, static_dir, data_dir)
+}
+
+async fn create(app: &axum::Router, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    send(app, "POST", "/api/vector/collections/create", Some(body)).await
+}
+
+async fn upsert(app: &axum::Router, collection: &str, vectors: &[Vec<f32>]) {
+    let indexed = vectors.iter().enumerate().collect::<Vec<_>>();
+    for chunk in indexed.chunks(1_000) {
+        let points = chunk
+            .iter()
+            .map(|(i, v)| serde_json::json!({ "id": format!("p{i}"), "vector": v, "payload": { "i": i } }))
+            .collect::<Vec<_>>();
+        let (status, _) = send(
+            app,
+            "POST",
+            "/api/vector/points/upsert",
+            Some(serde_json::json!({ "collection": collection, "points": points })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+async fn search(
+    app: &axum::Router,
+    collection: &str,
+    vector: &[f32],
+    extra: serde_json::Value,
+) -> Vec<(String, f64)> {
+    let mut body = serde_json::json!({ "collection": collection, "vector": vector, "topK": 10 });
+    body.as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    let (status, json) = send(app, "POST", "/api/vector/search", Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| {
+            (
+                h["id"].as_str().unwrap().to_string(),
+                h["score"].as_f64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn collections_validate_distance_and_quantization() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (status, json) = create(
+        &app,
+        serde_json::json!({ "name": "l2", "dimension": 2, "distance": "L2", "quantization": "int8" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["distance"], "euclidean");
+    assert_eq!(json["quantization"], "int8");
+
+    let (status, json) = create(
+        &app,
+        serde_json::json!({ "name": "plain", "dimension": 2, "distance": "dot" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json.get("quantization").is_none());
+
+    let (status, json) = create(
+        &app,
+        serde_json::json!({ "name": "bad", "dimension": 2, "distance": "manhattan" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "unsupported_distance");
+
+    for quantization in ["binary", "pq"] {
+        let (status, json) = create(
+            &app,
+            serde_json::json!({
+                "name": "bad",
+                "dimension": 2,
+                "distance": "euclidean",
+                "quantization": quantization
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["message"], "unsupported_quantization");
+    }
+
+    let (status, json) = send(&app, "GET", "/api/vector/collections", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.as_array().unwrap().len(), 2);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": "plain", "vector": [1.0, 0.0], "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "distance_mismatch");
+    assert_eq!(json["distance"], "dot");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": "l2", "vector": [1.0, 0.0], "oversampling": 100 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "invalid_oversampling");
+}
+
+#[tokio::test]
+async fn dot_and_euclidean_rank_by_their_metric() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    let vectors = vec![
+        vec![1.0, 0.0],
+        vec![10.0, 0.0],
+        vec![3.0, 4.0],
+        vec![0.0, -2.0],
+    ];
+    for (name, distance) in [("dots", "dot"), ("euclid", "euclidean"), ("cos", "cosine")] {
+        let (status, _) = create(
+            &app,
+            serde_json::json!({ "name": name, "dimension": 2, "distance": distance }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        upsert(&app, name, &vectors).await;
+    }
+
+    for exact in [false, true] {
+        let extra = serde_json::json!({ "exact": exact });
+        let hits = search(&app, "dots", &[1.0, 0.0], extra.clone()).await;
+        assert_eq!(hits[0], ("p1".to_string(), 10.0));
+        assert_eq!(hits[1].0, "p2");
+        assert_eq!(hits[3], ("p3".to_string(), 0.0));
+
+        let hits = search(&app, "euclid", &[0.0, 0.0], extra.clone()).await;
+        assert_eq!(
+            hits,
+            vec![
+                ("p0".to_string(), 1.0),
+                ("p3".to_string(), 2.0),
+                ("p2".to_string(), 5.0),
+                ("p1".to_string(), 10.0),
+            ]
+        );
+
+        let hits = search(&app, "cos", &[1.0, 0.0], extra).await;
+        assert_eq!(hits[0], ("p0".to_string(), 1.0));
+        assert_eq!(hits[1], ("p1".to_string(), 1.0));
+    }
+}
+
+#[tokio::test]
+async fn quantized_collections_rescore_against_full_vectors() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+
+    let mut rng = Lcg(7);
+    let vectors = (0..800).map(|_| rng.vector(32)).collect::<Vec<_>>();
+    let queries = (0..12).map(|_| rng.vector(32)).collect::<Vec<_>>();
+    let configs = [
+        ("int8_cos", "cosine", "int8"),
+        ("int8_l2", "euclidean", "int8"),
+        ("bin_dot", "dot", "binary"),
+    ];
+    for (name, distance, quantization) in configs {
+        let (status, _) = create(
+            &app,
+            serde_json::json!({
+                "name": name,
+                "dimension": 32,
+                "distance": distance,
+                "quantization": quantization
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        upsert(&app, name, &vectors).await;
+    }
+
+    for (name, _, quantization) in configs {
+        let oversampling = if quantization == "binary" { 10 } else { 4 };
+        let mut found = 0;
+        for q in &queries {
+            let exact = search(&app, name, q, serde_json::json!({ "exact": true })).await;
+            let ann = search(
+                &app,
+                name,
+                q,
+                serde_json::json!({ "oversampling": oversampling, "ef": 256 }),
+            )
+            .await;
+            let exact_ids = exact.iter().map(|(id, _)| id).collect::<HashSet<_>>();
+            found += ann.iter().filter(|(id, _)| exact_ids.contains(id)).count();
+            for (id, score) in &ann {
+                if let Some((_, expected)) = exact.iter().find(|(e, _)| e == id) {
+                    assert!((score - expected).abs() < 1e-4, "{name} {id}");
+                }
+            }
+        }
+        let recall = found as f64 / (queries.len() * 10) as f64;
+        assert!(recall >= 0.9, "{name} recall@10 {recall}");
+
+        let raw = search(
+            &app,
+            name,
+            &queries[0],
+            serde_json::json!({ "rescore": false }),
+        )
+        .await;
+        assert_eq!(raw.len(), 10);
+    }
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({
+            "collection": "int8_cos",
+            "filter": { "must": [ { "key": "i", "match": { "value": 0 } } ] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 1);
+    let before = search(&app, "int8_cos", &queries[1], serde_json::json!({})).await;
+
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    let after = search(&restarted, "int8_cos", &queries[1], serde_json::json!({})).await;
+    assert_eq!(before, after);
+    let exact = search(
+        &restarted,
+        "bin_dot",
+        &queries[2],
+        serde_json::json!({ "exact": true }),
+    )
+    .await;
+    let expected = vectors
+        .iter()
+        .map(|v| v.iter().zip(&queries[2]).map(|(a, b)| a * b).sum::<f32>())
+        .fold(f32::MIN, f32::max);
+    assert!((exact[0].1 - expected as f64).abs() < 1e-4);
+}
@@ -0,0 +1,366 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf, std::path::PathBuf) {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    (dir, static_dir, data_dir)
+}
+
+async fn seed(app: &axum::Router, collection: &str, points: Vec<serde_json::Value>) {
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": collection, "dimension": 2, "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    for chunk in points.chunks(1_000) {
+        let (status, _) = send(
+            app,
+            "POST",
+            "/api/vector/points/upsert",
+            Some(serde_json::json!({ "collection": collection, "points": chunk })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+async fn filtered(
+    app: &axum::Router,
+    collection: &str,
+    filter: serde_json::Value,
+    extra: serde_json::Value,
+) -> Vec<String> {
+    let mut body = serde_json::json!({
+        "collection": collection,
+        "vector": [1.0, 0.0],
+        "topK": 100,
+        "filter": filter
+    });
+    body.as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    let (status, json) = send(app, "POST", "/api/vector/search", Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let mut ids = json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["id"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+fn docs() -> Vec<serde_json::Value> {
+    vec![
+        serde_json::json!({ "id": "a", "vector": [1.0, 0.1], "payload": {
+            "tenant": "acme", "score": 3, "tags": ["red", "blue"],
+            "meta": { "author": { "name": "Ada" }, "createdAt": "2024-01-05T10:00:00Z" },
+            "title": "Quarterly Revenue Report"
+        } }),
+        serde_json::json!({ "id": "b", "vector": [1.0, 0.2], "payload": {
+            "tenant": "acme", "score": 7.5, "tags": ["green"],
+            "meta": { "author": { "name": "Bob" }, "createdAt": "2024-02-10T08:30:00+02:00" },
+            "title": "Hiring plan"
+        } }),
+        serde_json::json!({ "id": "c", "vector": [1.0, 0.3], "payload": {
+            "tenant": "globex", "score": 10, "archived": true,
+            "meta": { "author": { "name": "Ada" }, "createdAt": "2024-03-01" },
+            "title": "Revenue forecast"
+        } }),
+        serde_json::json!({ "id": "d", "vector": [1.0, 0.4], "payload": {
+            "tenant": "initech", "archived": null,
+            "comments": [ { "user": "zed" }, { "user": "amy" } ]
+        } }),
+    ]
+}
+
+#[tokio::test]
+async fn filters_support_boolean_clauses_ranges_and_nested_keys() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    seed(&app, "docs", docs()).await;
+
+    let cases = [
+        (
+            serde_json::json!({ "must": [ { "key": "tenant", "match": { "value": "acme" } } ] }),
+            vec!["a", "b"],
+        ),
+        (
+            serde_json::json!({ "should": [
+                { "key": "tenant", "match": { "value": "globex" } },
+                { "key": "tags", "match": { "value": "green" } }
+            ] }),
+            vec!["b", "c"],
+        ),
+        (
+            serde_json::json!({ "mustNot": [ { "key": "tenant", "match": { "any": ["acme", "initech"] } } ] }),
+            vec!["c"],
+        ),
+        (
+            serde_json::json!({ "must_not": [ { "key": "tenant", "match": { "in": ["acme"] } } ] }),
+            vec!["c", "d"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "tenant", "match": { "except": ["acme"] } } ] }),
+            vec!["c", "d"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "score", "range": { "gt": 3, "lte": 10 } } ] }),
+            vec!["b", "c"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "score", "match": { "value": 3.0 } } ] }),
+            vec!["a"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "meta.createdAt", "range": {
+                "gte": "2024-02-10T06:30:00Z", "lt": "2024-03-01T00:00:00Z"
+            } } ] }),
+            vec!["b"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "meta.createdAt", "range": { "gte": "2024-02-01" } } ] }),
+            vec!["b", "c"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "archived", "exists": true } ] }),
+            vec!["c"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "meta", "exists": false } ] }),
+            vec!["d"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "meta.author.name", "match": { "value": "Ada" } } ] }),
+            vec!["a", "c"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "comments[].user", "match": { "value": "amy" } } ] }),
+            vec!["d"],
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "title", "match": { "text": "revenue" } } ] }),
+            vec!["a", "c"],
+        ),
+        (
+            serde_json::json!({ "must": [
+                { "key": "meta.author.name", "match": { "value": "Ada" } },
+                { "should": [
+                    { "key": "tenant", "match": { "value": "globex" } },
+                    { "key": "score", "range": { "lt": 1 } }
+                ] }
+            ] }),
+            vec!["c"],
+        ),
+    ];
+    for (filter, expected) in cases {
+        for exact in [false, true] {
+            let ids = filtered(
+                &app,
+                "docs",
+                filter.clone(),
+                serde_json::json!({ "exact": exact }),
+            )
+            .await;
+            assert_eq!(ids, expected, "{filter} exact={exact}");
+        }
+    }
+
+    for (filter, message) in [
+        (
+            serde_json::json!({ "must": [ { "key": "", "match": { "value": 1 } } ] }),
+            "invalid_filter_key",
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "a", "match": { "value": 1 }, "exists": true } ] }),
+            "invalid_filter_condition",
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "a", "match": { "value": 1, "text": "x" } } ] }),
+            "invalid_filter_match",
+        ),
+        (
+            serde_json::json!({ "must": [ { "key": "a", "range": { "gt": "yesterday" } } ] }),
+            "invalid_filter_range",
+        ),
+    ] {
+        let (status, json) = send(
+            &app,
+            "POST",
+            "/api/vector/search",
+            Some(
+                serde_json::json!({ "collection": "docs", "vector": [1.0, 0.0], "filter": filter }),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(json["message"], message);
+    }
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({
+            "collection": "docs",
+            "filter": { "must": [
+                { "key": "meta.createdAt", "range": { "lt": "2024-02-15" } },
+                { "key": "tags", "match": { "any": ["red", "green"] } }
+            ] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 2);
+    assert_eq!(
+        filtered(&app, "docs", serde_json::json!({}), serde_json::json!({})).await,
+        vec!["c", "d"]
+    );
+}
+
+#[tokio::test]
+async fn payload_indexes_scope_filtered_search_and_persist() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+    let tenants = ["acme", "globex", "initech", "umbrella"];
+    let points = (0..3_000)
+        .map(|i| {
+            let angle = i as f32 * 0.001;
+            serde_json::json!({
+                "id": format!("p{i}"),
+                "vector": [angle.cos(), angle.sin()],
+                "payload": {
+                    "tenant": if i % 100 == 0 { "tiny" } else { tenants[i % 4] },
+                    "day": format!("2024-01-{:02}", i % 28 + 1),
+                    "n": i
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    seed(&app, "shared", points).await;
+
+    let tenant_filter =
+        serde_json::json!({ "must": [ { "key": "tenant", "match": { "value": "tiny" } } ] });
+    let day_filter = serde_json::json!({ "must": [
+        { "key": "tenant", "match": { "any": ["acme", "globex"] } },
+        { "key": "day", "range": { "gte": "2024-01-10", "lt": "2024-01-12" } }
+    ] });
+    let before_tenant = filtered(
+        &app,
+        "shared",
+        tenant_filter.clone(),
+        serde_json::json!({ "exact": true }),
+    )
+    .await;
+    let before_day = filtered(
+        &app,
+        "shared",
+        day_filter.clone(),
+        serde_json::json!({ "exact": true }),
+    )
+    .await;
+    assert_eq!(before_tenant.len(), 30);
+    assert!(!before_day.is_empty());
+
+    for (field, schema) in [("tenant", "keyword"), ("day", "datetime"), ("n", "integer")] {
+        let (status, json) = send(
+            &app,
+            "POST",
+            "/api/vector/collections/index",
+            Some(serde_json::json!({ "collection": "shared", "field": field, "schema": schema })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{json}");
+    }
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/index",
+        Some(serde_json::json!({ "collection": "shared", "field": "tenant", "schema": "geo" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "unsupported_schema");
+
+    assert_eq!(
+        filtered(&app, "shared", tenant_filter.clone(), serde_json::json!({})).await,
+        before_tenant
+    );
+    assert_eq!(
+        filtered(&app, "shared", day_filter.clone(), serde_json::json!({})).await,
+        before_day
+    );
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({ "collection": "shared", "points": [
+            { "id": "p0", "vector": [1.0, 0.0], "payload": { "tenant": "acme", "n": 0 } },
+            { "id": "fresh", "vector": [0.0, 1.0], "payload": { "tenant": "tiny", "n": 5000 } }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({
+            "collection": "shared",
+            "filter": { "must": [
+                { "key": "tenant", "match": { "value": "tiny" } },
+                { "key": "n", "range": { "gte": 2000 } }
+            ] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 11);
+    let tiny = filtered(&app, "shared", tenant_filter.clone(), serde_json::json!({})).await;
+    assert_eq!(tiny.len(), 19);
+    assert!(!tiny.contains(&"p0".to_string()));
+
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    let (status, json) = send(&restarted, "GET", "/api/vector/collections", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        json[0]["payloadIndexes"],
+        serde_json::json!([
+            { "field": "day", "schema": "datetime" },
+            { "field": "n", "schema": "number" },
+            { "field": "tenant", "schema": "keyword" }
+        ])
+    );
+    assert_eq!(
+        filtered(&restarted, "shared", tenant_filter, serde_json::json!({})).await,
+        tiny
+    );
+
+    let (status, json) = send(
+        &restarted,
+        "POST",
+        "/api/vector/collections/index/delete",
+        Some(serde_json::json!({ "collection": "shared", "field": "day" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["payloadIndexes"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        filtered(&restarted, "shared", day_filter, serde_json::json!({})).await,
+        before_day
+    );
+}
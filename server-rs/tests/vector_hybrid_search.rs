@@ -0,0 +1,252 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+async fn search(app: &axum::Router, body: serde_json::Value) -> Vec<String> {
+    let mut req = serde_json::json!({ "collection": "kb", "topK": 3 });
+    req.as_object_mut()
+        .unwrap()
+        .extend(body.as_object().unwrap().clone());
+    let (status, json) = send(app, "POST", "/api/vector/search", Some(req)).await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn seed(app: &axum::Router) {
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": "kb", "dimension": 3, "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/collections/index",
+        Some(serde_json::json!({ "collection": "kb", "field": "body", "schema": "text" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let points = serde_json::json!([
+        { "id": "reset-guide", "vector": [1.0, 0.0, 0.0], "payload": {
+            "body": "How to recover when the network drops connections", "lang": "en" } },
+        { "id": "reset-guide-copy", "vector": [0.99, 0.01, 0.0], "payload": {
+            "body": "How to recover when the network drops connections (mirror)", "lang": "en" } },
+        { "id": "reset-guide-v2", "vector": [0.98, 0.02, 0.0], "payload": {
+            "body": "Recovering dropped network connections", "lang": "en" } },
+        { "id": "sku", "vector": [0.0, 0.0, 1.0], "payload": {
+            "body": "Replacement filter SKU-4471-B fits model X2", "lang": "en" } },
+        { "id": "err", "vector": [0.0, 1.0, 0.0], "payload": {
+            "body": "Client logs ERR_CONN_RESET after proxy restart", "lang": "en" } },
+        { "id": "zh", "vector": [0.5, 0.5, 0.0], "payload": {
+            "body": "数据库连接超时，请检查防火墙", "lang": "zh" } },
+        { "id": "other", "vector": [0.7, 0.0, 0.7], "payload": {
+            "body": ["Shipping times", "for filter cartridges"], "lang": "en" } }
+    ]);
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({ "collection": "kb", "points": points })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn keyword_search_finds_codes_error_strings_and_cjk() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+    seed(&app).await;
+
+    assert_eq!(
+        search(&app, serde_json::json!({ "query": "sku-4471-b" })).await[0],
+        "sku"
+    );
+    assert_eq!(
+        search(&app, serde_json::json!({ "query": "ERR_CONN_RESET" })).await,
+        vec!["err"]
+    );
+    assert_eq!(
+        search(&app, serde_json::json!({ "query": "conn reset" })).await[0],
+        "err"
+    );
+    assert_eq!(
+        search(&app, serde_json::json!({ "query": "连接超时" })).await,
+        vec!["zh"]
+    );
+    assert_eq!(
+        search(&app, serde_json::json!({ "query": "cartridges" })).await,
+        vec!["other"]
+    );
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": "kb", "query": "filter", "topK": 5 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let hits = json["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 2);
+    assert!(hits[0]["keywordScore"].as_f64().unwrap() > 0.0);
+    assert!(hits[0].get("vectorScore").is_none());
+
+    assert!(search(
+        &app,
+        serde_json::json!({
+            "query": "连接超时",
+            "filter": { "must": [ { "key": "lang", "match": { "value": "en" } } ] }
+        })
+    )
+    .await
+    .is_empty());
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({ "collection": "kb", "points": [
+            { "id": "sku", "vector": [0.0, 0.0, 1.0], "payload": { "body": "Discontinued part" } }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(search(&app, serde_json::json!({ "query": "SKU-4471-B" }))
+        .await
+        .is_empty());
+
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    assert_eq!(
+        search(&restarted, serde_json::json!({ "query": "discontinued" })).await,
+        vec!["sku"]
+    );
+
+    for (body, message) in [
+        (
+            serde_json::json!({ "mode": "hybrid", "vector": [1.0, 0.0, 0.0] }),
+            "query_required",
+        ),
+        (
+            serde_json::json!({ "query": "x", "textField": "title" }),
+            "text_index_missing",
+        ),
+        (
+            serde_json::json!({ "query": "x", "alpha": 2 }),
+            "invalid_alpha",
+        ),
+        (
+            serde_json::json!({ "query": "x", "mmr": { "lambda": -1 } }),
+            "invalid_mmr_lambda",
+        ),
+        (
+            serde_json::json!({ "query": "x", "mode": "hybrid" }),
+            "dimension_mismatch",
+        ),
+    ] {
+        let mut req = serde_json::json!({ "collection": "kb" });
+        req.as_object_mut()
+            .unwrap()
+            .extend(body.as_object().unwrap().clone());
+        let (status, json) = send(&restarted, "POST", "/api/vector/search", Some(req)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(json["message"], message);
+    }
+}
+
+#[tokio::test]
+async fn hybrid_search_fuses_rankings_and_mmr_diversifies() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    seed(&app).await;
+
+    let query_vector = serde_json::json!([1.0, 0.0, 0.0]);
+    let vector_only = search(&app, serde_json::json!({ "vector": query_vector })).await;
+    assert_eq!(
+        vector_only,
+        vec!["reset-guide", "reset-guide-copy", "reset-guide-v2"]
+    );
+    assert!(!vector_only.contains(&"err".to_string()));
+
+    let hybrid = search(
+        &app,
+        serde_json::json!({ "vector": query_vector, "query": "ERR_CONN_RESET" }),
+    )
+    .await;
+    assert_eq!(hybrid[0], "err");
+    assert!(hybrid.contains(&"reset-guide".to_string()));
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "kb",
+            "vector": query_vector,
+            "query": "ERR_CONN_RESET",
+            "fusion": "weighted",
+            "alpha": 0.5,
+            "topK": 2
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let err = json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|h| h["id"] == "err")
+        .unwrap();
+    assert!(err["keywordScore"].as_f64().unwrap() > 0.0);
+    assert!(err["vectorScore"].is_number());
+
+    let weighted_vector = search(
+        &app,
+        serde_json::json!({ "vector": query_vector, "query": "ERR_CONN_RESET", "fusion": "weighted", "alpha": 1.0 }),
+    )
+    .await;
+    assert_eq!(weighted_vector, vector_only);
+    let weighted_keyword = search(
+        &app,
+        serde_json::json!({ "vector": query_vector, "query": "ERR_CONN_RESET", "fusion": "weighted", "alpha": 0.0 }),
+    )
+    .await;
+    assert_eq!(weighted_keyword[0], "err");
+
+    let diverse = search(
+        &app,
+        serde_json::json!({ "vector": query_vector, "mmr": { "lambda": 0.3, "fetchK": 7 } }),
+    )
+    .await;
+    assert_eq!(diverse[0], "reset-guide");
+    assert!(!diverse.contains(&"reset-guide-copy".to_string()));
+    assert!(!diverse.contains(&"reset-guide-v2".to_string()));
+
+    let relevant = search(
+        &app,
+        serde_json::json!({ "vector": query_vector, "mmr": { "lambda": 1.0 } }),
+    )
+    .await;
+    assert_eq!(relevant, vector_only);
+}
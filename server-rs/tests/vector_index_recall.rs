@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 40) as f32 / (1u64 << 24) as f32) * 2.0 - 1.0
+    }
+
+    fn vector(&mut self, dim: usize) -> Vec<f32> {
+        (0..dim).map(|_| self.next()).collect()
+    }
+
+    fn near(&mut self, centroids: &[Vec<f32>]) -> Vec<f32> {
+        let c = &centroids
+            [((self.next() + 1.0) / 2.0 * centroids.len() as f32) as usize % centroids.len()];
+        c.iter().map(|x| x + 0.25 * self.next()).collect()
+    }
+}
+
+async fn seed_collection(app: &axum::Router, name: &str, vectors: &[Vec<f32>]) {
+    let dim = vectors[0].len();
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": name, "dimension": dim, "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let indexed = vectors.iter().enumerate().collect::<Vec<_>>();
+    for chunk in indexed.chunks(1_000) {
+        let points = chunk
+            .iter()
+            .map(|(i, v)| {
+                serde_json::json!({
+                    "id": format!("p{i}"),
+                    "vector": v,
+                    "payload": { "parity": i % 2 }
+                })
+            })
+            .collect::<Vec<_>>();
+        let (status, _) = send(
+            app,
+            "POST",
+            "/api/vector/points/upsert",
+            Some(serde_json::json!({ "collection": name, "points": points })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+async fn search_ids(
+    app: &axum::Router,
+    collection: &str,
+    vector: &[f32],
+    extra: serde_json::Value,
+) -> Vec<String> {
+    let mut body = serde_json::json!({ "collection": collection, "vector": vector, "topK": 10 });
+    body.as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    let (status, json) = send(app, "POST", "/api/vector/search", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn recall(
+    app: &axum::Router,
+    collection: &str,
+    queries: &[Vec<f32>],
+    ann_params: serde_json::Value,
+) -> f64 {
+    let mut found = 0usize;
+    let mut total = 0usize;
+    for q in queries {
+        let exact = search_ids(app, collection, q, serde_json::json!({ "exact": true })).await;
+        let ann = search_ids(app, collection, q, ann_params.clone())
+            .await
+            .into_iter()
+            .collect::<HashSet<_>>();
+        total += exact.len();
+        found += exact.iter().filter(|id| ann.contains(*id)).count();
+    }
+    found as f64 / total as f64
+}
+
+#[tokio::test]
+async fn hnsw_search_matches_exact_search_and_survives_restart() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+
+    let mut rng = Lcg(42);
+    let vectors = (0..2_000).map(|_| rng.vector(16)).collect::<Vec<_>>();
+    seed_collection(&app, "bench", &vectors).await;
+    assert!(data_dir.join("vector/segments/bench/index.hnsw").is_file());
+
+    let queries = (0..40).map(|_| rng.vector(16)).collect::<Vec<_>>();
+    let r = recall(&app, "bench", &queries, serde_json::json!({})).await;
+    assert!(r >= 0.95, "recall@10 {r}");
+
+    let filtered = search_ids(
+        &app,
+        "bench",
+        &queries[0],
+        serde_json::json!({ "filter": { "must": [ { "key": "parity", "match": { "value": 1 } } ] } }),
+    )
+    .await;
+    assert_eq!(filtered.len(), 10);
+    assert!(filtered
+        .iter()
+        .all(|id| id[1..].parse::<usize>().unwrap() % 2 == 1));
+
+    let top = search_ids(&app, "bench", &queries[1], serde_json::json!({})).await;
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({
+            "collection": "bench",
+            "filter": { "must": [ { "key": "parity", "match": { "value": 0 } } ] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 1_000);
+    let after = search_ids(&app, "bench", &queries[1], serde_json::json!({})).await;
+    assert!(after
+        .iter()
+        .all(|id| id[1..].parse::<usize>().unwrap() % 2 == 1));
+    assert!(top
+        .iter()
+        .filter(|id| id[1..].parse::<usize>().unwrap() % 2 == 1)
+        .all(|id| after.contains(id)));
+
+    // The delete schedules a background compaction on this app; let it swap
+    // generations before a second app opens the same files.
+    for _ in 0..500 {
+        let (status, stats) = send(
+            &app,
+            "POST",
+            "/api/vector/collections/stats",
+            Some(serde_json::json!({ "collection": "bench" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        if stats["deadRecords"] == 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    let reloaded = search_ids(&restarted, "bench", &queries[1], serde_json::json!({})).await;
+    assert_eq!(reloaded, after);
+    let r = recall(&restarted, "bench", &queries, serde_json::json!({})).await;
+    assert!(r >= 0.95, "recall@10 after delete {r}");
+}
+
+#[tokio::test]
+#[ignore = "benchmark; run with --release -- --ignored --nocapture"]
+async fn hnsw_benchmark_100k_points() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let started = Instant::now();
+    let mut rng = Lcg(42);
+    let centroids = (0..500).map(|_| rng.vector(64)).collect::<Vec<_>>();
+    let vectors = (0..100_000)
+        .map(|_| rng.near(&centroids))
+        .collect::<Vec<_>>();
+    seed_collection(&app, "bench", &vectors).await;
+    println!("indexed 100k points in {:?}", started.elapsed());
+
+    let queries = (0..200).map(|_| rng.near(&centroids)).collect::<Vec<_>>();
+    let started = Instant::now();
+    for q in &queries {
+        search_ids(&app, "bench", q, serde_json::json!({})).await;
+    }
+    println!("hnsw: {:?}/query", started.elapsed() / queries.len() as u32);
+    let started = Instant::now();
+    for q in &queries[..20] {
+        search_ids(&app, "bench", q, serde_json::json!({ "exact": true })).await;
+    }
+    println!("exact: {:?}/query", started.elapsed() / 20);
+    for ef in [128, 256, 512] {
+        let r = recall(
+            &app,
+            "bench",
+            &queries[..50],
+            serde_json::json!({ "ef": ef }),
+        )
+        .await;
+        println!("recall@10 ef={ef}: {r:.3}");
+    }
+}
@@ -0,0 +1,316 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+
+mod common;
+
+use common::{send, setup, spawn_mock_openai, wait_for_job};
+
+fn embed(text: &str) -> Vec<f64> {
+    vec![
+        1.0,
+        text.len() as f64 / 100.0,
+        text.split_whitespace().count() as f64,
+    ]
+}
+
+fn serve_embeddings() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let url = spawn_mock_openai(move |req| {
+        let data = req.body["input"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                serde_json::json!({ "index": i, "embedding": embed(text.as_str().unwrap_or("")) })
+            })
+            .collect::<Vec<_>>();
+        seen2.lock().unwrap().push(req.body.clone());
+        serde_json::json!({ "data": data })
+    });
+    (url, seen)
+}
+
+async fn create_provider_and_collection(app: &axum::Router, embed_url: &str) -> String {
+    let (status, provider) = send(
+        app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": embed_url,
+            "apiKey": "sk-test",
+            "defaultEmbeddingModel": "mock-embed"
+        })),
+    )
+    .await;
+    assert!(status.is_success(), "{provider}");
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": "docs", "dimension": 3, "distance": "cosine" })),
+    )
+    .await;
+    assert!(status.is_success(), "{json}");
+    provider["id"].as_str().unwrap().to_string()
+}
+
+async fn get_points(app: &axum::Router, ids: &[&str]) -> Vec<serde_json::Value> {
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/points/get",
+        Some(serde_json::json!({ "collection": "docs", "ids": ids })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    json["points"].as_array().unwrap().clone()
+}
+
+async fn ingest(app: &axum::Router, body: serde_json::Value) -> serde_json::Value {
+    let (status, json) = send(app, "POST", "/api/jobs/ingest-documents", Some(body)).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{json}");
+    assert_eq!(json["job"]["jobType"], "ingest_documents");
+    wait_for_job(app, json["job"]["id"].as_str().unwrap()).await
+}
+
+fn words(n: usize, tag: &str) -> String {
+    (0..n)
+        .map(|i| format!("{tag}{i}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[tokio::test]
+async fn ingest_chunks_documents_and_reembeds_only_changed_ones() {
+    let (embed_url, seen) = serve_embeddings();
+    let (_dir, app) = setup();
+    let provider_id = create_provider_and_collection(&app, &embed_url).await;
+
+    let long = words(40, "w");
+    let request = |docs: serde_json::Value| {
+        serde_json::json!({
+            "providerId": provider_id,
+            "collection": "docs",
+            "documents": docs,
+            "chunking": { "strategy": "tokens", "chunkSize": 16, "overlap": 4 },
+            "batchSize": 3
+        })
+    };
+    let docs = serde_json::json!([
+        { "id": "a", "text": long, "title": "Alpha", "metadata": { "lang": "en" } },
+        { "id": "b", "text": "short note" }
+    ]);
+
+    let job = ingest(&app, request(docs.clone())).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["stats"]["ingested"], 2);
+    assert_eq!(job["stats"]["chunks"], 4);
+    assert_eq!(job["stats"]["batches"], 2);
+    assert_eq!(job["stats"]["model"], "mock-embed");
+    {
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(seen
+            .iter()
+            .all(|b| b["model"] == "mock-embed" && b["input"].as_array().unwrap().len() <= 3));
+    }
+
+    let points = get_points(&app, &["a#0", "a#1", "a#2", "a#3", "b#0"]).await;
+    let ids = points
+        .iter()
+        .map(|p| p["id"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["a#0", "a#1", "a#2", "b#0"]);
+    let chars = long.chars().collect::<Vec<_>>();
+    for (idx, p) in points[..3].iter().enumerate() {
+        let payload = &p["payload"];
+        assert_eq!(payload["_documentId"], "a");
+        assert_eq!(payload["_chunkIndex"], idx);
+        assert_eq!(payload["title"], "Alpha");
+        assert_eq!(payload["lang"], "en");
+        let (start, end) = (
+            payload["_start"].as_u64().unwrap() as usize,
+            payload["_end"].as_u64().unwrap() as usize,
+        );
+        let slice = chars[start..end].iter().collect::<String>();
+        assert_eq!(payload["text"], slice);
+        assert!(slice.split_whitespace().count() <= 16);
+    }
+    assert!(points[0]["payload"]["text"]
+        .as_str()
+        .unwrap()
+        .ends_with("w15"));
+    assert!(points[1]["payload"]["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("w12 "));
+    assert_eq!(points[3]["payload"]["text"], "short note");
+
+    let job = ingest(&app, request(docs)).await;
+    assert_eq!(job["stats"]["skipped"], 2);
+    assert_eq!(job["stats"]["ingested"], 0);
+    assert_eq!(seen.lock().unwrap().len(), 2);
+
+    let docs = serde_json::json!([
+        { "id": "a", "text": words(10, "v"), "title": "Alpha", "metadata": { "lang": "en" } },
+        { "id": "b", "text": "short note" }
+    ]);
+    let job = ingest(&app, request(docs)).await;
+    let stats = &job["stats"];
+    assert_eq!(stats["skipped"], 1);
+    assert_eq!(stats["ingested"], 1);
+    assert_eq!(stats["chunks"], 1);
+    assert_eq!(stats["deletedChunks"], 2);
+    assert_eq!(seen.lock().unwrap().len(), 3);
+    let ids = get_points(&app, &["a#0", "a#1", "a#2", "b#0"])
+        .await
+        .iter()
+        .map(|p| p["id"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["a#0", "b#0"]);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/ingest-documents",
+        Some(serde_json::json!({
+            "providerId": provider_id,
+            "collection": "docs",
+            "documents": [{ "id": "a", "text": "x" }, { "id": "a", "text": "y" }]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{json}");
+    assert_eq!(json["error"], "validation_failed");
+}
+
+#[tokio::test]
+async fn ingest_splits_markdown_from_a_datasource_by_headings() {
+    use sqlx::{Connection, Executor};
+
+    let (embed_url, _seen) = serve_embeddings();
+    let (dir, app) = setup();
+    let provider_id = create_provider_and_collection(&app, &embed_url).await;
+
+    let db_path = dir.path().join("docs.db");
+    std::fs::File::create(&db_path).unwrap();
+    let url = sqlite_url(&db_path);
+    sqlx::any::install_default_drivers();
+    let mut conn = <sqlx::AnyConnection as Connection>::connect(&url)
+        .await
+        .unwrap();
+    conn.execute("CREATE TABLE pages (id INTEGER PRIMARY KEY, body TEXT, lang TEXT)")
+        .await
+        .unwrap();
+    conn.execute(
+        "INSERT INTO pages (body, lang) VALUES \
+         ('intro line\n# Guide\nSetup text\n## Install\nrun it\n```\n# not a heading\n```\n# FAQ\nq and a\n', 'en'), \
+         ('plain text only', 'zh')",
+    )
+    .await
+    .unwrap();
+    conn.close().await.unwrap();
+
+    let (status, ds) = send(
+        &app,
+        "POST",
+        "/api/datasources",
+        Some(serde_json::json!({
+            "name": "pages",
+            "driver": "sqlite",
+            "url": url,
+            "allowSchema": true
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{ds}");
+
+    let request = |prune_missing: bool| {
+        serde_json::json!({
+            "providerId": provider_id,
+            "collection": "docs",
+            "dataSourceId": ds["id"],
+            "query": "SELECT id, body, lang FROM pages",
+            "idField": "id",
+            "textField": "body",
+            "metadataFields": ["lang"],
+            "chunking": { "strategy": "headings", "chunkSize": 64, "overlap": 8 },
+            "pruneMissing": prune_missing
+        })
+    };
+    let job = ingest(&app, request(false)).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["stats"]["documents"], 2);
+    assert_eq!(job["stats"]["chunks"], 5);
+
+    let points = get_points(&app, &["1#0", "1#1", "1#2", "1#3", "2#0"]).await;
+    let headings = points
+        .iter()
+        .map(|p| p["payload"]["_headings"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headings,
+        vec![
+            serde_json::Value::Null,
+            serde_json::json!(["Guide"]),
+            serde_json::json!(["Guide", "Install"]),
+            serde_json::json!(["FAQ"]),
+            serde_json::Value::Null,
+        ]
+    );
+    assert_eq!(points[0]["payload"]["text"], "intro line");
+    assert!(points[2]["payload"]["text"]
+        .as_str()
+        .unwrap()
+        .contains("# not a heading"));
+    assert_eq!(points[3]["payload"]["text"], "# FAQ\nq and a");
+    assert_eq!(points[4]["payload"]["lang"], "zh");
+    assert_eq!(points[4]["payload"]["_documentId"], "2");
+
+    let mut conn = <sqlx::AnyConnection as Connection>::connect(&url)
+        .await
+        .unwrap();
+    conn.execute("DELETE FROM pages WHERE id = 2")
+        .await
+        .unwrap();
+    conn.close().await.unwrap();
+    let job = ingest(&app, request(false)).await;
+    assert_eq!(job["stats"]["skipped"], 1);
+    assert_eq!(job["stats"]["pruned"], 0);
+    assert_eq!(get_points(&app, &["2#0"]).await.len(), 1);
+    let job = ingest(&app, request(true)).await;
+    assert_eq!(job["status"], "succeeded", "{job}");
+    assert_eq!(job["stats"]["pruned"], 1);
+    assert_eq!(job["stats"]["deletedChunks"], 1);
+    assert!(get_points(&app, &["2#0"]).await.is_empty());
+    assert_eq!(get_points(&app, &["1#0"]).await.len(), 1);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/ingest-documents",
+        Some(serde_json::json!({
+            "providerId": provider_id,
+            "collection": "docs",
+            "dataSourceId": ds["id"],
+            "query": "DELETE FROM pages",
+            "idField": "id",
+            "textField": "body"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{json}");
+    assert_eq!(json["error"], "readonly_required");
+}
+
+fn sqlite_url(path: &std::path::Path) -> String {
+    let p = path.to_string_lossy().replace('\\', "/");
+    let p = p.strip_prefix('/').unwrap_or(p.as_str());
+    format!("sqlite:///{p}")
+}
@@ -0,0 +1,448 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt as _;
+use tempfile::tempdir;
+use tower::ServiceExt as _;
+
+mod common;
+
+use common::send;
+
+async fn send_bytes(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    body: Body,
+    content_type: &str,
+) -> (StatusCode, Vec<u8>) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", content_type)
+                .body(body)
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, bytes.to_vec())
+}
+
+fn app_in(dir: &std::path::Path) -> axum::Router {
+    let static_dir = dir.join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    server_rs::build_app_with_data_dir(static_dir, dir.join("data"))
+}
+
+fn raw_archive(meta: &serde_json::Value, vectors: &[u8]) -> Vec<u8> {
+    use std::io::Write as _;
+    let meta = meta.to_string().into_bytes();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"CEVSNAP1").unwrap();
+    encoder.write_all(&1u64.to_le_bytes()).unwrap();
+    for section in [&meta[..], vectors, &[], &[]] {
+        encoder
+            .write_all(&(section.len() as u64).to_le_bytes())
+            .unwrap();
+        encoder.write_all(section).unwrap();
+    }
+    encoder.finish().unwrap()
+}
+
+fn vector_for(i: usize) -> Vec<f32> {
+    vec![1.0 + i as f32, (i % 5) as f32 - 2.0, 0.5 * (i % 3) as f32]
+}
+
+fn ids_of(json: &serde_json::Value) -> Vec<String> {
+    json["points"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn scroll_all(
+    app: &axum::Router,
+    collection: &str,
+    filter: Option<serde_json::Value>,
+) -> Vec<String> {
+    let mut cursor = serde_json::Value::Null;
+    let mut out = Vec::new();
+    loop {
+        let (status, json) = send(
+            app,
+            "POST",
+            "/api/vector/points/scroll",
+            Some(serde_json::json!({
+                "collection": collection,
+                "filter": filter,
+                "limit": 7,
+                "cursor": cursor,
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{json}");
+        out.extend(ids_of(&json));
+        cursor = json["nextCursor"].clone();
+        if cursor.is_null() {
+            return out;
+        }
+    }
+}
+
+async fn seed(app: &axum::Router) {
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({
+            "name": "notes",
+            "dimension": 3,
+            "distance": "cosine",
+            "quantization": "int8",
+            "embeddingModel": "mock-embed"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/vector/collections/index",
+        Some(serde_json::json!({ "collection": "notes", "field": "tag", "schema": "keyword" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let points = (0..25)
+        .map(|i| {
+            let mut payload = serde_json::json!({
+                "tag": if i % 2 == 0 { "even" } else { "odd" },
+                "n": i,
+                "meta": { "lang": "en" },
+            });
+            if i < 10 {
+                payload["tags"] = serde_json::json!(["a", i]);
+            }
+            serde_json::json!({ "id": format!("p{i:02}"), "vector": vector_for(i), "payload": payload })
+        })
+        .collect::<Vec<_>>();
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": "notes",
+            "points": points,
+            "embeddingModel": "mock-embed"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({
+            "collection": "notes",
+            "filter": { "must": [{ "key": "n", "range": { "gte": 20 } }] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["deleted"], 5);
+}
+
+#[tokio::test]
+async fn stats_scroll_and_get_points() {
+    let dir = tempdir().unwrap();
+    let app = app_in(dir.path());
+    seed(&app).await;
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/stats",
+        Some(serde_json::json!({ "collection": "notes" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["points"], 20);
+    assert_eq!(json["records"], 30);
+    assert_eq!(json["deadRecords"], 10);
+    assert_eq!(json["collection"]["embeddingModel"], "mock-embed");
+    assert!(json["bytes"]["vectors"].as_u64().unwrap() > 0);
+    let schema = &json["payloadSchema"];
+    assert_eq!(schema["tag"]["points"], 20);
+    assert_eq!(schema["tag"]["types"]["string"], 20);
+    assert_eq!(schema["tag"]["index"], "keyword");
+    assert_eq!(schema["n"]["types"]["number"], 20);
+    assert_eq!(schema["meta"]["types"]["object"], 20);
+    assert_eq!(schema["meta.lang"]["points"], 20);
+    assert_eq!(schema["tags"]["points"], 10);
+    assert_eq!(schema["tags[]"]["types"]["string"], 10);
+    assert_eq!(schema["tags[]"]["types"]["number"], 10);
+
+    let all = scroll_all(&app, "notes", None).await;
+    let expected = (0..20).map(|i| format!("p{i:02}")).collect::<Vec<_>>();
+    assert_eq!(all, expected);
+    let odd = scroll_all(
+        &app,
+        "notes",
+        Some(serde_json::json!({ "must": [{ "key": "tag", "match": { "value": "odd" } }] })),
+    )
+    .await;
+    assert_eq!(odd.len(), 10);
+    assert!(odd
+        .iter()
+        .all(|id| id[1..].parse::<usize>().unwrap() % 2 == 1));
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/scroll",
+        Some(serde_json::json!({ "collection": "notes", "limit": 2, "withVector": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids_of(&json), vec!["p00", "p01"]);
+    assert_eq!(json["nextCursor"], "p01");
+    let v = json["points"][1]["vector"].as_array().unwrap();
+    for (got, want) in v.iter().zip(vector_for(1)) {
+        assert!((got.as_f64().unwrap() as f32 - want).abs() < 1e-6);
+    }
+    assert_eq!(json["points"][1]["payload"]["tag"], "odd");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/get",
+        Some(serde_json::json!({ "collection": "notes", "ids": ["p03", "p22", "missing", "p01"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids_of(&json), vec!["p03", "p01"]);
+    assert!(json["points"][0].get("vector").is_none());
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/scroll",
+        Some(serde_json::json!({
+            "collection": "notes",
+            "filter": { "must": [{ "key": "", "match": { "value": 1 } }] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{json}");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/stats",
+        Some(serde_json::json!({ "collection": "missing" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "not_found");
+}
+
+#[tokio::test]
+async fn drop_snapshot_and_restore_collections() {
+    let dir = tempdir().unwrap();
+    let app = app_in(dir.path());
+    seed(&app).await;
+
+    let (status, archive) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/snapshot",
+        Body::from(serde_json::json!({ "collection": "notes" }).to_string()),
+        "application/json",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!archive.is_empty());
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/delete",
+        Some(serde_json::json!({ "collection": "notes" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    let (_, json) = send(&app, "GET", "/api/vector/collections", None).await;
+    assert_eq!(json, serde_json::json!([]));
+    assert!(!dir.path().join("data/vector/segments/notes").exists());
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": "notes", "vector": [1.0, 0.0, 0.0] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/delete",
+        Some(serde_json::json!({ "collection": "notes" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let other = tempdir().unwrap();
+    let other_app = app_in(other.path());
+    for target in [&app, &other_app] {
+        let (status, body) = send_bytes(
+            target,
+            "POST",
+            "/api/vector/collections/restore",
+            Body::from(archive.clone()),
+            "application/octet-stream",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["points"], 20);
+        assert_eq!(json["collection"]["name"], "notes");
+        assert_eq!(json["collection"]["quantization"], "int8");
+        assert_eq!(json["collection"]["payloadIndexes"][0]["field"], "tag");
+
+        let (status, json) = send(
+            target,
+            "POST",
+            "/api/vector/search",
+            Some(serde_json::json!({
+                "collection": "notes",
+                "vector": vector_for(7),
+                "topK": 1,
+                "filter": { "must": [{ "key": "tag", "match": { "value": "odd" } }] }
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{json}");
+        assert_eq!(json["hits"][0]["id"], "p07");
+        let (_, json) = send(
+            target,
+            "POST",
+            "/api/vector/points/get",
+            Some(serde_json::json!({ "collection": "notes", "ids": ["p11"], "withVector": true })),
+        )
+        .await;
+        let v = json["points"][0]["vector"].as_array().unwrap();
+        for (got, want) in v.iter().zip(vector_for(11)) {
+            assert!((got.as_f64().unwrap() as f32 - want).abs() < 1e-6);
+        }
+    }
+
+    let (status, body) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore",
+        Body::from(archive.clone()),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "already_exists");
+
+    let (status, _) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore?name=notes_copy",
+        Body::from(archive.clone()),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": "notes",
+            "points": [{ "id": "extra", "vector": [0.0, 1.0, 0.0] }],
+            "embeddingModel": "mock-embed"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore?name=notes&overwrite=true",
+        Body::from(archive.clone()),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(scroll_all(&app, "notes", None).await.len(), 20);
+    assert_eq!(scroll_all(&app, "notes_copy", None).await.len(), 20);
+    let (_, json) = send(&app, "GET", "/api/vector/collections", None).await;
+    assert_eq!(json.as_array().unwrap().len(), 2);
+
+    let (_, json) = send(&app, "GET", "/api/vector/collections", None).await;
+    let corrupt = raw_archive(&json[0], b"not a segment");
+    let (status, body) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore?name=notes&overwrite=true",
+        Body::from(corrupt),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["message"], "invalid_snapshot");
+    assert_eq!(scroll_all(&app, "notes", None).await.len(), 20);
+
+    std::fs::write(
+        dir.path().join("data/vector/segments/.notes.restore"),
+        "blocks staging",
+    )
+    .unwrap();
+    let (status, _) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore?name=notes&overwrite=true",
+        Body::from(archive.clone()),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(scroll_all(&app, "notes", None).await.len(), 20);
+    std::fs::remove_file(dir.path().join("data/vector/segments/.notes.restore")).unwrap();
+    let restarted = app_in(dir.path());
+    assert_eq!(scroll_all(&restarted, "notes", None).await.len(), 20);
+
+    let (status, body) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore?name=broken",
+        Body::from(b"not a snapshot".to_vec()),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["message"], "invalid_snapshot");
+    let (status, _) = send_bytes(
+        &app,
+        "POST",
+        "/api/vector/collections/restore?name=..%2Fescape",
+        Body::from(archive),
+        "application/octet-stream",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
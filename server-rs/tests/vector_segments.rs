@@ -0,0 +1,257 @@
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::send;
+
+fn point(i: usize) -> serde_json::Value {
+    serde_json::json!({
+        "id": format!("p{i}"),
+        "vector": [1.0, i as f32, (i % 7) as f32],
+        "payload": { "n": i }
+    })
+}
+
+async fn upsert(app: &axum::Router, collection: &str, range: std::ops::Range<usize>) {
+    let (status, _) = send(
+        app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": collection,
+            "points": range.map(point).collect::<Vec<_>>()
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+async fn search(app: &axum::Router, collection: &str, vector: serde_json::Value) -> Vec<String> {
+    let (status, json) = send(
+        app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": collection, "vector": vector, "topK": 5, "exact": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn setup() -> (tempfile::TempDir, std::path::PathBuf, std::path::PathBuf) {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    (dir, static_dir, data_dir)
+}
+
+fn generation(data_dir: &std::path::Path, collection: &str) -> u64 {
+    let text = std::fs::read_to_string(
+        data_dir
+            .join("vector/segments")
+            .join(collection)
+            .join("manifest.json"),
+    )
+    .unwrap();
+    serde_json::from_str::<serde_json::Value>(&text).unwrap()["generation"]
+        .as_u64()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn legacy_jsonl_points_are_migrated_to_segments() {
+    let (_dir, static_dir, data_dir) = setup();
+    std::fs::create_dir_all(data_dir.join("vector/collections")).unwrap();
+    std::fs::create_dir_all(data_dir.join("vector/points")).unwrap();
+    std::fs::write(
+        data_dir.join("vector/collections/legacy.json"),
+        serde_json::json!({
+            "name": "legacy",
+            "dimension": 3,
+            "distance": "cosine",
+            "createdAt": "2024-01-01T00:00:00Z"
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let lines = (0..5)
+        .map(|i| {
+            let mut p = point(i);
+            p["batchId"] = serde_json::json!("b1");
+            p.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(data_dir.join("vector/points/legacy.jsonl"), lines).unwrap();
+
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+    let hits = search(&app, "legacy", serde_json::json!([1.0, 4.0, 4.0])).await;
+    assert_eq!(hits.len(), 5);
+    assert_eq!(hits[0], "p4");
+
+    let seg = data_dir.join("vector/segments/legacy");
+    assert!(seg.join("vectors-1.bin").is_file());
+    assert!(seg.join("payloads-1.bin").is_file());
+    assert!(!data_dir.join("vector/points/legacy.jsonl").exists());
+    assert!(data_dir
+        .join("vector/points/legacy.jsonl.migrated")
+        .is_file());
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({ "collection": "legacy", "batchId": "b1" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 5);
+
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    assert!(
+        search(&restarted, "legacy", serde_json::json!([1.0, 0.0, 0.0]))
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn writes_append_to_segment_and_survive_restart() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": "docs", "dimension": 3, "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    upsert(&app, "docs", 0..100).await;
+    let vectors = data_dir.join("vector/segments/docs/vectors-1.bin");
+    let before = std::fs::read(&vectors).unwrap();
+    assert_eq!(&before[..8], b"CEVSEG01");
+
+    upsert(&app, "docs", 100..110).await;
+    let after = std::fs::read(&vectors).unwrap();
+    assert_eq!(&after[..before.len()], &before[..]);
+    let upsert_record = 1 + 4 + "p100".len() + 3 * 4 + 8 + 4;
+    assert_eq!(after.len() - before.len(), 10 * upsert_record);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({
+            "collection": "docs",
+            "filter": { "must": [ { "key": "n", "match": { "value": 109 } } ] }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 1);
+    let tombstoned = std::fs::read(&vectors).unwrap();
+    assert_eq!(&tombstoned[..after.len()], &after[..]);
+    assert_eq!(tombstoned.len() - after.len(), 1 + 4 + "p109".len());
+
+    let hits = search(&app, "docs", serde_json::json!([1.0, 109.0, 4.0])).await;
+    assert_eq!(hits.len(), 5);
+    assert!(!hits.contains(&"p109".to_string()));
+
+    let restarted = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+    let reloaded = search(&restarted, "docs", serde_json::json!([1.0, 109.0, 4.0])).await;
+    assert_eq!(reloaded, hits);
+    let (status, json) = send(
+        &restarted,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": "docs", "vector": [1.0, 50.0, 1.0], "topK": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["hits"][0]["payload"]["n"], 50);
+
+    let (status, json) = send(
+        &restarted,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": "docs", "dimension": 8, "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{json}");
+    assert_eq!(json["error"], "already_exists");
+    assert_eq!(std::fs::read(&vectors).unwrap(), tombstoned);
+    let reopened = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    assert_eq!(
+        search(&reopened, "docs", serde_json::json!([1.0, 109.0, 4.0])).await,
+        hits
+    );
+}
+
+#[tokio::test]
+async fn deletes_trigger_background_compaction() {
+    let (_dir, static_dir, data_dir) = setup();
+    let app = server_rs::build_app_with_data_dir(static_dir.clone(), data_dir.clone());
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({ "name": "churn", "dimension": 3, "distance": "cosine" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    upsert(&app, "churn", 0..600).await;
+    let grown = std::fs::metadata(data_dir.join("vector/segments/churn/vectors-1.bin"))
+        .unwrap()
+        .len();
+    assert_eq!(generation(&data_dir, "churn"), 1);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/delete",
+        Some(serde_json::json!({ "collection": "churn" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 600);
+    upsert(&app, "churn", 0..20).await;
+
+    let mut compacted = false;
+    for _ in 0..200 {
+        if generation(&data_dir, "churn") > 1 {
+            compacted = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(compacted);
+    let seg = data_dir.join("vector/segments/churn");
+    let gen = generation(&data_dir, "churn");
+    assert!(!seg.join("vectors-1.bin").exists());
+    assert!(
+        std::fs::metadata(seg.join(format!("vectors-{gen}.bin")))
+            .unwrap()
+            .len()
+            < grown / 10
+    );
+
+    let hits = search(&app, "churn", serde_json::json!([1.0, 19.0, 5.0])).await;
+    assert_eq!(hits[0], "p19");
+    let restarted = server_rs::build_app_with_data_dir(static_dir, data_dir);
+    assert_eq!(
+        search(&restarted, "churn", serde_json::json!([1.0, 19.0, 5.0])).await,
+        hits
+    );
+}
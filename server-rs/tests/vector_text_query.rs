@@ -0,0 +1,380 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{send, spawn_mock_openai};
+
+fn embed(text: &str) -> Vec<f64> {
+    match text {
+        "cat" | "kitten" => vec![1.0, 0.05, 0.0],
+        "dog" | "puppy" => vec![0.0, 1.0, 0.05],
+        _ => vec![0.05, 0.0, 1.0],
+    }
+}
+
+fn serve_embeddings() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let url = spawn_mock_openai(move |req| {
+        let data = req.body["input"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                serde_json::json!({ "index": i, "embedding": embed(text.as_str().unwrap_or("")) })
+            })
+            .collect::<Vec<_>>();
+        seen2.lock().unwrap().push(req.body.clone());
+        serde_json::json!({ "data": data })
+    });
+    (url, seen)
+}
+
+fn hit_ids(json: &serde_json::Value) -> Vec<&str> {
+    json["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["id"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn text_queries_are_embedded_with_the_collection_model() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let (embed_url, seen) = serve_embeddings();
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir);
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": embed_url,
+            "apiKey": "sk-test",
+            "defaultEmbeddingModel": "provider-default"
+        })),
+    )
+    .await;
+    let provider_id = provider["id"].as_str().unwrap().to_string();
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({
+            "name": "pets",
+            "dimension": 3,
+            "distance": "cosine",
+            "embeddingModel": "mock-small"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(json["embeddingModel"], "mock-small");
+
+    let points = serde_json::json!([
+        { "id": "cat", "vector": embed("cat"), "payload": { "name": "cat" } },
+        { "id": "dog", "vector": embed("dog"), "payload": { "name": "dog" } },
+        { "id": "fish", "vector": embed("fish"), "payload": { "name": "fish" } }
+    ]);
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": "pets",
+            "points": points,
+            "embeddingModel": "mock-large"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "embedding_model_mismatch");
+    assert_eq!(json["embeddingModel"], "mock-small");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({ "collection": "pets", "points": points })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "embedding_model_required");
+    assert_eq!(json["embeddingModel"], "mock-small");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": "pets",
+            "points": points,
+            "embeddingModel": "mock-small"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "pets",
+            "query": "puppy",
+            "providerId": provider_id,
+            "topK": 2
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(hit_ids(&json), vec!["dog", "cat"]);
+    {
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0]["model"], "mock-small");
+        assert_eq!(seen[0]["input"], serde_json::json!(["puppy"]));
+    }
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "pets",
+            "query": "kitten",
+            "providerId": provider_id,
+            "embeddingModel": "mock-large"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "embedding_model_mismatch");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "pets",
+            "vector": embed("cat"),
+            "embeddingModel": "mock-large"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "embedding_model_mismatch");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({ "collection": "pets", "providerId": provider_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "query_required");
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/vector/collections/create",
+        Some(serde_json::json!({
+            "name": "wide",
+            "dimension": 4,
+            "distance": "cosine",
+            "embeddingModel": "mock-small"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "wide",
+            "query": "cat",
+            "providerId": provider_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "dimension_mismatch");
+    assert_eq!(json["dimension"], 4);
+    assert_eq!(json["embeddingDimension"], 3);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "pets",
+            "query": "cat",
+            "providerId": "missing"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"], "provider_failed");
+}
+
+#[tokio::test]
+async fn embedding_model_is_recorded_on_first_upsert() {
+    std::env::set_var("DATA_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    let (embed_url, seen) = serve_embeddings();
+
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let static_dir = dir.path().join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "INDEX").unwrap();
+    let app = server_rs::build_app_with_data_dir(static_dir, data_dir.clone());
+
+    let (_, provider) = send(
+        &app,
+        "POST",
+        "/api/providers",
+        Some(serde_json::json!({
+            "name": "mock",
+            "provider": "siliconflow",
+            "baseUrl": embed_url,
+            "apiKey": "sk-test",
+            "defaultEmbeddingModel": "provider-default"
+        })),
+    )
+    .await;
+    let provider_id = provider["id"].as_str().unwrap().to_string();
+
+    for name in ["docs", "manual"] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/vector/collections/create",
+            Some(serde_json::json!({ "name": name, "dimension": 3, "distance": "cosine" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "docs",
+            "query": "cat",
+            "providerId": provider_id
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "embedding_model_missing");
+    assert!(seen.lock().unwrap().is_empty());
+
+    let (status, dataset) = send(
+        &app,
+        "POST",
+        "/api/datasets",
+        Some(serde_json::json!({
+            "name": "animals",
+            "rows": [
+                { "key": "a", "text": "cat" },
+                { "key": "b", "text": "dog" },
+                { "key": "c", "text": "fish" }
+            ]
+        })),
+    )
+    .await;
+    assert!(status.is_success(), "{dataset}");
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/jobs/embed-to-vector",
+        Some(serde_json::json!({
+            "datasetId": dataset["id"],
+            "providerId": provider_id,
+            "collection": "docs",
+            "idField": "key",
+            "textField": "text"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": "manual",
+            "points": [{ "id": "x", "vector": [1.0, 0.0, 0.0] }],
+            "embeddingModel": "mock-small"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+
+    let (status, json) = send(&app, "GET", "/api/vector/collections", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let models = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["name"].as_str().unwrap().to_string(),
+                c["embeddingModel"].as_str().unwrap_or("").to_string(),
+            )
+        })
+        .collect::<std::collections::HashMap<_, _>>();
+    assert_eq!(models["docs"], "provider-default");
+    assert_eq!(models["manual"], "mock-small");
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/search",
+        Some(serde_json::json!({
+            "collection": "docs",
+            "query": "kitten",
+            "providerId": provider_id,
+            "topK": 1
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{json}");
+    assert_eq!(hit_ids(&json), vec!["a"]);
+    assert_eq!(
+        seen.lock().unwrap().last().unwrap()["model"],
+        "provider-default"
+    );
+
+    let app = server_rs::build_app_with_data_dir(dir.path().join("static"), data_dir);
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/vector/points/upsert",
+        Some(serde_json::json!({
+            "collection": "manual",
+            "points": [{ "id": "y", "vector": [0.0, 1.0, 0.0] }],
+            "embeddingModel": "provider-default"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["message"], "embedding_model_mismatch");
+}